@@ -0,0 +1,292 @@
+// Model activity
+// Per-model spend, requests and tokens from OpenRouter's /api/v1/activity endpoint
+// (provisioning key only), cached locally and grouped per menubar timeframe.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::i18n::tr;
+use crate::{get_config_dir, key_fingerprint, read_settings, write_file_atomic};
+
+const ACTIVITY_CACHE_FILE: &str = "activity-cache.json";
+/// Activity is reported per completed day, so an hourly refresh is plenty
+const ACTIVITY_CACHE_TTL_SECS: i64 = 60 * 60;
+
+/// One row of /api/v1/activity: usage of one model (endpoint) on one UTC day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ActivityRecord {
+    pub date: String,
+    pub model: String,
+    #[serde(default)]
+    pub usage: f64,
+    #[serde(default)]
+    pub requests: u64,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub reasoning_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivityResponse {
+    data: Option<Vec<ActivityRecord>>,
+    error: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct ActivityCache {
+    fetched_at: i64,            // unix seconds
+    key_fingerprint: String,    // cache belongs to this provisioning key
+    records: Vec<ActivityRecord>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ModelUsage {
+    pub model: String,
+    pub usage: f64,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub reasoning_tokens: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DailyModelUsage {
+    pub date: String,
+    pub models: Vec<ModelUsage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModelBreakdown {
+    pub timeframe: String,
+    pub from: String,
+    pub to: String,
+    pub total_usage: f64,
+    pub total_requests: u64,
+    pub models: Vec<ModelUsage>,
+    pub days: Vec<DailyModelUsage>,
+    pub fetched_at: i64,
+}
+
+fn cache_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(ACTIVITY_CACHE_FILE))
+}
+
+fn read_cache() -> ActivityCache {
+    cache_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_cache(cache: &ActivityCache) -> Result<(), String> {
+    let path = cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| tr!("file.create_dir", dir.display(), e))?;
+    }
+    let contents = serde_json::to_string(cache)
+        .map_err(|e| tr!("file.write", path.display(), e))?;
+    write_file_atomic(&path, &contents)
+        .map_err(|e| tr!("file.write", path.display(), e))
+}
+
+/// The provisioning key from settings, or a helpful error
+pub(crate) fn configured_provisioning_key() -> Result<String, String> {
+    let key = read_settings()?.provisioning_key.trim().to_string();
+    if key.is_empty() {
+        return Err(tr!("key.no_provisioning"));
+    }
+    Ok(key)
+}
+
+async fn fetch_activity_remote(provisioning_key: &str) -> Result<Vec<ActivityRecord>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(20))
+        .build()
+        .map_err(|_| tr!("net.client"))?;
+
+    let response = client
+        .get("https://openrouter.ai/api/v1/activity")
+        .header("Authorization", format!("Bearer {}", provisioning_key))
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                tr!("net.timeout")
+            } else {
+                tr!("net.error", e)
+            }
+        })?;
+
+    let status = response.status();
+    if status == 401 || status == 403 {
+        return Err(tr!("activity.needs_provisioning"));
+    }
+    if !status.is_success() {
+        return Err(tr!("activity.status", status));
+    }
+
+    let body: ActivityResponse = response
+        .json()
+        .await
+        .map_err(|e| tr!("activity.parse", e))?;
+    if let Some(error) = body.error {
+        return Err(tr!("api.error", error));
+    }
+
+    // Dates may come with a time part; keep YYYY-MM-DD
+    Ok(body
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .map(|mut record| {
+            record.date.truncate(10);
+            record
+        })
+        .collect())
+}
+
+/// Cached activity records. The endpoint only covers the last 30 days, so fresh
+/// data replaces the days it covers and older cached days are kept.
+pub(crate) async fn load_activity(provisioning_key: &str, force_refresh: bool) -> Result<(Vec<ActivityRecord>, i64), String> {
+    let now = Utc::now().timestamp();
+    let fingerprint = key_fingerprint(provisioning_key);
+    let mut cache = read_cache();
+
+    let fresh = cache.key_fingerprint == fingerprint && now - cache.fetched_at < ACTIVITY_CACHE_TTL_SECS;
+    if fresh && !force_refresh {
+        return Ok((cache.records, cache.fetched_at));
+    }
+
+    let records = fetch_activity_remote(provisioning_key).await?;
+    if cache.key_fingerprint != fingerprint {
+        cache.records.clear();
+    }
+    cache.records.retain(|old| !records.iter().any(|new| new.date == old.date));
+    cache.records.extend(records);
+    cache.records.sort_by(|a, b| a.date.cmp(&b.date));
+    cache.fetched_at = now;
+    cache.key_fingerprint = fingerprint;
+    write_cache(&cache)?;
+
+    Ok((cache.records, cache.fetched_at))
+}
+
+/// First and last day of a timeframe in UTC. Activity only covers completed days, so every
+/// range ends yesterday: yesterday, the 7 days up to it, or its month so far.
+pub(crate) fn timeframe_range(timeframe: &str, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let last = today.pred_opt().unwrap_or(today);
+    let first = match timeframe {
+        "daily" => last,
+        "weekly" => last - chrono::Duration::days(6),
+        _ => last.with_day(1).unwrap_or(last),
+    };
+    (first, last)
+}
+
+fn add_record(models: &mut Vec<ModelUsage>, record: &ActivityRecord) {
+    let entry = match models.iter_mut().position(|m| m.model == record.model) {
+        Some(index) => &mut models[index],
+        None => {
+            models.push(ModelUsage { model: record.model.clone(), ..Default::default() });
+            models.last_mut().expect("just pushed")
+        }
+    };
+    entry.usage += record.usage;
+    entry.requests += record.requests;
+    entry.prompt_tokens += record.prompt_tokens;
+    entry.completion_tokens += record.completion_tokens;
+    entry.reasoning_tokens += record.reasoning_tokens;
+}
+
+fn sort_by_spend(models: &mut [ModelUsage]) {
+    models.sort_by(|a, b| b.usage.partial_cmp(&a.usage).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+/// Group records of one timeframe by model (top `top` by spend) and by day
+pub(crate) fn summarize(records: &[ActivityRecord], timeframe: &str, top: Option<usize>, fetched_at: i64) -> ModelBreakdown {
+    let (first, last) = timeframe_range(timeframe, Utc::now().date_naive());
+    let from = first.format("%Y-%m-%d").to_string();
+    let to = last.format("%Y-%m-%d").to_string();
+
+    let mut models = Vec::new();
+    let mut days: Vec<DailyModelUsage> = Vec::new();
+    for record in records.iter().filter(|r| r.date >= from && r.date <= to) {
+        add_record(&mut models, record);
+        match days.iter_mut().find(|d| d.date == record.date) {
+            Some(day) => add_record(&mut day.models, record),
+            None => {
+                let mut day = DailyModelUsage { date: record.date.clone(), models: Vec::new() };
+                add_record(&mut day.models, record);
+                days.push(day);
+            }
+        }
+    }
+
+    sort_by_spend(&mut models);
+    for day in days.iter_mut() {
+        sort_by_spend(&mut day.models);
+    }
+    days.sort_by(|a, b| a.date.cmp(&b.date));
+
+    let total_usage = models.iter().map(|m| m.usage).sum();
+    let total_requests = models.iter().map(|m| m.requests).sum();
+    if let Some(top) = top {
+        models.truncate(top);
+    }
+
+    ModelBreakdown {
+        timeframe: timeframe.to_string(),
+        from,
+        to,
+        total_usage,
+        total_requests,
+        models,
+        days,
+        fetched_at,
+    }
+}
+
+/// Spend per model for a timeframe (defaults to the menubar timeframe)
+#[tauri::command]
+pub async fn get_model_breakdown(
+    timeframe: Option<String>,
+    top: Option<usize>,
+    force_refresh: Option<bool>,
+) -> Result<ModelBreakdown, String> {
+    let timeframe = match timeframe {
+        Some(t) => t,
+        None => read_settings()?.menubar_timeframe,
+    };
+    let key = configured_provisioning_key()?;
+    let (records, fetched_at) = load_activity(&key, force_refresh.unwrap_or(false)).await?;
+    Ok(summarize(&records, &timeframe, top, fetched_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn timeframes_end_with_the_last_completed_day() {
+        // Monday 9 March: the weekly range still has a full week of data
+        assert_eq!(timeframe_range("daily", day(9)), (day(8), day(8)));
+        assert_eq!(timeframe_range("weekly", day(9)), (day(2), day(8)));
+        assert_eq!(timeframe_range("monthly", day(9)), (day(1), day(8)));
+        // On the 1st the month so far is the previous month
+        assert_eq!(timeframe_range("monthly", day(1)), (NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(), NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()));
+    }
+}
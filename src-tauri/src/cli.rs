@@ -0,0 +1,522 @@
+// Headless command line
+// `bp-esc cli <command>` runs the same backend code as the app without starting
+// the tray/UI, for terminals and scripts. Output is plain text or --json.
+
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+use crate::number_format::NumberFormat;
+use crate::{
+    activity, fetch_balance_internal, history, pricing, projects, read_settings, reports, save_settings_internal, team,
+    ApiKeyConfig, AppSettings, BalanceData,
+};
+
+const USAGE: &str = "\
+Usage: bp-esc cli <command> [options]
+
+Commands:
+  balance   Balance, pace, rate limit and tier of the active key
+            --key <label>                      another configured key instead
+            --json                             machine-readable output
+  models    Top models by spend per timeframe (needs a provisioning key)
+            --timeframe daily|weekly|monthly   only this timeframe (default: all three)
+            --top N                            models per timeframe (default: 5)
+            --refresh                          ignore the local activity cache
+            --json                             machine-readable output
+  estimate  Cost of a planned job against the remaining budget and pace
+            estimate <model> --prompt N --completion N [--requests N]
+            --key <label>                      compare against another configured key
+            --offline                          skip the balance, only price the job
+            --refresh                          reload the model price list
+            --json                             machine-readable output
+  team      Team member keys (needs a provisioning key)
+            team list [--active] [--json]      every key with limit, usage and pace
+            team create <name> [--limit USD]   prints the new key once
+            team limit <key> <USD|none>        change or remove a limit
+            team disable <key> | enable <key>
+            <key> is a hash, unique hash prefix or key name
+  project   Project tag that new usage is attributed to
+            project [--json]                   current tag and this month's totals per tag
+            project set <name> | clear
+            project detect [dir] [--quiet]     tag from the git repo containing dir (default: .)
+            project hook zsh|bash|fish         shell hook running `detect` on every cd
+  report    Monthly spend report from the local balance history
+            report [YYYY-MM]                   month (default: current)
+            --format md|csv|html|all           default: all
+            --out <dir>                        default: ~/Documents/BP-ESC Reports
+            --stdout                           print one format instead of writing files
+            --json                             the report data as JSON
+  help      Show this message
+";
+
+const TIMEFRAMES: [&str; 3] = ["daily", "weekly", "monthly"];
+
+/// Returns the exit code when the process was started as `bp-esc cli ...`
+pub(crate) fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some("cli") {
+        return None;
+    }
+    Some(run(&args[1..]))
+}
+
+fn run(args: &[String]) -> i32 {
+    let rest = args.get(1..).unwrap_or(&[]);
+    let result = match args.first().map(String::as_str) {
+        Some("balance") => tauri::async_runtime::block_on(cmd_balance(rest)),
+        Some("models") => tauri::async_runtime::block_on(cmd_models(rest)),
+        Some("estimate") => tauri::async_runtime::block_on(cmd_estimate(rest)),
+        Some("team") => tauri::async_runtime::block_on(cmd_team(rest)),
+        Some("project") => cmd_project(rest),
+        Some("report") => cmd_report(rest),
+        Some("help") | Some("--help") | Some("-h") | None => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        Some(other) => Err(format!("Unknown command '{}'. Run `bp-esc cli help`.", other)),
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+// ============================================================================
+// ARGUMENT HELPERS
+// ============================================================================
+
+fn has_flag(args: &[String], name: &str) -> bool {
+    args.iter().any(|a| a == name)
+}
+
+/// Value of `--name value` or `--name=value`
+fn option_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let prefix = format!("{}=", name);
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == name {
+            args.get(i + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix(&prefix)
+        }
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(args: &[String], name: &str, default: T) -> Result<T, String> {
+    match option_value(args, name) {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("{} expects a number, got '{}'", name, value)),
+        None => Ok(default),
+    }
+}
+
+/// Positional arguments (everything that is not an option or an option's value)
+fn positionals(args: &[String]) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut skip_next = false;
+    for arg in args {
+        if skip_next {
+            skip_next = false;
+        } else if matches!(arg.as_str(), "--limit" | "--prompt" | "--completion" | "--requests" | "--key" | "--format" | "--out") {
+            skip_next = true;
+        } else if !arg.starts_with("--") {
+            out.push(arg.as_str());
+        }
+    }
+    out
+}
+
+/// "none"/"off" removes a limit, anything else must be a dollar amount
+fn parse_limit(value: &str) -> Result<Option<f64>, String> {
+    match value {
+        "none" | "off" | "unlimited" => Ok(None),
+        v => v
+            .trim_start_matches('$')
+            .parse::<f64>()
+            .ok()
+            .filter(|l| l.is_finite() && *l >= 0.0)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid limit '{}' (a dollar amount or 'none')", value)),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize output: {}", e))?;
+    println!("{}", json);
+    Ok(())
+}
+
+// ============================================================================
+// FORMATTING
+// ============================================================================
+
+fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        t if t >= 1_000_000 => format!("{:.1}M", t as f64 / 1_000_000.0),
+        t if t >= 1_000 => format!("{:.1}k", t as f64 / 1_000.0),
+        t => t.to_string(),
+    }
+}
+
+/// Display format from the settings, read once per run
+fn number_format() -> &'static NumberFormat {
+    static FORMAT: OnceLock<NumberFormat> = OnceLock::new();
+    FORMAT.get_or_init(|| NumberFormat::from_settings(&read_settings().unwrap_or_default()))
+}
+
+fn money(value: f64) -> String {
+    number_format().money(value, 2)
+}
+
+fn format_money(value: Option<f64>) -> String {
+    value.map(money).unwrap_or_else(|| "-".to_string())
+}
+
+fn print_team_table(members: &[team::TeamMember]) {
+    println!(
+        "{:<24} {:<10} {:>10} {:>10} {:>10} {:>8}  STATUS",
+        "NAME", "HASH", "LIMIT", "MONTH", "REMAINING", "PACE"
+    );
+    for member in members {
+        let balance = &member.balance;
+        let pace = balance
+            .pace_month_delta_percent
+            .map(|d| format!("{:+.0}%", d))
+            .unwrap_or_else(|| "-".to_string());
+        let status = if member.disabled {
+            "disabled".to_string()
+        } else {
+            balance.pace_status.clone().unwrap_or_else(|| "-".to_string())
+        };
+        let name: String = member.name.chars().take(24).collect();
+        let hash: String = member.hash.chars().take(10).collect();
+        println!(
+            "{:<24} {:<10} {:>10} {:>10} {:>10} {:>8}  {}",
+            name,
+            hash,
+            format_money(balance.limit),
+            format_money(balance.usage_monthly),
+            format_money(balance.remaining_monthly),
+            pace,
+            status
+        );
+    }
+}
+
+fn print_member(member: &team::TeamMember, json: bool) -> Result<(), String> {
+    if json {
+        return print_json(member);
+    }
+    print_team_table(std::slice::from_ref(member));
+    Ok(())
+}
+
+fn print_balance(label: &str, balance: &BalanceData) {
+    println!("{}", label);
+    println!("  Limit       {}", format_money(balance.limit));
+    match balance.limit_reset.as_deref() {
+        Some("daily") => println!("  Used        {} today", format_money(balance.usage_period)),
+        Some("weekly") => println!("  Used        {} this week", format_money(balance.usage_period)),
+        _ => {}
+    }
+    println!("  Used        {} this month", format_money(balance.usage_monthly));
+    println!("  Remaining   {}", format_money(balance.remaining_monthly));
+    if let Some(account) = &balance.account {
+        let binding = if balance.binding_constraint.as_deref() == Some("account") { " (binding)" } else { "" };
+        println!("  Account     {} of {}{}", money(account.remaining), money(account.total_credits), binding);
+    }
+    if let Some(delta) = balance.pace_month_delta_percent {
+        println!(
+            "  Pace        {:+.0}% ({})",
+            delta,
+            balance.pace_status.as_deref().unwrap_or("-")
+        );
+    }
+    if let Some(reset) = &balance.limit_reset {
+        println!("  Resets      {}", reset);
+    }
+    if let Some(rate) = &balance.rate_limit {
+        println!("  Rate limit  {} requests / {}", rate.requests, rate.interval);
+    }
+    if balance.is_free_tier == Some(true) {
+        println!("  Free tier   yes (low rate limits, free models only until credits are purchased)");
+    }
+}
+
+fn timeframe_title(timeframe: &str) -> &'static str {
+    match timeframe {
+        "daily" => "Yesterday",
+        "weekly" => "Last 7 days",
+        _ => "Month to date",
+    }
+}
+
+// ============================================================================
+// COMMANDS
+// ============================================================================
+
+/// The key named by --key, or the active one
+fn selected_key<'a>(settings: &'a AppSettings, args: &[String]) -> Result<&'a ApiKeyConfig, String> {
+    match option_value(args, "--key") {
+        Some(label) => settings
+            .api_keys
+            .iter()
+            .find(|k| k.label.eq_ignore_ascii_case(label))
+            .ok_or_else(|| format!("No configured key labelled '{}'", label)),
+        None => settings
+            .api_keys
+            .get(settings.active_api_key_index)
+            .ok_or_else(|| "No API key configured.".to_string()),
+    }
+}
+
+async fn cmd_balance(args: &[String]) -> Result<(), String> {
+    let settings = read_settings()?;
+    let key = selected_key(&settings, args)?;
+
+    let balance = fetch_balance_internal(&key.key).await?;
+    if has_flag(args, "--json") {
+        return print_json(&balance);
+    }
+    print_balance(&key.label, &balance);
+    Ok(())
+}
+
+async fn cmd_models(args: &[String]) -> Result<(), String> {
+    let timeframes: Vec<&str> = match option_value(args, "--timeframe") {
+        Some(t) if TIMEFRAMES.contains(&t) => vec![t],
+        Some(t) => return Err(format!("Unknown timeframe '{}' (daily, weekly, monthly)", t)),
+        None => TIMEFRAMES.to_vec(),
+    };
+    let top: usize = parse_number(args, "--top", 5)?;
+
+    let key = activity::configured_provisioning_key()?;
+    let (records, fetched_at) = activity::load_activity(&key, has_flag(args, "--refresh")).await?;
+    let breakdowns: Vec<activity::ModelBreakdown> = timeframes
+        .iter()
+        .map(|t| activity::summarize(&records, t, Some(top), fetched_at))
+        .collect();
+
+    if has_flag(args, "--json") {
+        return print_json(&breakdowns);
+    }
+
+    for (i, breakdown) in breakdowns.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "{} ({} – {})  {} · {} requests",
+            timeframe_title(&breakdown.timeframe),
+            breakdown.from,
+            breakdown.to,
+            money(breakdown.total_usage),
+            breakdown.total_requests
+        );
+        if breakdown.models.is_empty() {
+            println!("  no activity");
+        }
+        for (rank, model) in breakdown.models.iter().enumerate() {
+            println!(
+                "  {:>2}. {:<45} {:>10} {:>7} req {:>8} tok",
+                rank + 1,
+                model.model,
+                money(model.usage),
+                model.requests,
+                format_tokens(model.prompt_tokens + model.completion_tokens + model.reasoning_tokens)
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn cmd_estimate(args: &[String]) -> Result<(), String> {
+    let model = match positionals(args).as_slice() {
+        [model] => model.to_string(),
+        _ => return Err("Usage: bp-esc cli estimate <model> --prompt N --completion N [--requests N]".to_string()),
+    };
+    let prompt: u64 = parse_number(args, "--prompt", 0)?;
+    let completion: u64 = parse_number(args, "--completion", 0)?;
+    let requests: u64 = parse_number(args, "--requests", 1)?;
+
+    let balance = if has_flag(args, "--offline") {
+        None
+    } else {
+        let settings = read_settings()?;
+        let key = selected_key(&settings, args)?;
+        match fetch_balance_internal(&key.key).await {
+            Ok(balance) => Some(balance),
+            Err(e) => {
+                eprintln!("warning: balance unavailable ({}), estimating cost only", e);
+                None
+            }
+        }
+    };
+
+    let estimate =
+        pricing::estimate_job(&model, prompt, completion, requests, balance.as_ref(), has_flag(args, "--refresh")).await?;
+    if has_flag(args, "--json") {
+        return print_json(&estimate);
+    }
+
+    println!("{} ({})", estimate.model_name, estimate.model);
+    println!(
+        "  Job         {} × {} prompt + {} completion tokens",
+        estimate.requests,
+        format_tokens(estimate.prompt_tokens),
+        format_tokens(estimate.completion_tokens)
+    );
+    println!("  Per request {}", number_format().money(estimate.cost_per_request, 4));
+    println!("  Total       {}", money(estimate.total_cost));
+    if let Some(remaining) = estimate.remaining_monthly {
+        println!("  Remaining   {} → {}", money(remaining), format_money(estimate.remaining_after));
+    }
+    if let (Some(before), Some(after)) = (estimate.pace_delta_before, estimate.pace_delta_after) {
+        println!(
+            "  Pace        {:+.0}% → {:+.0}% ({})",
+            before,
+            after,
+            estimate.pace_status_after.as_deref().unwrap_or("-")
+        );
+    }
+    if estimate.price_source == pricing::PriceSource::Bundled {
+        println!("  Prices      bundled snapshot (price list unavailable)");
+    }
+    println!();
+    println!("{}", estimate.summary);
+    Ok(())
+}
+
+async fn cmd_team(args: &[String]) -> Result<(), String> {
+    let json = has_flag(args, "--json");
+    let positional = positionals(args);
+
+    match positional.as_slice() {
+        [] | ["list"] => {
+            let members = team::list_members(!has_flag(args, "--active")).await?;
+            if json {
+                return print_json(&members);
+            }
+            if members.is_empty() {
+                println!("No team keys.");
+            } else {
+                print_team_table(&members);
+            }
+            Ok(())
+        }
+        ["create", name] => {
+            let limit = option_value(args, "--limit").map(parse_limit).transpose()?.flatten();
+            let created = team::create_member_key(name, limit).await?;
+            if json {
+                return print_json(&created);
+            }
+            print_team_table(std::slice::from_ref(&created.member));
+            println!();
+            println!("New key (shown only once): {}", created.key);
+            Ok(())
+        }
+        ["limit", query, value] => {
+            let limit = parse_limit(value)?;
+            let members = team::list_members(true).await?;
+            let hash = team::resolve_member(&members, query)?.hash.clone();
+            print_member(&team::set_member_limit(&hash, limit).await?, json)
+        }
+        [action @ ("disable" | "enable"), query] => {
+            let members = team::list_members(true).await?;
+            let hash = team::resolve_member(&members, query)?.hash.clone();
+            print_member(&team::set_member_disabled(&hash, *action == "disable").await?, json)
+        }
+        _ => Err("Unknown team command. Run `bp-esc cli help`.".to_string()),
+    }
+}
+
+fn cmd_project(args: &[String]) -> Result<(), String> {
+    let positional = positionals(args);
+    let quiet = has_flag(args, "--quiet");
+    let mut settings = read_settings()?;
+
+    let project = match positional.as_slice() {
+        [] | ["show"] => {
+            let spend = history::project_totals(&history::current_month());
+            if has_flag(args, "--json") {
+                return print_json(&serde_json::json!({ "current_project": settings.current_project, "month": spend }));
+            }
+            println!("Current project: {}", settings.current_project.as_deref().unwrap_or("none"));
+            println!("{} by project (from balance samples)", spend.month);
+            for total in &spend.by_project {
+                println!("  {:<32} {}", total.name, money(total.cost));
+            }
+            println!("  {:<32} {}", "(no project)", money(spend.untagged_cost));
+            return Ok(());
+        }
+        ["hook", shell] => {
+            let exe = std::env::current_exe().map_err(|e| format!("Could not locate bp-esc: {}", e))?;
+            print!("{}", projects::hook_snippet(shell, &exe.to_string_lossy())?);
+            return Ok(());
+        }
+        ["set", name] => Some(name.to_string()),
+        ["clear"] => None,
+        ["detect", rest @ ..] if rest.len() <= 1 => {
+            let dir = match rest.first() {
+                Some(dir) => std::path::PathBuf::from(dir),
+                None => std::env::current_dir().map_err(|e| format!("Could not read the working directory: {}", e))?,
+            };
+            // Outside a repo the tag stays as it is (e.g. while in ~/Downloads)
+            let Some(name) = projects::detect_git_project(&dir) else {
+                if !quiet {
+                    println!("No git repository at {}; project unchanged.", dir.display());
+                }
+                return Ok(());
+            };
+            Some(name)
+        }
+        _ => return Err("Usage: project [show|set <name>|clear|detect [dir]|hook <shell>]".to_string()),
+    };
+
+    if projects::select_project(&mut settings, project.as_deref())? {
+        save_settings_internal(&settings)?;
+    }
+    if !quiet {
+        println!("Current project: {}", settings.current_project.as_deref().unwrap_or("none"));
+    }
+    Ok(())
+}
+
+fn cmd_report(args: &[String]) -> Result<(), String> {
+    let month = match positionals(args).as_slice() {
+        [] => history::current_month(),
+        [month] => month.to_string(),
+        _ => return Err("Usage: report [YYYY-MM] [--format md|csv|html|all] [--out DIR] [--stdout]".to_string()),
+    };
+    let report = reports::generate(&month)?;
+    if has_flag(args, "--json") {
+        return print_json(&report);
+    }
+
+    let formats: Vec<&str> = match option_value(args, "--format") {
+        None | Some("all") => reports::FORMATS.to_vec(),
+        Some(format) => vec![format],
+    };
+    if has_flag(args, "--stdout") {
+        let [format] = formats.as_slice() else {
+            return Err("--stdout needs a single --format".to_string());
+        };
+        print!("{}", reports::render(&report, format)?);
+        return Ok(());
+    }
+
+    let dir = match option_value(args, "--out") {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => reports::default_dir()?,
+    };
+    for file in reports::write_files(&report, &dir, &formats)? {
+        println!("{}", file.display());
+    }
+    Ok(())
+}
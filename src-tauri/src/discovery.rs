@@ -0,0 +1,650 @@
+// Key discovery
+// Scans well-known configuration files of local AI tools for OpenRouter keys so
+// they can be imported without copy/paste. Nothing is imported automatically.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::i18n::tr;
+use crate::opencode::{extract_openrouter_key_from_value, opencode_auth_path_in};
+use crate::{
+    key_fingerprint, mask_key, read_settings, save_settings_internal, validate_api_key,
+    verify_key_live, AppSettings, ApiKeyConfig,
+};
+
+/// Generic config files larger than this are skipped during the ~/.config sweep
+const MAX_SCAN_FILE_BYTES: u64 = 256 * 1024;
+
+/// Variable/field names that carry an OpenRouter key in env files and generic configs
+const KEY_NAMES: [&str; 3] = ["OPENROUTER_API_KEY", "openrouter_api_key", "openrouter-api-key"];
+
+/// A key together with the file it was found in
+type Hit = (String, PathBuf);
+
+type Scanner = fn(&Path) -> Vec<Hit>;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KeySource {
+    pub tool: String,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DiscoveredKey {
+    pub fingerprint: String,
+    pub masked_key: String,
+    pub sources: Vec<KeySource>,
+    pub already_configured: bool,
+    pub existing_label: Option<String>,
+    pub suggested_label: String,
+}
+
+// ============================================================================
+// VALUE HELPERS
+// ============================================================================
+
+/// Strip quotes and trailing comments from a shell/YAML/TOML scalar
+fn clean_scalar(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let value = if let Some(rest) = raw.strip_prefix('"') {
+        rest.split('"').next().unwrap_or("")
+    } else if let Some(rest) = raw.strip_prefix('\'') {
+        rest.split('\'').next().unwrap_or("")
+    } else {
+        raw.split_whitespace().next().unwrap_or("")
+    };
+
+    let value = value.trim();
+    // References like $OTHER_VAR or ${...} are not literal keys
+    if value.is_empty() || value.contains('$') || validate_api_key(value).is_err() {
+        return None;
+    }
+    Some(value.to_string())
+}
+
+/// Match `NAME=value`, `NAME: value` or `NAME = value` for any of the key names
+fn value_after_key_name(line: &str) -> Option<String> {
+    for name in KEY_NAMES {
+        if let Some(rest) = line.strip_prefix(name) {
+            let rest = rest.trim_start();
+            if let Some(value) = rest.strip_prefix('=').or_else(|| rest.strip_prefix(':')) {
+                return clean_scalar(value);
+            }
+        }
+    }
+    None
+}
+
+/// Drop // and /* */ comments plus trailing commas so JSONC (Zed) parses as JSON
+fn strip_jsonc(contents: &str) -> String {
+    let mut out = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            if c == '\\' {
+                if let Some(escaped) = chars.next() {
+                    out.push(escaped);
+                }
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for next in chars.by_ref() {
+                    if prev == '*' && next == '/' {
+                        break;
+                    }
+                    prev = next;
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+
+    // Trailing commas: drop a comma when the next non-whitespace char closes a block
+    let mut result = String::with_capacity(out.len());
+    let chars: Vec<char> = out.chars().collect();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if in_string {
+            if c == '\\' && i + 1 < chars.len() {
+                result.push(c);
+                result.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = chars[i + 1..].iter().find(|ch| !ch.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                i += 1;
+                continue;
+            }
+        }
+        result.push(c);
+        i += 1;
+    }
+    result
+}
+
+fn read_json_file(path: &Path, jsonc: bool) -> Option<Value> {
+    let contents = fs::read_to_string(path).ok()?;
+    if jsonc {
+        serde_json::from_str(&strip_jsonc(&contents)).ok()
+    } else {
+        serde_json::from_str(&contents).ok()
+    }
+}
+
+// ============================================================================
+// PER-TOOL PARSERS
+// ============================================================================
+
+/// OpenCode: auth.json entry, or provider.openrouter.options.apiKey in opencode.json
+fn scan_opencode(home: &Path) -> Vec<Hit> {
+    let mut found = Vec::new();
+
+    let auth_path = opencode_auth_path_in(home);
+    if let Some(key) = read_json_file(&auth_path, false)
+        .and_then(|json| extract_openrouter_key_from_value(&json))
+    {
+        found.push((key, auth_path));
+    }
+
+    for name in ["opencode.json", "opencode.jsonc"] {
+        let path = home.join(".config").join("opencode").join(name);
+        let key = read_json_file(&path, true).and_then(|json| {
+            json.pointer("/provider/openrouter/options/apiKey")
+                .and_then(|v| v.as_str())
+                .and_then(clean_scalar)
+        });
+        if let Some(key) = key {
+            found.push((key, path));
+        }
+    }
+
+    found
+}
+
+/// aider: openrouter-api-key, `api-key: [openrouter=...]` and `set-env: [OPENROUTER_API_KEY=...]`
+fn scan_aider(home: &Path) -> Vec<Hit> {
+    let path = home.join(".aider.conf.yml");
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+
+    let mut found = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        // List items under api-key:/set-env: as well as inline values
+        let item = line.strip_prefix("- ").unwrap_or(line).trim();
+        let item = item.trim_matches(|c| c == '"' || c == '\'');
+        let key = value_after_key_name(item)
+            .or_else(|| item.strip_prefix("openrouter=").and_then(clean_scalar))
+            .or_else(|| {
+                item.strip_prefix("api-key:")
+                    .map(str::trim)
+                    .and_then(|v| v.strip_prefix("openrouter="))
+                    .and_then(clean_scalar)
+            });
+        if let Some(key) = key {
+            found.push((key, path.clone()));
+        }
+    }
+    found
+}
+
+/// Continue: models with provider "openrouter" in config.json or config.yaml
+fn scan_continue(home: &Path) -> Vec<Hit> {
+    let dir = home.join(".continue");
+    let mut found = Vec::new();
+
+    let json_path = dir.join("config.json");
+    if let Some(json) = read_json_file(&json_path, true) {
+        if let Some(models) = json.get("models").and_then(|m| m.as_array()) {
+            for model in models {
+                let is_openrouter = model.get("provider").and_then(|p| p.as_str()) == Some("openrouter");
+                if let (true, Some(key)) = (
+                    is_openrouter,
+                    model.get("apiKey").and_then(|k| k.as_str()).and_then(clean_scalar),
+                ) {
+                    found.push((key, json_path.clone()));
+                }
+            }
+        }
+    }
+
+    // YAML config: track the provider of the current list item and pick up its apiKey
+    let yaml_path = dir.join("config.yaml");
+    if let Ok(contents) = fs::read_to_string(&yaml_path) {
+        let mut provider: Option<String> = None;
+        let mut pending_key: Option<String> = None;
+        for line in contents.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("- ") {
+                if let (Some("openrouter"), Some(key)) = (provider.as_deref(), pending_key.take()) {
+                    found.push((key, yaml_path.clone()));
+                }
+                provider = None;
+                pending_key = None;
+            }
+            let field = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+            if let Some(value) = field.strip_prefix("provider:") {
+                provider = Some(value.trim().trim_matches(|c| c == '"' || c == '\'').to_string());
+            } else if let Some(value) = field.strip_prefix("apiKey:") {
+                pending_key = clean_scalar(value);
+            }
+        }
+        if let (Some("openrouter"), Some(key)) = (provider.as_deref(), pending_key) {
+            found.push((key, yaml_path));
+        }
+    }
+
+    found
+}
+
+/// Zed: language_models.openrouter.api_key (settings.json is JSONC)
+fn scan_zed(home: &Path) -> Vec<Hit> {
+    let path = home.join(".config").join("zed").join("settings.json");
+    let key = read_json_file(&path, true).and_then(|json| {
+        ["/language_models/openrouter/api_key", "/language_models/openrouter/apiKey"]
+            .iter()
+            .find_map(|ptr| json.pointer(ptr).and_then(|v| v.as_str()).and_then(clean_scalar))
+    });
+    key.map(|k| vec![(k, path)]).unwrap_or_default()
+}
+
+/// Shell profiles and env files: `export OPENROUTER_API_KEY=...` and fish `set -gx`
+fn scan_shell(home: &Path) -> Vec<Hit> {
+    let files = [
+        home.join(".env"),
+        home.join(".envrc"),
+        home.join(".zshrc"),
+        home.join(".zprofile"),
+        home.join(".zshenv"),
+        home.join(".bashrc"),
+        home.join(".bash_profile"),
+        home.join(".profile"),
+        home.join(".config").join("fish").join("config.fish"),
+    ];
+
+    let mut found = Vec::new();
+    for path in files {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+            let key = if let Some(rest) = line.strip_prefix("set ") {
+                // fish: set -gx OPENROUTER_API_KEY value
+                let mut parts = rest.split_whitespace().skip_while(|p| p.starts_with('-'));
+                match (parts.next(), parts.next()) {
+                    (Some("OPENROUTER_API_KEY"), Some(value)) => clean_scalar(value),
+                    _ => None,
+                }
+            } else {
+                value_after_key_name(line)
+            };
+            if let Some(key) = key {
+                found.push((key, path.clone()));
+            }
+        }
+    }
+    found
+}
+
+/// Remaining TOML/YAML/env files under ~/.config (two levels deep)
+fn scan_generic_configs(home: &Path) -> Vec<Hit> {
+    fn visit(dir: &Path, depth: u32, out: &mut Vec<Hit>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                let name = entry.file_name();
+                // Our own config and the tools with dedicated parsers are skipped
+                let skip = ["bpesc-balance", "opencode", "zed", "fish"]
+                    .iter()
+                    .any(|s| name == *s);
+                if depth < 2 && !skip {
+                    visit(&path, depth + 1, out);
+                }
+                continue;
+            }
+            let is_config = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| matches!(e, "toml" | "yaml" | "yml" | "env"))
+                .unwrap_or(false);
+            if !is_config || meta.len() > MAX_SCAN_FILE_BYTES {
+                continue;
+            }
+            let Ok(contents) = fs::read_to_string(&path) else {
+                continue;
+            };
+            for line in contents.lines() {
+                let line = line.trim();
+                let line = line.strip_prefix("export ").unwrap_or(line);
+                if let Some(key) = value_after_key_name(line) {
+                    out.push((key, path.clone()));
+                }
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    visit(&home.join(".config"), 1, &mut found);
+    found
+}
+
+// ============================================================================
+// DISCOVERY
+// ============================================================================
+
+/// Scan every known location and merge the hits per key
+fn scan_all() -> Result<Vec<(String, Vec<KeySource>)>, String> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| tr!("file.no_home"))?;
+    Ok(scan_home(&home))
+}
+
+fn scan_home(home: &Path) -> Vec<(String, Vec<KeySource>)> {
+    let scanners: [(&str, Scanner); 6] = [
+        ("OpenCode", scan_opencode),
+        ("aider", scan_aider),
+        ("Continue", scan_continue),
+        ("Zed", scan_zed),
+        ("Shell", scan_shell),
+        ("Config", scan_generic_configs),
+    ];
+
+    let mut merged: Vec<(String, Vec<KeySource>)> = Vec::new();
+    for (tool, scan) in scanners {
+        for (key, path) in scan(home) {
+            let source = KeySource {
+                tool: tool.to_string(),
+                path: path.display().to_string().replacen(&home.display().to_string(), "~", 1),
+            };
+            match merged.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, sources)) => {
+                    if !sources.iter().any(|s| s.path == source.path) {
+                        sources.push(source);
+                    }
+                }
+                None => merged.push((key, vec![source])),
+            }
+        }
+    }
+    merged
+}
+
+/// Label for an imported key, unique among the configured labels
+fn unique_label(base: &str, settings: &AppSettings) -> String {
+    let taken = |label: &str| settings.api_keys.iter().any(|k| k.label == label);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} {}", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| base.to_string())
+}
+
+fn describe(key: &str, sources: Vec<KeySource>, settings: &AppSettings) -> DiscoveredKey {
+    let existing = settings.api_keys.iter().find(|k| k.key == key);
+    let base = sources.first().map(|s| s.tool.clone()).unwrap_or_else(|| "Imported".to_string());
+    DiscoveredKey {
+        fingerprint: key_fingerprint(key),
+        masked_key: mask_key(key),
+        sources,
+        already_configured: existing.is_some(),
+        existing_label: existing.map(|k| k.label.clone()),
+        suggested_label: unique_label(&base, settings),
+    }
+}
+
+/// List OpenRouter keys found in local tool configs (keys themselves stay in the backend)
+#[tauri::command]
+pub fn discover_api_keys() -> Result<Vec<DiscoveredKey>, String> {
+    let settings = read_settings()?;
+    Ok(scan_all()?
+        .into_iter()
+        .map(|(key, sources)| describe(&key, sources, &settings))
+        .collect())
+}
+
+/// Keys that could not be imported, with the verification error
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportFailure {
+    pub masked_key: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportResult {
+    pub settings: AppSettings,
+    pub imported: usize,
+    pub failed: Vec<ImportFailure>,
+}
+
+/// Verify and import the selected discoveries (by fingerprint); the active key is left unchanged
+#[tauri::command]
+pub async fn import_discovered_keys(app: AppHandle, fingerprints: Vec<String>) -> Result<ImportResult, String> {
+    let mut settings = read_settings()?;
+    let mut imported = 0;
+    let mut failed = Vec::new();
+
+    for (key, sources) in scan_all()? {
+        if !fingerprints.contains(&key_fingerprint(&key)) {
+            continue;
+        }
+        if settings.api_keys.iter().any(|k| k.key == key) {
+            continue;
+        }
+        // Stale keys in old dotfiles are common; only working keys are imported
+        let verification = match verify_key_live(&app, &key).await {
+            Ok(verification) => verification,
+            Err(error) => {
+                failed.push(ImportFailure { masked_key: mask_key(&key), error });
+                continue;
+            }
+        };
+        let label = describe(&key, sources, &settings).suggested_label;
+        let mut cfg = ApiKeyConfig::new(key, label);
+        cfg.verified = Some(verification);
+        settings.api_keys.push(cfg);
+        imported += 1;
+    }
+
+    if imported > 0 {
+        save_settings_internal(&settings)?;
+        let _ = app.emit("settings-updated", &settings);
+    }
+    Ok(ImportResult { settings, imported, failed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_A: &str = "sk-or-v1-aaaaaaaaaaaaaaaaaaaaaaaa";
+    const KEY_B: &str = "sk-or-v1-bbbbbbbbbbbbbbbbbbbbbbbb";
+    const KEY_C: &str = "sk-or-v1-cccccccccccccccccccccccc";
+
+    /// Throwaway home directory holding the given files
+    fn fake_home(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let home = std::env::temp_dir().join(format!("bpesc-discovery-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        for (path, contents) in files {
+            let path = home.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        home
+    }
+
+    fn keys(hits: Vec<Hit>) -> Vec<String> {
+        hits.into_iter().map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn strips_jsonc_comments_and_trailing_commas() {
+        let jsonc = r#"// Zed settings
+{
+  "theme": "One Dark", // trailing comment
+  /* block
+     comment */
+  "url": "https://openrouter.ai//api/v1",
+  "quote": "a \"// not a comment\", ",
+  "list": [1, 2,],
+}"#;
+        let value: Value = serde_json::from_str(&strip_jsonc(jsonc)).unwrap();
+        assert_eq!(value["theme"], "One Dark");
+        assert_eq!(value["url"], "https://openrouter.ai//api/v1");
+        assert_eq!(value["quote"], "a \"// not a comment\", ");
+        assert_eq!(value["list"], serde_json::json!([1, 2]));
+    }
+
+    #[test]
+    fn scans_opencode_auth_and_jsonc_config() {
+        let home = fake_home("opencode", &[
+            (".local/share/opencode/auth.json", &format!(r#"{{"anthropic": {{"type": "api", "key": "sk-ant-REDACTED"}}, "openrouter": {{"type": "api", "key": "{}"}}}}"#, KEY_A)),
+            (".config/opencode/opencode.jsonc", &format!("{{\n  // keys\n  \"provider\": {{ \"openrouter\": {{ \"options\": {{ \"apiKey\": \"{}\", }}, }}, }},\n}}", KEY_B)),
+            (".config/opencode/opencode.json", r#"{"provider": {"openrouter": {"options": {"apiKey": "{env:OPENROUTER_API_KEY}"}}}}"#),
+        ]);
+        assert_eq!(keys(scan_opencode(&home)), [KEY_A, KEY_B]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn scans_aider_list_and_inline_forms() {
+        let home = fake_home("aider", &[(".aider.conf.yml", &format!(
+            "# openrouter-api-key: {c}\nopenrouter-api-key: {a}\napi-key:\n  - anthropic=sk-ant-REDACTED\n  - \"openrouter={b}\"\nset-env:\n  - OPENROUTER_API_KEY={c}\napi-key: openrouter={a}\n",
+            a = KEY_A, b = KEY_B, c = KEY_C,
+        ))]);
+        assert_eq!(keys(scan_aider(&home)), [KEY_A, KEY_B, KEY_C, KEY_A]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn scans_continue_json_and_yaml_models() {
+        let home = fake_home("continue", &[
+            (".continue/config.json", &format!(r#"{{"models": [{{"provider": "anthropic", "apiKey": "sk-ant-REDACTED"}}, {{"provider": "openrouter", "apiKey": "{}"}}]}}"#, KEY_A)),
+            (".continue/config.yaml", &format!(
+                "models:\n  - name: Claude\n    provider: openrouter\n    apiKey: \"{b}\"\n  - name: Direct\n    provider: anthropic\n    apiKey: sk-ant-REDACTED\n  - apiKey: {c}\n    provider: 'openrouter'\n",
+                b = KEY_B, c = KEY_C,
+            )),
+        ]);
+        assert_eq!(keys(scan_continue(&home)), [KEY_A, KEY_B, KEY_C]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn scans_zed_jsonc_settings() {
+        let home = fake_home("zed", &[(".config/zed/settings.json", &format!(
+            "// Zed\n{{\n  \"language_models\": {{\n    \"openrouter\": {{ \"api_key\": \"{}\", }}, // mine\n  }},\n}}\n",
+            KEY_A,
+        ))]);
+        assert_eq!(keys(scan_zed(&home)), [KEY_A]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn scans_shell_exports_and_skips_references() {
+        let home = fake_home("shell", &[
+            (".zshrc", &format!(
+                "# export OPENROUTER_API_KEY={c}\nexport OPENROUTER_API_KEY=\"{a}\"\nexport OPENROUTER_API_KEY=$OPENROUTER_API_KEY\nOPENROUTER_API_KEY=${{OTHER_KEY}}\n",
+                a = KEY_A, c = KEY_C,
+            )),
+            (".env", &format!("OPENROUTER_API_KEY='{}' # work\n", KEY_B)),
+            (".config/fish/config.fish", &format!("set -gx OPENROUTER_API_KEY {}\n", KEY_C)),
+        ]);
+        assert_eq!(keys(scan_shell(&home)), [KEY_B, KEY_A, KEY_C]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn scans_generic_configs_two_levels_deep() {
+        let home = fake_home("generic", &[
+            (".config/tool/config.toml", &format!("openrouter_api_key = \"{}\"\n", KEY_A)),
+            (".config/tool.env", &format!("export OPENROUTER_API_KEY={}\n", KEY_B)),
+            (".config/other/settings.yaml", "openrouter-api-key: $OPENROUTER_API_KEY\n"),
+            (".config/other/settings.json", &format!("{{\"openrouter_api_key\": \"{}\"}}\n", KEY_C)),
+            (".config/zed/keys.toml", &format!("openrouter_api_key = \"{}\"\n", KEY_C)),
+            (".config/a/b/deep.toml", &format!("openrouter_api_key = \"{}\"\n", KEY_C)),
+        ]);
+        let mut found = keys(scan_generic_configs(&home));
+        found.sort();
+        assert_eq!(found, [KEY_A, KEY_B]);
+        fs::remove_dir_all(home).unwrap();
+    }
+
+    #[test]
+    fn merges_sources_and_flags_configured_keys() {
+        let home = fake_home("merge", &[
+            (".zshrc", &format!("export OPENROUTER_API_KEY={}\n", KEY_A)),
+            (".env", &format!("OPENROUTER_API_KEY={}\n", KEY_A)),
+            (".config/zed/settings.json", &format!(r#"{{"language_models": {{"openrouter": {{"api_key": "{}"}}}}}}"#, KEY_B)),
+        ]);
+        let merged = scan_home(&home);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].0, KEY_B);
+        assert_eq!(merged[1].0, KEY_A);
+        let paths: Vec<&str> = merged[1].1.iter().map(|s| s.path.as_str()).collect();
+        assert!(paths.iter().all(|p| p.starts_with("~/")), "{:?}", paths);
+        assert_eq!(paths.len(), 2);
+
+        let mut settings = AppSettings::default();
+        settings.api_keys.push(ApiKeyConfig::new(KEY_A.to_string(), "Shell".to_string()));
+        let found: Vec<DiscoveredKey> = merged
+            .into_iter()
+            .map(|(key, sources)| describe(&key, sources, &settings))
+            .collect();
+        assert!(!found[0].already_configured);
+        assert_eq!(found[0].suggested_label, "Zed");
+        assert!(found[1].already_configured);
+        assert_eq!(found[1].existing_label.as_deref(), Some("Shell"));
+        assert_eq!(found[1].suggested_label, "Shell 2");
+        fs::remove_dir_all(home).unwrap();
+    }
+}
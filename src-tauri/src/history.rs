@@ -0,0 +1,409 @@
+// Local spend history
+// Append-only JSON lines under ~/.config/bpesc-balance/history/, one file per month
+// (YYYY-MM.jsonl). Holds requests seen by the metering proxy and balance samples from
+// every fresh fetch; usage between two samples is attributed to the project tag that
+// was active, which gives per-project totals without the proxy.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::get_config_dir;
+use crate::i18n::tr;
+
+const HISTORY_DIR: &str = "history";
+
+/// One line of a history file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryEntry {
+    /// A request that went through the metering proxy
+    Request(RequestRecord),
+    /// Key usage at a fetch, tagged with the project active at that moment
+    Sample(BalanceSample),
+}
+
+impl HistoryEntry {
+    fn timestamp(&self) -> &str {
+        match self {
+            HistoryEntry::Request(record) => &record.timestamp,
+            HistoryEntry::Sample(sample) => &sample.timestamp,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BalanceSample {
+    pub timestamp: String, // RFC 3339, UTC
+    pub key_fingerprint: String,
+    /// Lifetime usage of the key (never resets, so deltas survive month boundaries)
+    pub usage: f64,
+    /// Monthly budget of the binding constraint at that moment
+    #[serde(default)]
+    pub budget: Option<f64>,
+    #[serde(default)]
+    pub project: Option<String>,
+}
+
+/// Usage between two consecutive samples of the same key
+pub(crate) struct UsageDelta<'a> {
+    pub previous: &'a BalanceSample,
+    pub sample: &'a BalanceSample,
+    pub delta: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RequestRecord {
+    pub timestamp: String, // RFC 3339, UTC
+    pub key_fingerprint: String,
+    pub client: String,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub generation_id: Option<String>,
+    pub path: String,
+    pub status: u16,
+    #[serde(default)]
+    pub prompt_tokens: u64,
+    #[serde(default)]
+    pub completion_tokens: u64,
+    /// USD as reported by OpenRouter (or estimated from the price list when missing)
+    #[serde(default)]
+    pub cost: f64,
+    #[serde(default)]
+    pub cost_estimated: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SpendTotal {
+    pub name: String,
+    pub cost: f64,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+/// Usage attributed to project tags from balance samples
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ProjectSpend {
+    pub month: String,
+    pub total_cost: f64,
+    /// Usage between samples while no project was selected
+    pub untagged_cost: f64,
+    pub by_project: Vec<ProjectTotal>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ProjectTotal {
+    pub name: String,
+    pub cost: f64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ProxySpend {
+    pub month: String,
+    pub total_cost: f64,
+    pub total_requests: u64,
+    pub by_client: Vec<SpendTotal>,
+    pub by_project: Vec<SpendTotal>,
+    pub by_model: Vec<SpendTotal>,
+}
+
+fn history_dir() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(HISTORY_DIR))
+}
+
+/// "YYYY-MM" of now, in UTC like the record timestamps
+pub(crate) fn current_month() -> String {
+    Utc::now().format("%Y-%m").to_string()
+}
+
+/// Months from callers become file names; only `YYYY-MM` gets that far
+fn check_month(month: &str) -> Result<(), String> {
+    let digits = month.char_indices().all(|(i, c)| if i == 4 { c == '-' } else { c.is_ascii_digit() });
+    if month.len() == 7 && digits && NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_ok() {
+        Ok(())
+    } else {
+        Err(tr!("report.invalid_month", month))
+    }
+}
+
+fn month_file(dir: &Path, month: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", month))
+}
+
+/// Append one entry to the month file of its timestamp inside `dir`
+pub(crate) fn append_to(dir: &Path, entry: &HistoryEntry) -> Result<(), String> {
+    let month = entry.timestamp().get(..7).unwrap_or_default().to_string();
+    let month = if month.len() == 7 { month } else { current_month() };
+
+    fs::create_dir_all(dir).map_err(|e| tr!("file.create_dir", dir.display(), e))?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+        .map_err(|e| tr!("file.permissions", dir.display(), e))?;
+
+    let path = month_file(dir, &month);
+    let line = serde_json::to_string(entry)
+        .map_err(|e| tr!("file.write", path.display(), e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(&path)
+        .map_err(|e| tr!("file.write", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| tr!("file.write", path.display(), e))?;
+    if matches!(entry, HistoryEntry::Sample(_)) {
+        SAMPLES_APPENDED.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Bumped for every balance sample appended by this process; series built from the
+/// samples (the sparkline) are cached until it moves
+static SAMPLES_APPENDED: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn samples_appended() -> u64 {
+    SAMPLES_APPENDED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn append(entry: &HistoryEntry) -> Result<(), String> {
+    append_to(&history_dir()?, entry)
+}
+
+/// Entries of one month; unreadable lines (e.g. from a newer version) are skipped
+pub(crate) fn read_month_from(dir: &Path, month: &str) -> Vec<HistoryEntry> {
+    fs::read_to_string(month_file(dir, month))
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub(crate) fn read_month(month: &str) -> Vec<HistoryEntry> {
+    history_dir().map(|dir| read_month_from(&dir, month)).unwrap_or_default()
+}
+
+fn requests(entries: &[HistoryEntry]) -> impl Iterator<Item = &RequestRecord> {
+    entries.iter().filter_map(|entry| match entry {
+        HistoryEntry::Request(record) => Some(record),
+        HistoryEntry::Sample(_) => None,
+    })
+}
+
+pub(crate) fn samples(entries: &[HistoryEntry]) -> impl Iterator<Item = &BalanceSample> {
+    entries.iter().filter_map(|entry| match entry {
+        HistoryEntry::Sample(sample) => Some(sample),
+        HistoryEntry::Request(_) => None,
+    })
+}
+
+/// Proxy spend of one project this month, across all keys
+pub(crate) fn project_spend(project: &str) -> f64 {
+    requests(&read_month(&current_month()))
+        .filter(|record| record.project.as_deref() == Some(project))
+        .map(|record| record.cost)
+        .sum()
+}
+
+/// Record the usage of a configured key after a fresh fetch
+pub(crate) fn record_sample(key_fingerprint: String, usage: f64, budget: Option<f64>, project: Option<String>) -> Result<(), String> {
+    append(&HistoryEntry::Sample(BalanceSample {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        key_fingerprint,
+        usage,
+        budget,
+        project,
+    }))
+}
+
+/// "YYYY-MM" of the month before
+pub(crate) fn previous_month(month: &str) -> Option<String> {
+    let date = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    Some(date.pred_opt()?.format("%Y-%m").to_string())
+}
+
+/// Positive usage deltas between consecutive samples of each key that end inside
+/// `entries`. `seed` holds samples from before the month, so its first interval is
+/// counted too. Drops (key reset) are skipped.
+pub(crate) fn usage_deltas<'a>(seed: &'a [HistoryEntry], entries: &'a [HistoryEntry]) -> Vec<UsageDelta<'a>> {
+    let mut deltas = Vec::new();
+    let mut last: Vec<&BalanceSample> = Vec::new(); // latest sample per key
+    for (sample, in_month) in samples(seed).map(|s| (s, false)).chain(samples(entries).map(|s| (s, true))) {
+        match last.iter_mut().find(|s| s.key_fingerprint == sample.key_fingerprint) {
+            Some(previous) => {
+                let delta = sample.usage - previous.usage;
+                if in_month && delta > 0.0 {
+                    deltas.push(UsageDelta { previous, sample, delta });
+                }
+                *previous = sample;
+            }
+            None => last.push(sample),
+        }
+    }
+    deltas
+}
+
+/// Attribute each usage delta to the project of the earlier sample (the tag active
+/// during that interval)
+pub(crate) fn summarize_samples(seed: &[HistoryEntry], entries: &[HistoryEntry], month: &str) -> ProjectSpend {
+    let mut spend = ProjectSpend { month: month.to_string(), ..Default::default() };
+    for UsageDelta { previous, delta, .. } in usage_deltas(seed, entries) {
+        spend.total_cost += delta;
+        match previous.project.as_deref() {
+            Some(project) => match spend.by_project.iter_mut().find(|t| t.name == project) {
+                Some(total) => total.cost += delta,
+                None => spend.by_project.push(ProjectTotal { name: project.to_string(), cost: delta }),
+            },
+            None => spend.untagged_cost += delta,
+        }
+    }
+    spend.by_project.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+    spend
+}
+
+/// Per-project totals of a month from the balance samples
+pub(crate) fn project_totals(month: &str) -> ProjectSpend {
+    let seed = previous_month(month).map(|m| read_month(&m)).unwrap_or_default();
+    summarize_samples(&seed, &read_month(month), month)
+}
+
+/// Spend of one key per UTC day over the `days` days ending with `today`, oldest first
+/// (usage deltas count on the day of the later sample)
+pub(crate) fn daily_spend(key_fingerprint: &str, today: NaiveDate, days: usize) -> Vec<f64> {
+    let month = today.format("%Y-%m").to_string();
+    let mut entries = previous_month(&month).map(|m| read_month(&m)).unwrap_or_default();
+    entries.extend(read_month(&month));
+
+    let mut spend = vec![0.0; days];
+    for UsageDelta { sample, delta, .. } in usage_deltas(&[], &entries) {
+        if sample.key_fingerprint != key_fingerprint {
+            continue;
+        }
+        let Ok(date) = DateTime::parse_from_rfc3339(&sample.timestamp) else { continue };
+        let age = (today - date.with_timezone(&Utc).date_naive()).num_days();
+        if (0..days as i64).contains(&age) {
+            spend[days - 1 - age as usize] += delta;
+        }
+    }
+    spend
+}
+
+fn add_to(totals: &mut Vec<SpendTotal>, name: &str, record: &RequestRecord) {
+    let entry = match totals.iter_mut().position(|t| t.name == name) {
+        Some(index) => &mut totals[index],
+        None => {
+            totals.push(SpendTotal { name: name.to_string(), ..Default::default() });
+            totals.last_mut().expect("just pushed")
+        }
+    };
+    entry.cost += record.cost;
+    entry.requests += 1;
+    entry.prompt_tokens += record.prompt_tokens;
+    entry.completion_tokens += record.completion_tokens;
+}
+
+/// Proxy spend of a month grouped by client, project and model (optionally for one key)
+pub(crate) fn summarize_requests(entries: &[HistoryEntry], month: &str, key_fingerprint: Option<&str>) -> ProxySpend {
+    let mut spend = ProxySpend { month: month.to_string(), ..Default::default() };
+    for record in requests(entries).filter(|r| key_fingerprint.map_or(true, |fp| r.key_fingerprint == fp)) {
+        spend.total_cost += record.cost;
+        spend.total_requests += 1;
+        add_to(&mut spend.by_client, &record.client, record);
+        add_to(&mut spend.by_project, record.project.as_deref().unwrap_or("(none)"), record);
+        add_to(&mut spend.by_model, record.model.as_deref().unwrap_or("(unknown)"), record);
+    }
+    for totals in [&mut spend.by_client, &mut spend.by_project, &mut spend.by_model] {
+        totals.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap_or(std::cmp::Ordering::Equal));
+    }
+    spend
+}
+
+/// Spend recorded by the metering proxy for a month (default: current), all keys
+#[tauri::command]
+pub fn get_proxy_spend(month: Option<String>) -> Result<ProxySpend, String> {
+    let month = month.unwrap_or_else(current_month);
+    check_month(&month)?;
+    Ok(summarize_requests(&read_month(&month), &month, None))
+}
+
+/// Usage per project tag from balance samples for a month (default: current)
+#[tauri::command]
+pub fn get_project_spend(month: Option<String>) -> Result<ProjectSpend, String> {
+    let month = month.unwrap_or_else(current_month);
+    check_month(&month)?;
+    Ok(project_totals(&month))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(timestamp: &str, key: &str, usage: f64, project: Option<&str>) -> HistoryEntry {
+        HistoryEntry::Sample(BalanceSample {
+            timestamp: timestamp.to_string(),
+            key_fingerprint: key.to_string(),
+            usage,
+            budget: None,
+            project: project.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn usage_goes_to_the_tag_of_the_earlier_sample() {
+        let entries = [
+            sample("2026-10-01T09:00:00Z", "a", 10.0, Some("web")),
+            sample("2026-10-01T10:00:00Z", "a", 14.0, Some("api")),
+            sample("2026-10-01T11:00:00Z", "a", 15.0, None),
+            sample("2026-10-01T12:00:00Z", "a", 17.0, Some("web")),
+            // Another key's samples interleave without mixing intervals
+            sample("2026-10-01T09:30:00Z", "b", 100.0, Some("api")),
+            sample("2026-10-01T12:30:00Z", "b", 101.5, None),
+        ];
+        let spend = summarize_samples(&[], &entries, "2026-10");
+        assert_eq!(spend.total_cost, 8.5);
+        assert_eq!(spend.untagged_cost, 2.0);
+        let totals: Vec<(&str, f64)> = spend.by_project.iter().map(|t| (t.name.as_str(), t.cost)).collect();
+        assert_eq!(totals, vec![("web", 4.0), ("api", 2.5)]);
+    }
+
+    #[test]
+    fn drops_after_a_key_reset_are_skipped() {
+        let entries = [
+            sample("2026-10-01T09:00:00Z", "a", 10.0, Some("web")),
+            sample("2026-10-01T10:00:00Z", "a", 15.0, Some("web")),
+            sample("2026-10-01T11:00:00Z", "a", 2.0, Some("web")),
+            sample("2026-10-01T12:00:00Z", "a", 5.0, Some("web")),
+        ];
+        let deltas: Vec<f64> = usage_deltas(&[], &entries).iter().map(|d| d.delta).collect();
+        assert_eq!(deltas, vec![5.0, 3.0]);
+        assert_eq!(summarize_samples(&[], &entries, "2026-10").total_cost, 8.0);
+    }
+
+    #[test]
+    fn the_previous_month_seeds_the_first_interval() {
+        let seed = [
+            sample("2026-09-30T20:00:00Z", "a", 6.0, Some("old")),
+            sample("2026-09-30T22:00:00Z", "a", 8.0, Some("web")),
+        ];
+        let entries = [sample("2026-10-01T08:00:00Z", "a", 11.0, None)];
+
+        // Only the interval ending inside the month counts, under the seed's last tag
+        let spend = summarize_samples(&seed, &entries, "2026-10");
+        assert_eq!(spend.total_cost, 3.0);
+        assert_eq!(spend.by_project.len(), 1);
+        assert_eq!((spend.by_project[0].name.as_str(), spend.by_project[0].cost), ("web", 3.0));
+
+        // Without the seed the month's first sample is only a baseline
+        assert_eq!(summarize_samples(&[], &entries, "2026-10").total_cost, 0.0);
+        assert_eq!(previous_month("2026-01").as_deref(), Some("2025-12"));
+    }
+}
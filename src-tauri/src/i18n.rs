@@ -0,0 +1,390 @@
+// Localisation
+// Message catalogue for every user-facing string of the backend: tray menu, menubar
+// letters, notifications and errors returned to the window. `tr!(key, args...)` looks a
+// key up in the current language and fills its `{}` slots in order. The language is the
+// `language` setting ("auto" follows the system). Report files follow it too, except the
+// CSV columns; the CLI's own output and log lines stay English.
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{is_german_locale, AppSettings};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Lang {
+    En,
+    De,
+}
+
+/// Resolved language: 0 = not yet resolved, then 1 + Lang index
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// key, English, German
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Tray menu and menubar
+    ("tray.show_window", "Show Window", "Fenster anzeigen"),
+    ("tray.hide_window", "Hide Window", "Fenster ausblenden"),
+    ("tray.show", "Show", "Anzeigen"),
+    ("tray.daily", "Daily", "Täglich"),
+    ("tray.weekly", "Weekly", "Wöchentlich"),
+    ("tray.monthly", "Monthly", "Monatlich"),
+    ("tray.quit", "Quit", "Beenden"),
+    ("tray.project", "Project: {}", "Projekt: {}"),
+    ("tray.project_none", "none", "keins"),
+    ("tray.no_project", "No project", "Kein Projekt"),
+    ("tray.project_hint", "Add projects with `bp-esc cli project set <name>`", "Projekte mit `bp-esc cli project set <name>` hinzufügen"),
+    ("tray.report", "Spend Report", "Ausgabenbericht"),
+    ("tray.report_this_month", "This Month", "Dieser Monat"),
+    ("tray.report_last_month", "Last Month", "Letzter Monat"),
+    ("tray.pin_activate", "Make Active Key", "Als aktiven Schlüssel verwenden"),
+    ("tray.pin_refresh", "Refresh", "Aktualisieren"),
+    ("tray.unpin", "Unpin from Menubar", "Von der Menüleiste lösen"),
+    ("menubar.monthly", "M", "M"),
+    ("menubar.weekly", "W", "W"),
+    ("menubar.daily", "D", "T"),
+    // Tray tooltip and accessibility label
+    ("status.remaining_month", "{} remaining this month", "{} übrig diesen Monat"),
+    ("status.remaining_week", "{} remaining this week", "{} übrig diese Woche"),
+    ("status.remaining_day", "{} remaining today", "{} übrig heute"),
+    ("status.used_month", "{} used this month", "{} verbraucht diesen Monat"),
+    ("status.used_week", "{} used this week", "{} verbraucht diese Woche"),
+    ("status.used_day", "{} used today", "{} verbraucht heute"),
+    ("status.no_data", "no balance yet", "noch kein Guthaben"),
+    ("status.updated", "updated {}", "aktualisiert {}"),
+    ("status.mood_due", "mood entry due", "Mood-Eintrag fällig"),
+    ("pace.on", "on pace", "im Plan"),
+    ("pace.ahead", "{}% ahead of pace", "{}% über Plan"),
+    ("pace.behind", "{}% behind pace", "{}% unter Plan"),
+    // Key rotation reminders
+    ("rotation.overdue", "Rotate key \u{201c}{}\u{201d} (overdue by {} days)", "Schlüssel \u{201e}{}\u{201c} erneuern ({} Tage überfällig)"),
+    ("rotation.today", "Rotate key \u{201c}{}\u{201d} (due today)", "Schlüssel \u{201e}{}\u{201c} erneuern (heute fällig)"),
+    ("rotation.tomorrow", "Rotate key \u{201c}{}\u{201d} (due tomorrow)", "Schlüssel \u{201e}{}\u{201c} erneuern (morgen fällig)"),
+    ("rotation.due_in", "Rotate key \u{201c}{}\u{201d} (due in {} days)", "Schlüssel \u{201e}{}\u{201c} erneuern (fällig in {} Tagen)"),
+    // Settings and files
+    ("settings.unknown_language", "Unknown language '{}' (auto, en or de).", "Unbekannte Sprache '{}' (auto, en oder de)."),
+    ("settings.template", "Menubar template: {}", "Menüleisten-Vorlage: {}"),
+    ("settings.create_dir", "Failed to create config directory: {}", "Konfigurationsordner konnte nicht angelegt werden: {}"),
+    ("settings.serialize", "Failed to serialize settings: {}", "Einstellungen konnten nicht umgewandelt werden: {}"),
+    ("settings.write", "Failed to write settings: {}", "Einstellungen konnten nicht gespeichert werden: {}"),
+    ("settings.read", "Failed to read settings: {}", "Einstellungen konnten nicht gelesen werden: {}"),
+    ("settings.parse", "Failed to parse settings: {}", "Einstellungsdatei ist ungültig: {}"),
+    ("settings.reset", "Failed to delete config directory: {}", "Konfigurationsordner konnte nicht gelöscht werden: {}"),
+    ("file.no_home", "Could not determine home directory", "Home-Verzeichnis konnte nicht ermittelt werden"),
+    ("file.write", "Failed to write {}: {}", "{} konnte nicht geschrieben werden: {}"),
+    ("file.create_dir", "Failed to create {}: {}", "{} konnte nicht angelegt werden: {}"),
+    ("file.permissions", "Failed to set permissions on {}: {}", "Zugriffsrechte für {} konnten nicht gesetzt werden: {}"),
+    ("file.remove", "Failed to remove {}: {}", "{} konnte nicht entfernt werden: {}"),
+    ("file.env_read", "Failed to read .env file: {}", ".env-Datei konnte nicht gelesen werden: {}"),
+    ("file.env_write", "Failed to write .env file: {}", ".env-Datei konnte nicht geschrieben werden: {}"),
+    ("file.env_dir_permissions", "Failed to set directory permissions: {}", "Zugriffsrechte des Ordners konnten nicht gesetzt werden: {}"),
+    ("file.env_permissions", "Failed to set file permissions: {}", "Zugriffsrechte der Datei konnten nicht gesetzt werden: {}"),
+    ("log.missing", "Log file does not exist yet.", "Die Logdatei existiert noch nicht."),
+    ("app.clipboard", "Failed to copy to clipboard: {}", "Kopieren in die Zwischenablage fehlgeschlagen: {}"),
+    ("app.tray_icon", "Failed to update tray icon: {}", "Menüleistensymbol konnte nicht aktualisiert werden: {}"),
+    ("app.tray_template", "Failed to set icon template mode: {}", "Vorlagenmodus des Symbols konnte nicht gesetzt werden: {}"),
+    ("app.monitor", "Failed to get monitor: {}", "Bildschirm konnte nicht ermittelt werden: {}"),
+    ("app.no_monitor", "No monitor available", "Kein Bildschirm verfügbar"),
+    ("app.window_size", "Failed to get window size: {}", "Fenstergröße konnte nicht ermittelt werden: {}"),
+    ("app.window_position", "Failed to set position: {}", "Fensterposition konnte nicht gesetzt werden: {}"),
+    ("app.shortcut", "Failed to register shortcut: {}", "Tastenkürzel konnte nicht registriert werden: {}"),
+    // API keys
+    ("key.empty", "API key cannot be empty", "Der API-Schlüssel darf nicht leer sein"),
+    ("key.prefix", "API key must start with 'sk-'", "Der API-Schlüssel muss mit 'sk-' beginnen"),
+    ("key.too_short", "API key is too short", "Der API-Schlüssel ist zu kurz"),
+    ("key.not_found", "No API key at position {}", "Kein API-Schlüssel an Position {}"),
+    ("key.default_label", "Key {}", "Schlüssel {}"),
+    ("key.duplicate", "This key is already configured as \"{}\".", "Dieser Schlüssel ist bereits als \u{201e}{}\u{201c} eingerichtet."),
+    ("key.successor_same", "The successor key is the same as the current key.", "Der Nachfolgeschlüssel ist derselbe wie der aktuelle Schlüssel."),
+    ("key.new_same", "The new key is the same as the current key.", "Der neue Schlüssel ist derselbe wie der aktuelle Schlüssel."),
+    ("key.rotate_missing", "Enter the new key or stage a successor key first.", "Gib zuerst den neuen Schlüssel ein oder hinterlege einen Nachfolgeschlüssel."),
+    ("key.rotate_failed", "New key failed verification: {}", "Der neue Schlüssel wurde nicht bestätigt: {}"),
+    ("key.validation_failed", "Key validation failed: {}", "Schlüsselprüfung fehlgeschlagen: {}"),
+    ("key.unknown_timeframe", "Unknown timeframe '{}' (daily, weekly, monthly)", "Unbekannter Zeitraum '{}' (daily, weekly, monthly)"),
+    ("key.unknown_reset", "Unknown reset period '{}' (daily, weekly, monthly)", "Unbekannter Rücksetzzeitraum '{}' (daily, weekly, monthly)"),
+    ("key.provisioning_failed", "Provisioning key failed verification: {}", "Der Provisioning-Schlüssel wurde nicht bestätigt: {}"),
+    ("key.not_provisioning", "This is a regular API key. Create a provisioning key under openrouter.ai/settings/provisioning-keys.", "Das ist ein normaler API-Schlüssel. Lege unter openrouter.ai/settings/provisioning-keys einen Provisioning-Schlüssel an."),
+    ("key.no_provisioning", "No provisioning key configured (Settings > OpenRouter > Provisioning key).", "Kein Provisioning-Schlüssel eingerichtet (Einstellungen > OpenRouter > Provisioning key)."),
+    ("key.none_configured", "No API key configured in BP-ESC.", "In BP-ESC ist kein API-Schlüssel eingerichtet."),
+    // Network and OpenRouter API
+    ("net.client", "Could not initialize network client. Please restart the app.", "Netzwerk-Client konnte nicht gestartet werden. Bitte starte die App neu."),
+    ("net.timeout", "Request timed out. Check your internet connection.", "Zeitüberschreitung der Anfrage. Prüfe deine Internetverbindung."),
+    ("net.connect", "Could not connect to OpenRouter. Check your internet connection.", "Keine Verbindung zu OpenRouter. Prüfe deine Internetverbindung."),
+    ("net.error", "Network error: {}", "Netzwerkfehler: {}"),
+    ("net.body", "Failed to read response body: {}", "Antwort konnte nicht gelesen werden: {}"),
+    ("api.invalid_key", "Invalid API key. Please check your key and try again.", "Ungültiger API-Schlüssel. Prüfe den Schlüssel und versuche es erneut."),
+    ("api.status", "API request failed with status: {}", "API-Anfrage fehlgeschlagen mit Status: {}"),
+    ("api.parse", "Failed to parse API response: {}. Body: {}", "API-Antwort konnte nicht verarbeitet werden: {}. Inhalt: {}"),
+    ("api.error", "API error: {}", "API-Fehler: {}"),
+    ("api.missing_data", "API response missing data. Please try again.", "Der API-Antwort fehlen Daten. Bitte versuche es erneut."),
+    ("activity.needs_provisioning", "Activity requires a provisioning key. Check the key in Settings > OpenRouter.", "Die Aktivität erfordert einen Provisioning-Schlüssel. Prüfe den Schlüssel unter Einstellungen > OpenRouter."),
+    ("activity.status", "Activity request failed with status: {}", "Aktivitätsanfrage fehlgeschlagen mit Status: {}"),
+    ("activity.parse", "Failed to parse activity response: {}", "Aktivitätsantwort konnte nicht verarbeitet werden: {}"),
+    // Cost estimates
+    ("pricing.models_status", "Models request failed with status: {}", "Modellabfrage fehlgeschlagen mit Status: {}"),
+    ("pricing.models_parse", "Failed to parse models list: {}", "Modellliste konnte nicht verarbeitet werden: {}"),
+    ("pricing.unknown_model", "Unknown model '{}'.", "Unbekanntes Modell '{}'."),
+    ("pricing.unknown_model_similar", "Unknown model '{}'. Did you mean: {}?", "Unbekanntes Modell '{}'. Meintest du: {}?"),
+    ("pricing.requests", "The number of requests must be at least 1.", "Die Anzahl der Anfragen muss mindestens 1 sein."),
+    ("pricing.costs", "This job costs about {}.", "Dieser Auftrag kostet etwa {}."),
+    ("pricing.exceeds", " That is {} more than the {} left this month.", " Das sind {} mehr als die {}, die diesen Monat übrig sind."),
+    ("pricing.remains", " {} of the {} left this month would remain.", " Von den {}, die diesen Monat übrig sind, blieben {}."),
+    ("pricing.after", " After this job you will be {}.", " Danach liegst du {}."),
+    // Team keys
+    ("team.needs_provisioning", "Team management requires a provisioning key. Check the key in Settings > OpenRouter.", "Die Teamverwaltung erfordert einen Provisioning-Schlüssel. Prüfe den Schlüssel unter Einstellungen > OpenRouter."),
+    ("team.not_found", "Key not found. It may have been deleted.", "Schlüssel nicht gefunden. Er wurde eventuell gelöscht."),
+    ("team.status", "Keys API request failed with status {}: {}", "Anfrage an die Keys-API fehlgeschlagen mit Status {}: {}"),
+    ("team.parse", "Failed to parse keys API response: {}", "Antwort der Keys-API konnte nicht verarbeitet werden: {}"),
+    ("team.name_required", "A name is required for the new key.", "Der neue Schlüssel braucht einen Namen."),
+    ("team.invalid_limit", "The limit must be a dollar amount of 0 or more.", "Das Limit muss ein Dollarbetrag von 0 oder mehr sein."),
+    ("team.no_key_returned", "OpenRouter did not return the new key.", "OpenRouter hat den neuen Schlüssel nicht zurückgegeben."),
+    ("team.no_match", "No team key matches '{}'.", "Kein Teamschlüssel passt zu '{}'."),
+    ("team.ambiguous", "'{}' matches {} keys; use a longer hash prefix.", "'{}' passt zu {} Schlüsseln; nimm einen längeren Hash-Anfang."),
+    // OpenCode sync
+    ("opencode.read", "Failed to read OpenCode auth file: {}", "OpenCode-Anmeldedatei konnte nicht gelesen werden: {}"),
+    ("opencode.parse", "Failed to parse OpenCode auth file: {}", "OpenCode-Anmeldedatei ist ungültig: {}"),
+    ("opencode.no_key", "OpenRouter key not found in OpenCode auth file.", "In der OpenCode-Anmeldedatei ist kein OpenRouter-Schlüssel."),
+    ("opencode.layout", "OpenCode auth file has an unexpected layout; not modified.", "Die OpenCode-Anmeldedatei hat ein unerwartetes Format und wurde nicht geändert."),
+    ("opencode.backup", "Failed to back up OpenCode auth file: {}", "Sicherung der OpenCode-Anmeldedatei fehlgeschlagen: {}"),
+    ("opencode.backup_permissions", "Failed to set backup permissions: {}", "Zugriffsrechte der Sicherung konnten nicht gesetzt werden: {}"),
+    ("opencode.create_dir", "Failed to create OpenCode data directory: {}", "OpenCode-Datenordner konnte nicht angelegt werden: {}"),
+    ("opencode.serialize", "Failed to serialize OpenCode auth file: {}", "OpenCode-Anmeldedatei konnte nicht umgewandelt werden: {}"),
+    ("opencode.write", "Failed to write OpenCode auth file: {}", "OpenCode-Anmeldedatei konnte nicht geschrieben werden: {}"),
+    ("opencode.no_active_key", "No active API key to sync.", "Kein aktiver API-Schlüssel zum Abgleichen."),
+    ("opencode.verification", "OpenCode key failed verification: {}", "Der OpenCode-Schlüssel wurde nicht bestätigt: {}"),
+    // Reports
+    ("report.invalid_month", "Invalid month '{}' (expected YYYY-MM)", "Ungültiger Monat '{}' (erwartet JJJJ-MM)"),
+    ("report.unknown_format", "Unknown report format '{}' (md, csv, html)", "Unbekanntes Berichtsformat '{}' (md, csv, html)"),
+    ("report.create_dir", "Failed to create report directory: {}", "Berichtsordner konnte nicht angelegt werden: {}"),
+    ("report.not_started", "{} has not started yet.", "{} hat noch nicht begonnen."),
+    ("report.open", "Could not open {}: {}", "{} konnte nicht geöffnet werden: {}"),
+    ("report.title", "Spend report {}", "Ausgabenbericht {}"),
+    ("report.generated", "Generated {} from BP-ESC balance samples ({}).", "Erstellt {} aus den Kontostandsproben von BP-ESC ({})."),
+    ("report.complete", "complete month, {} days", "ganzer Monat, {} Tage"),
+    ("report.in_progress", "month in progress, day {} of {}", "laufender Monat, Tag {} von {}"),
+    ("report.all_keys", "All keys", "Alle Schlüssel"),
+    ("report.removed_key", "Removed key {}", "Entfernter Schlüssel {}"),
+    ("report.no_samples", "No balance samples this month.", "Keine Kontostandsproben in diesem Monat."),
+    ("report.total_spend", "Total spend", "Gesamtausgaben"),
+    ("report.of_budget", " of {} budget", " von {} Budget"),
+    ("report.pace_label", "Pace", "Plan"),
+    ("report.pace", "{} days on track, {} slightly over, {} well over pace{}", "{} Tage im Plan, {} leicht darüber, {} deutlich über Plan{}"),
+    ("report.pace_last_day", "; last day {} vs. pace", "; letzter Tag {} gegenüber Plan"),
+    ("report.forecast_label", "Forecast vs. actual", "Prognose und Ist"),
+    ("report.forecast_actual", "{} forecast ({}), actual {}{}", "Prognose {} ({}), tatsächlich {}{}"),
+    ("report.forecast_so_far", "{} forecast ({}), so far {}{}", "Prognose {} ({}), bisher {}{}"),
+    ("report.basis_day", "run rate to day {}", "Hochrechnung bis Tag {}"),
+    ("report.basis_to_date", "run rate to date (day {} of {})", "Hochrechnung bis heute (Tag {} von {})"),
+    ("report.peak_days", "Peak days", "Spitzentage"),
+    ("report.date", "Date", "Datum"),
+    ("report.spend", "Spend", "Ausgaben"),
+    ("report.cumulative", "Cumulative", "Kumuliert"),
+    ("report.pace_target", "Pace target", "Planwert"),
+    ("report.projects", "Projects", "Projekte"),
+    ("report.project", "Project", "Projekt"),
+    ("report.no_project", "(no project)", "(kein Projekt)"),
+    ("month.january", "January", "Januar"),
+    ("month.february", "February", "Februar"),
+    ("month.march", "March", "März"),
+    ("month.april", "April", "April"),
+    ("month.may", "May", "Mai"),
+    ("month.june", "June", "Juni"),
+    ("month.july", "July", "Juli"),
+    ("month.august", "August", "August"),
+    ("month.september", "September", "September"),
+    ("month.october", "October", "Oktober"),
+    ("month.november", "November", "November"),
+    ("month.december", "December", "Dezember"),
+    // Projects
+    ("project.name_too_long", "Project names are limited to {} characters.", "Projektnamen sind auf {} Zeichen begrenzt."),
+    ("project.unsupported_shell", "Unsupported shell '{}' (zsh, bash or fish).", "Nicht unterstützte Shell '{}' (zsh, bash oder fish)."),
+    // Metering proxy and budget guard
+    ("proxy.listen", "Could not listen on 127.0.0.1:{}: {}", "Port 127.0.0.1:{} konnte nicht geöffnet werden: {}"),
+    ("proxy.token", "Could not create the proxy token: {}", "Proxy-Token konnte nicht erstellt werden: {}"),
+    ("proxy.port_range", "Choose a port between 1024 and 65535.", "Wähle einen Port zwischen 1024 und 65535."),
+    ("proxy.negative_pace_cap", "The pace cap cannot be negative.", "Die Plan-Grenze darf nicht negativ sein."),
+    ("proxy.negative_project_cap", "The cap of project {} cannot be negative.", "Die Grenze von Projekt {} darf nicht negativ sein."),
+    ("proxy.override_limit", "The override lasts at most 24 hours.", "Die Ausnahme gilt höchstens 24 Stunden."),
+    ("guard.name_model", "only allowlisted models may be used; name the model in the request.", "nur freigegebene Modelle sind erlaubt; gib das Modell in der Anfrage an."),
+    ("guard.model_not_allowed", "model {} is not on the allowlist.", "Modell {} ist nicht freigegeben."),
+    ("guard.budget_exhausted", "the key's budget is exhausted ({} remaining).", "das Budget des Schlüssels ist aufgebraucht ({} übrig)."),
+    ("guard.ahead_of_pace", "spending is {}% ahead of pace (cap {}%).", "die Ausgaben liegen {}% über Plan (Grenze {}%)."),
+    ("guard.project_cap", "project {} has used {} of its {} monthly cap.", "Projekt {} hat {} seiner Monatsgrenze von {} verbraucht."),
+    ("guard.override_hint", "{} Use \"Override for 1 h\" in BP-ESC if this spend is intended.", "{} Nutze \u{201e}Override for 1 h\u{201c} in BP-ESC, wenn diese Ausgabe gewollt ist."),
+    // Number formats and menubar templates
+    ("format.unknown_locale", "Unknown number format '{}' (auto, en or de).", "Unbekanntes Zahlenformat '{}' (auto, en oder de)."),
+    ("format.currency_code", "Display currency '{}' must be a three-letter code like EUR.", "Die Anzeigewährung '{}' muss ein Code aus drei Buchstaben sein, z. B. EUR."),
+    ("format.exchange_rate", "Set an exchange rate (1 USD in {}) above 0.", "Lege einen Wechselkurs (1 USD in {}) über 0 fest."),
+    ("template.too_long", "Templates are limited to {} characters.", "Vorlagen sind auf {} Zeichen begrenzt."),
+    ("template.single_line", "Templates must be a single line.", "Vorlagen müssen einzeilig sein."),
+    ("template.unclosed", "Unclosed placeholder '{}'.", "Nicht geschlossener Platzhalter '{}'."),
+    ("template.unmatched", "Unmatched '}' (write '}}' for a literal brace).", "'}' ohne Gegenstück (für eine geschweifte Klammer '}}' schreiben)."),
+    ("template.unknown", "Unknown placeholder '{}'.", "Unbekannter Platzhalter '{}'."),
+    ("template.no_options", "'{}' is text and takes no format options.", "'{}' ist Text und hat keine Formatoptionen."),
+    ("template.invalid_format", "Invalid format '{}' in '{}' (use '+', '.N' or '+.N').", "Ungültiges Format '{}' in '{}' ('+', '.N' oder '+.N' verwenden)."),
+    ("template.max_decimals", "At most {} decimals in '{}'.", "Höchstens {} Nachkommastellen in '{}'."),
+    // Mood
+    ("mood.not_configured", "Mood not configured. Set Sheet ID and service account in Settings.", "Mood ist nicht eingerichtet. Lege Sheet-ID und Service-Account in den Einstellungen fest."),
+    ("mood.name_missing", "Your 3-char name is not set. Check Settings → Mood.", "Dein Kürzel (3 Zeichen) fehlt. Siehe Einstellungen → Mood."),
+    ("mood.invalid_value", "Invalid mood value: {}. Must be 1-6.", "Ungültiger Mood-Wert: {}. Erlaubt ist 1–6."),
+    ("mood.no_api_key", "No OpenRouter API key configured. Set one in Settings → OpenRouter.", "Kein OpenRouter-API-Schlüssel eingerichtet. Lege einen unter Einstellungen → OpenRouter an."),
+    ("mood.no_tag", "No tag configured. Set your tag in Settings → Mood.", "Kein Kürzel eingerichtet. Lege es unter Einstellungen → Mood fest."),
+    ("mood.keys_sheet_empty", "Keys sheet is empty or missing. Contact your administrator.", "Das Keys-Blatt ist leer oder fehlt. Wende dich an deine Administration."),
+    ("mood.access_denied", "Access denied: key and tag combination not found. Check your OpenRouter key and tag in Settings.", "Zugriff verweigert: Kombination aus Schlüssel und Kürzel nicht gefunden. Prüfe OpenRouter-Schlüssel und Kürzel in den Einstellungen."),
+    ("mood.all_required", "Sheet ID, service account email, and private key are all required.", "Sheet-ID, Service-Account-E-Mail und Private Key werden alle benötigt."),
+    ("mood.invalid_private_key", "Invalid private key: {}", "Ungültiger Private Key: {}"),
+    ("mood.jwt", "JWT encode error: {}", "JWT konnte nicht erstellt werden: {}"),
+    ("mood.token_request", "Token request failed: {}", "Token-Anfrage fehlgeschlagen: {}"),
+    ("mood.token_parse", "Token response parse error: {}", "Token-Antwort konnte nicht verarbeitet werden: {}"),
+    ("mood.no_token", "No access_token in response: {}", "Kein access_token in der Antwort: {}"),
+    ("mood.sheets_fetch", "Sheets fetch error: {}", "Fehler beim Lesen der Tabelle: {}"),
+    ("mood.sheets_status", "Sheets API error {}: {}", "Sheets-API-Fehler {}: {}"),
+    ("mood.sheets_parse", "Sheets response parse error: {}", "Tabellenantwort konnte nicht verarbeitet werden: {}"),
+    ("mood.update", "Sheets update error: {}", "Fehler beim Aktualisieren der Tabelle: {}"),
+    ("mood.update_status", "Sheets update failed {}: {}", "Aktualisieren der Tabelle fehlgeschlagen {}: {}"),
+    ("mood.append", "Sheets append error: {}", "Fehler beim Anfügen an die Tabelle: {}"),
+    ("mood.append_status", "Sheets append failed {}: {}", "Anfügen an die Tabelle fehlgeschlagen {}: {}"),
+    ("mood.auth_request", "Auth check request failed: {}", "Anfrage zur Zugriffsprüfung fehlgeschlagen: {}"),
+    ("mood.auth_status", "Auth check failed ({}): {}", "Zugriffsprüfung fehlgeschlagen ({}): {}"),
+    ("mood.auth_parse", "Auth response parse error: {}", "Antwort der Zugriffsprüfung konnte nicht verarbeitet werden: {}"),
+    ("mood.test_request", "Connection test request failed: {}", "Verbindungstest fehlgeschlagen: {}"),
+    ("mood.test_status", "Connection test failed ({}): {}", "Verbindungstest fehlgeschlagen ({}): {}"),
+    ("mood.test_parse", "Response parse error: {}", "Antwort konnte nicht verarbeitet werden: {}"),
+    ("mood.empty_sheet", "(empty sheet — add a header row)", "(leeres Blatt – füge eine Kopfzeile hinzu)"),
+];
+
+/// Look up `key` in the given language (the key itself when it is missing)
+pub(crate) fn text(lang: Lang, key: &'static str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, de)) => match lang {
+            Lang::En => en,
+            Lang::De => de,
+        },
+        None => key,
+    }
+}
+
+/// Fill the `{}` slots of a message in order
+pub(crate) fn fill(message: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(message.len());
+    let mut rest = message;
+    let mut args = args.iter();
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Reject languages the catalogue doesn't have
+pub(crate) fn validate(language: &str) -> Result<(), String> {
+    match language {
+        "auto" | "en" | "de" => Ok(()),
+        other => Err(tr!("settings.unknown_language", other)),
+    }
+}
+
+/// The language a `language` setting stands for
+pub(crate) fn resolve(language: &str) -> Lang {
+    match language {
+        "de" => Lang::De,
+        "en" => Lang::En,
+        _ if is_german_locale() => Lang::De,
+        _ => Lang::En,
+    }
+}
+
+/// Make the settings' language current (called whenever settings are saved)
+pub(crate) fn apply(settings: &AppSettings) {
+    store(resolve(&settings.language));
+}
+
+fn store(lang: Lang) {
+    LANGUAGE.store(lang as u8 + 1, Ordering::Relaxed);
+}
+
+/// The current language, read from the settings on first use. Tests always use
+/// English so they don't depend on the machine's settings.
+pub(crate) fn language() -> Lang {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Lang::En,
+        2 => Lang::De,
+        _ if cfg!(test) => Lang::En,
+        _ => {
+            // Store a value first: reading the settings may report errors through tr!
+            store(resolve("auto"));
+            if let Ok(settings) = crate::read_settings() {
+                apply(&settings);
+            }
+            language()
+        }
+    }
+}
+
+/// `tr!(key)` or `tr!(key, arg, ...)`: a catalogue message in the current language
+macro_rules! tr {
+    ($key:literal) => {
+        $crate::i18n::text($crate::i18n::language(), $key).to_string()
+    };
+    ($key:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill(
+            $crate::i18n::text($crate::i18n::language(), $key),
+            &[$(&$arg as &dyn std::fmt::Display),+],
+        )
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(message: &str) -> usize {
+        message.matches("{}").count()
+    }
+
+    #[test]
+    fn catalogue_is_complete_and_consistent() {
+        let mut keys: Vec<&str> = MESSAGES.iter().map(|(k, _, _)| *k).collect();
+        keys.sort_unstable();
+        let count = keys.len();
+        keys.dedup();
+        assert_eq!(keys.len(), count, "duplicate keys in the catalogue");
+        for (key, en, de) in MESSAGES {
+            assert_eq!(slots(en), slots(de), "{} has different placeholders", key);
+            assert!(!en.is_empty() && !de.is_empty(), "{} is empty", key);
+        }
+    }
+
+    #[test]
+    fn every_used_key_exists() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let source = std::fs::read_to_string(&path).unwrap();
+            for (i, _) in source.match_indices("tr!(\"") {
+                // Skip include_str! and other macros ending in "tr"
+                if source[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    continue;
+                }
+                let rest = &source[i + 5..];
+                let key = &rest[..rest.find('"').unwrap()];
+                assert!(MESSAGES.iter().any(|(k, _, _)| *k == key), "{} uses unknown key {}", path.display(), key);
+            }
+        }
+    }
+
+    #[test]
+    fn fills_slots_in_the_chosen_language() {
+        let en = fill(text(Lang::En, "rotation.due_in"), &[&"Work", &3]);
+        assert_eq!(en, "Rotate key \u{201c}Work\u{201d} (due in 3 days)");
+        let de = fill(text(Lang::De, "rotation.due_in"), &[&"Work", &3]);
+        assert_eq!(de, "Schlüssel \u{201e}Work\u{201c} erneuern (fällig in 3 Tagen)");
+        assert_eq!(text(Lang::De, "menubar.daily"), "T");
+        assert_eq!(text(Lang::En, "no.such.key"), "no.such.key");
+    }
+}
@@ -5,25 +5,24 @@
 #![allow(unexpected_cfgs)]
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::os::unix::fs::PermissionsExt; // macOS is Unix
 use std::sync::{Arc, Mutex};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use chrono::{Datelike, Local, TimeZone, Timelike};
+use chrono::{Datelike, Local, Timelike};
 use tauri::{
     AppHandle, Manager, WindowEvent, ActivationPolicy, PhysicalPosition, Emitter, State,
-    menu::{Menu, MenuItemBuilder, CheckMenuItem, PredefinedMenuItem},
+    menu::{Menu, MenuItemBuilder, CheckMenuItem, PredefinedMenuItem, IsMenuItem},
     tray::{TrayIconBuilder, TrayIconEvent, MouseButton, MouseButtonState},
     image::Image
 };
 use tauri_plugin_autostart::ManagerExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState, Shortcut};
-use image::Rgba;
 use tauri_plugin_autostart::MacosLauncher;
-use ab_glyph::{FontVec, PxScale};
+use ab_glyph::FontVec;
 
 #[cfg(target_os = "macos")]
 use cocoa::base::id;
@@ -39,12 +38,32 @@ use objc::declare::ClassDecl;
 use objc::runtime::{Class, Object, Sel};
 #[cfg(target_os = "macos")]
 use std::ffi::c_void;
-use imageproc::drawing::draw_text_mut;
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tokio::time::{interval, Interval};
 use tokio::sync::Mutex as TokioMutex;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 
+mod activity;
+mod cli;
+mod discovery;
+mod env_export;
+mod history;
+mod i18n;
+mod menubar_format;
+mod menubar_icon;
+mod number_format;
+mod opencode;
+mod pinned_trays;
+mod pricing;
+mod projects;
+mod proxy;
+mod reports;
+mod team;
+mod tray_status;
+
+use i18n::tr;
+use pinned_trays::TrayPin;
+
 // ============================================================================
 // AUTO-REFRESH STATE
 // ============================================================================
@@ -52,6 +71,8 @@ use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 pub struct AutoRefreshState {
     interval: Arc<TokioMutex<Option<Interval>>>,
     is_running: Arc<Mutex<bool>>,
+    /// Date (YYYY-MM-DD) rotation reminders were last emitted, so they fire once per day
+    rotation_notice_date: Arc<Mutex<Option<String>>>,
 }
 
 impl AutoRefreshState {
@@ -59,6 +80,7 @@ impl AutoRefreshState {
         Self {
             interval: Arc::new(TokioMutex::new(None)),
             is_running: Arc::new(Mutex::new(false)),
+            rotation_notice_date: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -72,7 +94,11 @@ async fn start_auto_refresh_timer(app: AppHandle, state: State<'_, AutoRefreshSt
     }
     
     let interval_minutes = settings.refresh_interval_minutes.max(1);
-    let interval_duration = Duration::from_secs((interval_minutes * 60) as u64);
+    let mut interval_duration = Duration::from_secs((interval_minutes * 60) as u64);
+    // Never tick faster than the strictest key's rate-limit guard allows
+    if let Some(throttle) = app.try_state::<BalanceThrottle>() {
+        interval_duration = interval_duration.max(throttle.max_gap());
+    }
     
     // Stop existing timer if any
     stop_auto_refresh_timer(&state)?;
@@ -87,6 +113,7 @@ async fn start_auto_refresh_timer(app: AppHandle, state: State<'_, AutoRefreshSt
     *state.is_running.lock().map_err(|e| e.to_string())? = true;
     let is_running = state.is_running.clone();
     let interval_arc = state.interval.clone();
+    let rotation_notice_date = state.rotation_notice_date.clone();
     
     // Spawn the timer task
     tokio::spawn(async move {
@@ -112,14 +139,19 @@ async fn start_auto_refresh_timer(app: AppHandle, state: State<'_, AutoRefreshSt
             }
             
             // Check if still enabled before emitting
-            if let Ok(settings) = read_settings() {
-                if settings.auto_refresh_enabled {
-                    // Emit event to frontend to refresh balance
-                    let _ = app.emit("rust-auto-refresh", ());
-                }
+            let enabled = read_settings().map(|s| s.auto_refresh_enabled).unwrap_or(false);
+            if enabled {
+                // Emit event to frontend to refresh balance
+                let _ = app.emit("rust-auto-refresh", ());
             }
             // Also re-evaluate mood blink condition on each refresh tick
             check_and_update_mood_blink(app.clone());
+            notify_key_rotation_reminders(&app, &rotation_notice_date);
+            opencode::check_external_change(&app);
+            // The window only fetches the active key
+            if enabled {
+                pinned_trays::refresh_all(&app).await;
+            }
         }
     });
     
@@ -165,6 +197,56 @@ impl Default for MoodBlinkState {
 pub struct ApiKeyConfig {
     pub key: String,
     pub label: String,
+    /// Stable identifier that survives key rotation (the secret itself changes)
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub created_at: Option<String>,     // YYYY-MM-DD
+    #[serde(default)]
+    pub rotated_at: Option<String>,     // YYYY-MM-DD, last in-place swap
+    #[serde(default)]
+    pub expires_at: Option<String>,     // YYYY-MM-DD, optional hard expiry
+    /// Replacement key staged during the overlap window, promoted by rotate_api_key
+    #[serde(default)]
+    pub successor_key: Option<String>,
+    /// Server-side facts from the last live check (None for keys saved before verification existed)
+    #[serde(default)]
+    pub verified: Option<KeyVerification>,
+    /// "daily" | "weekly" | "monthly"; only used when OpenRouter reports no reset period
+    #[serde(default)]
+    pub limit_reset: Option<String>,
+    /// Own menubar icon for this key next to the main one (None = not pinned)
+    #[serde(default)]
+    pub pin: Option<TrayPin>,
+}
+
+/// What /api/v1/key reported for a key when it was added, rotated or re-checked
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct KeyVerification {
+    pub verified_at: String,            // YYYY-MM-DD
+    #[serde(default)]
+    pub server_label: Option<String>,
+    #[serde(default)]
+    pub limit: Option<f64>,
+    #[serde(default)]
+    pub is_provisioning_key: bool,
+}
+
+impl ApiKeyConfig {
+    fn new(key: String, label: String) -> Self {
+        Self {
+            key,
+            label,
+            id: generate_key_id(),
+            created_at: Some(today_string()),
+            rotated_at: None,
+            expires_at: None,
+            successor_key: None,
+            verified: None,
+            limit_reset: None,
+            pin: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -211,6 +293,58 @@ pub struct AppSettings {
     pub pace_warn_threshold: f64,
     #[serde(default = "default_menubar_timeframe")]
     pub menubar_timeframe: String,  // "monthly" | "weekly" | "daily"
+    #[serde(default = "default_icon_style")]
+    pub icon_style: String,         // "hexagon" | "ring" | "bar" | "sparkline"
+    #[serde(default = "default_false")]
+    pub show_pace_marker: bool,     // line at the pace target inside the hexagon
+    #[serde(default)]
+    pub menubar_template: String,   // e.g. "{remaining_pct}% · {pace_delta:+}%"; empty = value/unit/superscript
+    #[serde(default = "default_false")]
+    pub compact_numbers: bool,      // 1.2k / 12.5k from 1000 on
+    #[serde(default = "default_number_locale")]
+    pub number_locale: String,      // "auto" | "en" | "de" (decimal separator)
+    #[serde(default = "default_display_currency")]
+    pub display_currency: String,   // ISO code; amounts are converted from USD
+    #[serde(default = "default_exchange_rate")]
+    pub exchange_rate: f64,         // display currency per USD
+    #[serde(default = "default_language")]
+    pub language: String,           // "auto" | "en" | "de" (tray, menubar letters, messages)
+
+    // Key rotation
+    #[serde(default = "default_key_rotation_interval_days")]
+    pub key_rotation_interval_days: u32,  // 0 = no scheduled rotation
+    #[serde(default = "default_key_rotation_reminder_days")]
+    pub key_rotation_reminder_days: u32,
+    #[serde(default = "default_false")]
+    pub opencode_sync_enabled: bool,      // keep ~/.local/share/opencode/auth.json in step
+    #[serde(default = "default_false")]
+    pub env_export_enabled: bool,         // write active.env / active.fish for shells and direnv
+
+    // Management (provisioning) key: activity data and team key admin, never used for balance
+    #[serde(default)]
+    pub provisioning_key: String,
+
+    // Local metering proxy (127.0.0.1 only)
+    #[serde(default = "default_false")]
+    pub proxy_enabled: bool,
+    #[serde(default = "default_proxy_port")]
+    pub proxy_port: u16,
+    #[serde(default = "default_true")]
+    pub proxy_guard_enabled: bool,        // reject requests once a budget is exhausted
+    #[serde(default = "default_proxy_guard_max_pace")]
+    pub proxy_guard_max_pace_percent: f64, // 0 = no pace cap
+    #[serde(default)]
+    pub proxy_allowed_models: Vec<String>, // empty = all; "vendor/*" allows a whole vendor
+    #[serde(default)]
+    pub proxy_project_caps: std::collections::BTreeMap<String, f64>, // USD per month
+    #[serde(default)]
+    pub proxy_guard_override_until: Option<String>, // RFC 3339; guard off until then
+
+    // Project tag for balance samples (tray submenu / `bp-esc cli project`)
+    #[serde(default)]
+    pub current_project: Option<String>,
+    #[serde(default)]
+    pub recent_projects: Vec<String>,
 
     // Mood / Team
     #[serde(default)]
@@ -235,6 +369,15 @@ fn default_zero() -> u32 { 0 }
 fn default_shortcut() -> String { "F19".to_string() }
 fn default_pace_warn_threshold() -> f64 { 20.0 }
 fn default_menubar_timeframe() -> String { "monthly".to_string() }
+fn default_icon_style() -> String { "hexagon".to_string() }
+fn default_number_locale() -> String { "auto".to_string() }
+fn default_display_currency() -> String { "USD".to_string() }
+fn default_exchange_rate() -> f64 { 1.0 }
+fn default_language() -> String { "auto".to_string() }
+fn default_key_rotation_interval_days() -> u32 { 90 }
+fn default_key_rotation_reminder_days() -> u32 { 14 }
+fn default_proxy_port() -> u16 { 8788 }
+fn default_proxy_guard_max_pace() -> f64 { 100.0 }
 
 impl Default for AppSettings {
     fn default() -> Self {
@@ -260,6 +403,28 @@ impl Default for AppSettings {
             menubar_monochrome: true,
             pace_warn_threshold: 20.0,
             menubar_timeframe: "monthly".to_string(),
+            icon_style: "hexagon".to_string(),
+            show_pace_marker: false,
+            menubar_template: String::new(),
+            compact_numbers: false,
+            number_locale: "auto".to_string(),
+            display_currency: "USD".to_string(),
+            exchange_rate: 1.0,
+            language: "auto".to_string(),
+            key_rotation_interval_days: 90,
+            key_rotation_reminder_days: 14,
+            opencode_sync_enabled: false,
+            env_export_enabled: false,
+            provisioning_key: String::new(),
+            proxy_enabled: false,
+            proxy_port: 8788,
+            proxy_guard_enabled: true,
+            proxy_guard_max_pace_percent: 100.0,
+            proxy_allowed_models: Vec::new(),
+            proxy_project_caps: std::collections::BTreeMap::new(),
+            proxy_guard_override_until: None,
+            current_project: None,
+            recent_projects: Vec::new(),
             mood_sheet_id: String::new(),
             mood_service_account_email: String::new(),
             mood_service_account_private_key: String::new(),
@@ -275,24 +440,52 @@ fn get_settings_file_path() -> Result<PathBuf, String> {
     Ok(config_dir.join("settings.json"))
 }
 
+/// Write a private (0600) file so readers see either the old or the new contents:
+/// a temp file next to it, created 0600, renamed over the target
+fn write_file_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    // Per process, so the app and a CLI call never share a temp file
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    // A leftover temp file would keep its old mode; `mode` only applies on creation
+    let _ = fs::remove_file(&tmp);
+    let written = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(&tmp)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .and_then(|_| fs::rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
 /// Internal function to save settings without an AppHandle
 fn save_settings_internal(settings: &AppSettings) -> Result<(), String> {
     let config_dir = get_config_dir()?;
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            .map_err(|e| tr!("settings.create_dir", e))?;
     }
     
     let path = get_settings_file_path()?;
     let contents = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    
-    fs::write(&path, contents)
-        .map_err(|e| format!("Failed to write settings: {}", e))?;
+        .map_err(|e| tr!("settings.serialize", e))?;
     
-    let perms = fs::Permissions::from_mode(0o600);
-    fs::set_permissions(&path, perms)
-        .map_err(|e| format!("Failed to set settings permissions: {}", e))?;
+    // The app and CLI calls (the project cd hook) read this file at any time
+    write_file_atomic(&path, &contents)
+        .map_err(|e| tr!("settings.write", e))?;
+
+    i18n::apply(settings);
+    // Every active-key change passes through here, so the exported env files follow it.
+    // settings.json is already written: a failed export must not fail the save.
+    if let Err(e) = env_export::refresh_env_files(settings) {
+        let _ = write_log(&format!("[Env export] {}", e));
+    }
     
     Ok(())
 }
@@ -305,58 +498,63 @@ fn read_settings() -> Result<AppSettings, String> {
         let mut s = AppSettings::default();
         if let Ok(Some(key)) = read_api_key() {
             s.api_key = Some(key.clone());
-            s.api_keys.push(ApiKeyConfig { key, label: "OpenRouter".to_string() });
+            s.api_keys.push(ApiKeyConfig::new(key, "OpenRouter".to_string()));
             let _ = save_settings_internal(&s); // Save migrated settings
         }
         s
     } else {
         let contents = fs::read_to_string(&path)
-            .map_err(|e| format!("Failed to read settings: {}", e))?;
+            .map_err(|e| tr!("settings.read", e))?;
         serde_json::from_str(&contents)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
+            .map_err(|e| tr!("settings.parse", e))?
     };
 
     // Migration for 0.3.0: If we have the legacy api_key but no api_keys list
     if settings.api_keys.is_empty() {
         if let Some(key) = settings.api_key.clone() {
-            settings.api_keys.push(ApiKeyConfig { key, label: "OpenRouter".to_string() });
+            settings.api_keys.push(ApiKeyConfig::new(key, "OpenRouter".to_string()));
             let _ = save_settings_internal(&settings);
         }
     }
+
+    // Migration for rotation metadata: keys saved before ids/dates existed
+    if ensure_key_metadata(&mut settings, None) {
+        let _ = save_settings_internal(&settings);
+    }
     
     Ok(settings)
 }
 
-#[tauri::command]
-fn read_opencode_openrouter_key() -> Result<String, String> {
-    let home = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
-    let auth_path = home
-        .join(".local")
-        .join("share")
-        .join("opencode")
-        .join("auth.json");
-
-    let contents = fs::read_to_string(&auth_path)
-        .map_err(|e| format!("Failed to read OpenCode auth file: {}", e))?;
-    let value: Value = serde_json::from_str(&contents)
-        .map_err(|e| format!("Failed to parse OpenCode auth file: {}", e))?;
-
-    extract_openrouter_key_from_value(&value)
-        .ok_or_else(|| "OpenRouter key not found in OpenCode auth file.".to_string())
-}
-
 #[tauri::command]
 fn copy_to_clipboard(app: AppHandle, text: String) -> Result<(), String> {
     app.clipboard()
         .write_text(text)
-        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+        .map_err(|e| tr!("app.clipboard", e))?;
     Ok(())
 }
 
 #[tauri::command]
 async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), String> {
+    // The frontend pushes new keys as plain {key, label}; carry over or create rotation metadata
+    let mut settings = settings;
+    menubar_format::parse(&settings.menubar_template).map_err(|e| tr!("settings.template", e))?;
+    number_format::validate(&settings)?;
+    i18n::validate(&settings.language)?;
+    let previous = read_settings().ok();
+    ensure_key_metadata(&mut settings, previous.as_ref());
     save_settings_internal(&settings)?;
+    // Menu labels are built in the current language
+    if previous.as_ref().map_or(true, |p| p.language != settings.language) {
+        refresh_tray_menu(&app);
+    }
+    // Removed keys drop their pinned icons; menus follow the language
+    pinned_trays::sync_later(&app);
+
+    // Opt-in: mirror an active-key switch into OpenCode's auth file
+    opencode::sync_after_settings_change(&app, previous.as_ref(), &settings);
+
+    // Start/stop the metering proxy; a busy port is reported by get_proxy_status
+    let _ = proxy::apply_settings(&app, &settings).await;
     
     // Update global shortcut
     let _ = update_app_shortcut(&app, &settings.global_shortcut, settings.global_shortcut_enabled);
@@ -376,6 +574,358 @@ async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), Stri
     
     Ok(())
 }
+// ============================================================================
+// KEY ROTATION
+// ============================================================================
+
+/// Today's local date as YYYY-MM-DD (the format used for all key dates)
+fn today_string() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn parse_key_date(value: &Option<String>) -> Option<chrono::NaiveDate> {
+    value
+        .as_deref()
+        .and_then(|s| chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok())
+}
+
+/// Time-based id; only needs to be unique within one settings file
+fn generate_key_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("key-{:x}{:02x}", nanos, seq & 0xff)
+}
+
+/// Fill in id/created_at for keys that lack them. Metadata is copied from the
+/// previously stored entry with the same secret first, so saves from the frontend
+/// (which pushes freshly added keys as plain {key, label}) don't churn ids.
+/// Returns true if anything changed.
+fn ensure_key_metadata(settings: &mut AppSettings, previous: Option<&AppSettings>) -> bool {
+    let mut changed = false;
+    for cfg in settings.api_keys.iter_mut() {
+        if cfg.id.is_empty() {
+            let stored = previous.and_then(|p| {
+                p.api_keys.iter().find(|k| k.key == cfg.key && !k.id.is_empty())
+            });
+            if let Some(stored) = stored {
+                cfg.id = stored.id.clone();
+                cfg.created_at = cfg.created_at.take().or_else(|| stored.created_at.clone());
+                cfg.rotated_at = cfg.rotated_at.take().or_else(|| stored.rotated_at.clone());
+                cfg.expires_at = cfg.expires_at.take().or_else(|| stored.expires_at.clone());
+                cfg.successor_key = cfg.successor_key.take().or_else(|| stored.successor_key.clone());
+                cfg.verified = cfg.verified.take().or_else(|| stored.verified.clone());
+                cfg.limit_reset = cfg.limit_reset.take().or_else(|| stored.limit_reset.clone());
+            } else {
+                cfg.id = generate_key_id();
+            }
+            changed = true;
+        }
+        if cfg.created_at.is_none() {
+            cfg.created_at = Some(today_string());
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Next date a key should be rotated: the explicit expiry or the rotation interval
+/// counted from the last rotation (or creation), whichever comes first.
+fn key_rotation_due_date(cfg: &ApiKeyConfig, settings: &AppSettings) -> Option<chrono::NaiveDate> {
+    let scheduled = if settings.key_rotation_interval_days > 0 {
+        parse_key_date(&cfg.rotated_at)
+            .or_else(|| parse_key_date(&cfg.created_at))
+            .map(|date| date + chrono::Duration::days(settings.key_rotation_interval_days as i64))
+    } else {
+        None
+    };
+
+    match (parse_key_date(&cfg.expires_at), scheduled) {
+        (Some(expires), Some(scheduled)) => Some(expires.min(scheduled)),
+        (expires, scheduled) => expires.or(scheduled),
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyRotationReminder {
+    pub index: usize,
+    pub id: String,
+    pub label: String,
+    pub due_date: String,
+    pub days_left: i64,       // negative = overdue
+    pub has_successor: bool,
+}
+
+/// Keys whose rotation date falls within the reminder window (or has passed)
+fn collect_key_rotation_reminders(settings: &AppSettings) -> Vec<KeyRotationReminder> {
+    let today = Local::now().date_naive();
+    let window = settings.key_rotation_reminder_days as i64;
+
+    settings.api_keys.iter().enumerate().filter_map(|(index, cfg)| {
+        let due = key_rotation_due_date(cfg, settings)?;
+        let days_left = (due - today).num_days();
+        if days_left > window {
+            return None;
+        }
+        Some(KeyRotationReminder {
+            index,
+            id: cfg.id.clone(),
+            label: cfg.label.clone(),
+            due_date: due.format("%Y-%m-%d").to_string(),
+            days_left,
+            has_successor: cfg.successor_key.is_some(),
+        })
+    }).collect()
+}
+
+fn rotation_reminder_text(reminder: &KeyRotationReminder) -> String {
+    match reminder.days_left {
+        d if d < 0 => tr!("rotation.overdue", reminder.label, -d),
+        0 => tr!("rotation.today", reminder.label),
+        1 => tr!("rotation.tomorrow", reminder.label),
+        d => tr!("rotation.due_in", reminder.label, d),
+    }
+}
+
+/// Emit rotation reminders to the frontend at most once per day and keep the tray menu current
+fn notify_key_rotation_reminders(app: &AppHandle, last_notice: &Mutex<Option<String>>) {
+    let Ok(settings) = read_settings() else { return };
+    let reminders = collect_key_rotation_reminders(&settings);
+    if reminders.is_empty() {
+        return;
+    }
+
+    let today = today_string();
+    if let Ok(mut notice) = last_notice.lock() {
+        if notice.as_deref() == Some(today.as_str()) {
+            return;
+        }
+        *notice = Some(today);
+    }
+
+    let _ = app.emit("key-rotation-due", &reminders);
+    refresh_tray_menu(app);
+}
+
+#[tauri::command]
+fn get_key_rotation_reminders() -> Result<Vec<KeyRotationReminder>, String> {
+    let settings = read_settings()?;
+    Ok(collect_key_rotation_reminders(&settings))
+}
+
+/// Stage (or clear with None) the successor key for the overlap window.
+/// The current key stays active until rotate_api_key promotes the successor.
+#[tauri::command]
+fn set_successor_key(index: usize, key: Option<String>) -> Result<AppSettings, String> {
+    let mut settings = read_settings()?;
+    let key = key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty());
+    if let Some(ref key) = key {
+        validate_api_key(key)?;
+    }
+
+    let cfg = settings.api_keys.get_mut(index)
+        .ok_or_else(|| tr!("key.not_found", index + 1))?;
+    if key.as_deref() == Some(cfg.key.as_str()) {
+        return Err(tr!("key.successor_same"));
+    }
+    cfg.successor_key = key;
+
+    save_settings_internal(&settings)?;
+    Ok(settings)
+}
+
+/// Swap a key in place: the new key (or the staged successor) is verified live,
+/// then replaces the secret while id, label and position stay untouched.
+#[tauri::command]
+async fn rotate_api_key(app: AppHandle, index: usize, new_key: Option<String>) -> Result<AppSettings, String> {
+    let mut settings = read_settings()?;
+    let current = settings.api_keys.get(index)
+        .ok_or_else(|| tr!("key.not_found", index + 1))?
+        .clone();
+
+    let new_key = new_key
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .or_else(|| current.successor_key.clone())
+        .ok_or_else(|| tr!("key.rotate_missing"))?;
+
+    if new_key == current.key {
+        return Err(tr!("key.new_same"));
+    }
+    if let Some((_, existing)) = find_duplicate_key(&settings, &new_key, Some(index)) {
+        return Err(tr!("key.duplicate", existing.label));
+    }
+
+    // Live check first — a typo must never replace a working key
+    let verification = verify_key_live(&app, &new_key)
+        .await
+        .map_err(|e| tr!("key.rotate_failed", e))?;
+
+    {
+        let cfg = &mut settings.api_keys[index];
+        cfg.key = new_key.clone();
+        cfg.verified = Some(verification);
+        cfg.rotated_at = Some(today_string());
+        cfg.expires_at = None; // the expiry belonged to the retired key
+        cfg.successor_key = None;
+    }
+    save_settings_internal(&settings)?;
+    let _ = log_message(app.clone(), format!("[INFO] Rotated API key '{}'", current.label));
+
+    // Only touch OpenCode if it was using the key we just retired
+    if settings.opencode_sync_enabled {
+        let opencode_had_old_key = opencode::read_opencode_openrouter_key()
+            .map(|key| key == current.key)
+            .unwrap_or(false);
+        if opencode_had_old_key {
+            if let Err(e) = opencode::push_key(&new_key) {
+                let _ = log_message(app.clone(), format!("[ERROR] Failed to update OpenCode auth file: {}", e));
+            }
+        }
+    }
+
+    let _ = app.emit("settings-updated", &settings);
+    refresh_tray_menu(&app);
+    Ok(settings)
+}
+
+// ============================================================================
+// KEY VERIFICATION
+// ============================================================================
+
+/// Result of checking a key before it is saved
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyCheckResult {
+    pub masked_key: String,
+    pub verification: KeyVerification,
+    pub duplicate_index: Option<usize>,
+    pub duplicate_label: Option<String>,
+}
+
+/// Position and entry of a configured key with the same secret, ignoring `exclude`
+fn find_duplicate_key<'a>(
+    settings: &'a AppSettings,
+    key: &str,
+    exclude: Option<usize>,
+) -> Option<(usize, &'a ApiKeyConfig)> {
+    let key = key.trim();
+    settings
+        .api_keys
+        .iter()
+        .enumerate()
+        .find(|(i, k)| Some(*i) != exclude && k.key.trim() == key)
+}
+
+/// Format check plus a live call to /api/v1/key; errors carry the provider's reason
+async fn verify_key_live(app: &AppHandle, key: &str) -> Result<KeyVerification, String> {
+    let balance = fetch_balance(app.clone(), key.trim().to_string()).await?;
+    Ok(KeyVerification {
+        verified_at: today_string(),
+        server_label: balance.label.filter(|l| !l.trim().is_empty()),
+        limit: balance.limit,
+        is_provisioning_key: balance.is_provisioning_key.unwrap_or(false),
+    })
+}
+
+/// Check a key without saving it (duplicates are reported, not rejected)
+#[tauri::command]
+async fn verify_api_key(app: AppHandle, key: String, exclude_index: Option<usize>) -> Result<KeyCheckResult, String> {
+    let key = key.trim().to_string();
+    validate_api_key(&key)?;
+    let settings = read_settings()?;
+    let duplicate = find_duplicate_key(&settings, &key, exclude_index)
+        .map(|(i, k)| (i, k.label.clone()));
+
+    let verification = verify_key_live(&app, &key).await?;
+    Ok(KeyCheckResult {
+        masked_key: mask_key(&key),
+        verification,
+        duplicate_index: duplicate.as_ref().map(|(i, _)| *i),
+        duplicate_label: duplicate.map(|(_, label)| label),
+    })
+}
+
+/// Verify and append a key, then make it active. The label falls back to the
+/// server-side label, then to "Key N".
+#[tauri::command]
+async fn add_api_key(app: AppHandle, key: String, label: Option<String>) -> Result<AppSettings, String> {
+    let key = key.trim().to_string();
+    validate_api_key(&key)?;
+
+    let mut settings = read_settings()?;
+    if let Some((_, existing)) = find_duplicate_key(&settings, &key, None) {
+        return Err(tr!("key.duplicate", existing.label));
+    }
+
+    let verification = verify_key_live(&app, &key)
+        .await
+        .map_err(|e| tr!("key.validation_failed", e))?;
+
+    let label = label
+        .map(|l| l.trim().to_string())
+        .filter(|l| !l.is_empty())
+        .or_else(|| verification.server_label.clone())
+        .unwrap_or_else(|| tr!("key.default_label", settings.api_keys.len() + 1));
+
+    let mut cfg = ApiKeyConfig::new(key, label);
+    cfg.verified = Some(verification);
+    settings.api_keys.push(cfg);
+    settings.active_api_key_index = settings.api_keys.len() - 1;
+
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    refresh_tray_menu(&app);
+    Ok(settings)
+}
+
+/// Reset period to assume for a key whose limit OpenRouter reports without one
+#[tauri::command]
+fn set_key_limit_reset(app: AppHandle, index: usize, period: Option<String>) -> Result<AppSettings, String> {
+    let period = period.filter(|p| !p.is_empty());
+    if let Some(p) = period.as_deref() {
+        if !matches!(p, "daily" | "weekly" | "monthly") {
+            return Err(tr!("key.unknown_reset", p));
+        }
+    }
+
+    let mut settings = read_settings()?;
+    let cfg = settings.api_keys.get_mut(index)
+        .ok_or_else(|| tr!("key.not_found", index + 1))?;
+    cfg.limit_reset = period;
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+/// Verify and store the management key (empty clears it). Only keys the server
+/// reports as provisioning keys are accepted.
+#[tauri::command]
+async fn set_provisioning_key(app: AppHandle, key: String) -> Result<AppSettings, String> {
+    let key = key.trim().to_string();
+    let mut settings = read_settings()?;
+
+    if !key.is_empty() {
+        validate_api_key(&key)?;
+        let verification = verify_key_live(&app, &key)
+            .await
+            .map_err(|e| tr!("key.provisioning_failed", e))?;
+        if !verification.is_provisioning_key {
+            return Err(tr!("key.not_provisioning"));
+        }
+    }
+
+    settings.provisioning_key = key;
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+// ============================================================================
 // MENUBAR ICON CONFIGURATION
 // ============================================================================
 // Adjust these constants to fine-tune the percentage text appearance in the menubar icon
@@ -383,7 +933,7 @@ async fn save_settings(app: AppHandle, settings: AppSettings) -> Result<(), Stri
 /// Logical size of the logo in points (square)
 const MENUBAR_LOGO_SIZE: u32 = 18;
 
-/// Scaling factor for Retina displays (2.0 for @2x)
+/// Pixels per point when no monitor reports a scale factor (Retina, @2x)
 const MENUBAR_RENDER_SCALE: f32 = 2.0;
 
 // --- VALUE CONFIGURATION (the number, e.g., "42") ---
@@ -446,19 +996,22 @@ unsafe fn register_appearance_observer_class() -> &'static Class {
             let app_handle = &*(ptr as *const AppHandle);
             
             // Re-render menubar icon with new appearance
-            if let Some(state) = app_handle.try_state::<MenubarState>() {
-                let balance = state.balance.lock().ok().and_then(|stored| stored.clone());
-                let settings = state.settings.lock().ok().and_then(|stored| stored.clone());
-                
-                if let (Some(balance), Some(settings)) = (balance, settings) {
-                    if let Err(e) = update_menubar_display(app_handle.clone(), balance, settings) {
-                        eprintln!("[KVO] Failed to update menubar on appearance change: {}", e);
-                    }
-                } else {
-                    eprintln!("[KVO] No cached balance/settings available for re-render");
-                }
-            } else {
-                eprintln!("[KVO] MenubarState not found in AppHandle");
+            if let Err(e) = redraw_menubar_from_cache(app_handle) {
+                eprintln!("[KVO] Failed to update menubar on appearance change: {}", e);
+            }
+        }
+    }
+
+    // NSApplicationDidChangeScreenParametersNotification: a display was added, removed or rescaled
+    extern "C" fn screen_parameters_changed(this: &Object, _sel: Sel, _notification: id) {
+        unsafe {
+            let ptr: *mut c_void = *this.get_ivar("app_handle_ptr");
+            if ptr.is_null() {
+                return;
+            }
+            let app_handle = &*(ptr as *const AppHandle);
+            if let Err(e) = redraw_menubar_from_cache(app_handle) {
+                eprintln!("[Screens] Failed to update menubar on display change: {}", e);
             }
         }
     }
@@ -468,6 +1021,10 @@ unsafe fn register_appearance_observer_class() -> &'static Class {
             sel!(observeValueForKeyPath:ofObject:change:context:),
             observe_value as extern "C" fn(&Object, Sel, id, id, id, *mut c_void),
         );
+        decl.add_method(
+            sel!(screenParametersChanged:),
+            screen_parameters_changed as extern "C" fn(&Object, Sel, id),
+        );
     }
     
     decl.register()
@@ -517,6 +1074,18 @@ unsafe fn setup_appearance_observer(app_handle: AppHandle) {
         context: context
     ];
     
+    // Re-query monitor scales when displays change; the window may be hidden the whole time
+    let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+    let name = NSString::alloc(cocoa::base::nil).init_str("NSApplicationDidChangeScreenParametersNotification");
+    let _: () = msg_send![
+        center,
+        addObserver: observer
+        selector: sel!(screenParametersChanged:)
+        name: name
+        object: cocoa::base::nil
+    ];
+    let _: () = msg_send![name, release];
+
     // Leak observer to keep it alive for app lifetime
     let _: id = msg_send![observer, retain];
     
@@ -585,6 +1154,7 @@ fn is_german_locale() -> bool {
         
         let lang_code_key = NSString::alloc(cocoa::base::nil).init_str("kCFLocaleLanguageCodeKey");
         let lang_code: id = msg_send![locale, objectForKey: lang_code_key];
+        let _: () = msg_send![lang_code_key, release];
         
         if lang_code.is_null() {
             return false;
@@ -613,7 +1183,7 @@ fn is_german_locale() -> bool {
 /// Get the config directory path: ~/.config/bpesc-balance/
 fn get_config_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir()
-        .ok_or_else(|| "Could not determine home directory".to_string())?;
+        .ok_or_else(|| tr!("file.no_home"))?;
     
     let config_dir = home
         .join(".config")
@@ -628,100 +1198,6 @@ fn get_env_file_path() -> Result<PathBuf, String> {
     Ok(config_dir.join(".env"))
 }
 
-fn extract_key_from_object(obj: &serde_json::Map<String, Value>) -> Option<String> {
-    let key_fields = ["api_key", "apiKey", "key", "token"];
-    for field in key_fields {
-        if let Some(Value::String(value)) = obj.get(field) {
-            let trimmed = value.trim();
-            if !trimmed.is_empty() {
-                return Some(trimmed.to_string());
-            }
-        }
-    }
-
-    if let Some(Value::Object(creds)) = obj.get("credentials") {
-        if let Some(key) = extract_key_from_object(creds) {
-            return Some(key);
-        }
-    }
-
-    None
-}
-
-fn is_openrouter_entry(obj: &serde_json::Map<String, Value>) -> bool {
-    let keys = ["provider", "name", "id"];
-    keys.iter().any(|field| {
-        obj.get(*field)
-            .and_then(|value| value.as_str())
-            .map(|value| value.eq_ignore_ascii_case("openrouter"))
-            .unwrap_or(false)
-    })
-}
-
-fn extract_key_from_provider(value: &Value) -> Option<String> {
-    if let Value::Object(obj) = value {
-        if let Some(key) = extract_key_from_object(obj) {
-            return Some(key);
-        }
-        if let Some(Value::Object(auth)) = obj.get("auth") {
-            if let Some(key) = extract_key_from_object(auth) {
-                return Some(key);
-            }
-        }
-    }
-    None
-}
-
-fn extract_openrouter_key_from_value(value: &Value) -> Option<String> {
-    if let Value::Object(obj) = value {
-        if let Some(Value::Object(providers)) = obj.get("providers") {
-            if let Some(provider) = providers.get("openrouter") {
-                if let Some(key) = extract_key_from_provider(provider) {
-                    return Some(key);
-                }
-            }
-        }
-
-        if let Some(provider) = obj.get("openrouter") {
-            if let Some(key) = extract_key_from_provider(provider) {
-                return Some(key);
-            }
-        }
-
-        if let Some(Value::Array(providers)) = obj.get("providers") {
-            for provider in providers {
-                if let Value::Object(entry) = provider {
-                    if is_openrouter_entry(entry) {
-                        if let Some(key) = extract_key_from_object(entry) {
-                            return Some(key);
-                        }
-                        if let Some(key) = extract_key_from_provider(provider) {
-                            return Some(key);
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    if let Value::Array(items) = value {
-        for item in items {
-            if let Value::Object(obj) = item {
-                if is_openrouter_entry(obj) {
-                    if let Some(key) = extract_key_from_object(obj) {
-                        return Some(key);
-                    }
-                    if let Some(key) = extract_key_from_provider(item) {
-                        return Some(key);
-                    }
-                }
-            }
-        }
-    }
-
-    None
-}
-
 /// Read API key from ~/.config/bpesc-balance/.env
 /// Open the error log file in the system default editor
 #[tauri::command]
@@ -732,7 +1208,7 @@ fn open_error_log() -> Result<(), String> {
     if log_path.exists() {
         open::that(log_path).map_err(|e| e.to_string())?;
     } else {
-        return Err("Log file does not exist yet.".to_string());
+        return Err(tr!("log.missing"));
     }
     Ok(())
 }
@@ -745,13 +1221,18 @@ fn open_app_log() -> Result<(), String> {
     if log_path.exists() {
         open::that(log_path).map_err(|e| e.to_string())?;
     } else {
-        return Err("Log file does not exist yet.".to_string());
+        return Err(tr!("log.missing"));
     }
     Ok(())
 }
 
 #[tauri::command]
 fn log_message(_app: AppHandle, message: String) -> Result<(), String> {
+    write_log(&message)
+}
+
+/// Append to app.log without an AppHandle (shared with the headless CLI)
+fn write_log(message: &str) -> Result<(), String> {
     let settings = match read_settings() {
         Ok(s) => s,
         Err(_) => return Ok(()), // Silent fail if settings unreadable
@@ -837,7 +1318,7 @@ fn read_api_key() -> Result<Option<String>, String> {
     
     // Read file contents
     let contents = fs::read_to_string(&env_path)
-        .map_err(|e| format!("Failed to read .env file: {}", e))?;
+        .map_err(|e| tr!("file.env_read", e))?;
     
     // Parse OPENROUTER_API_KEY=value
     for line in contents.lines() {
@@ -868,20 +1349,37 @@ fn validate_api_key(key: &str) -> Result<(), String> {
     let key = key.trim();
     
     if key.is_empty() {
-        return Err("API key cannot be empty".to_string());
+        return Err(tr!("key.empty"));
     }
     
     if !key.starts_with("sk-") {
-        return Err("API key must start with 'sk-'".to_string());
+        return Err(tr!("key.prefix"));
     }
     
     if key.len() < 20 {
-        return Err("API key is too short".to_string());
+        return Err(tr!("key.too_short"));
     }
     
     Ok(())
 }
 
+/// FNV-1a fingerprint — identifies a key in state files and the UI without storing it twice
+fn key_fingerprint(key: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in key.trim().as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Last four characters, matching the key list in the settings view
+fn mask_key(key: &str) -> String {
+    let chars: Vec<char> = key.trim().chars().collect();
+    let tail: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("\u{2022}\u{2022}\u{2022}\u{2022}{}", tail)
+}
+
 /// Save API key to ~/.config/bpesc-balance/.env
 #[tauri::command]
 fn save_api_key(key: String) -> Result<(), String> {
@@ -894,37 +1392,41 @@ fn save_api_key(key: String) -> Result<(), String> {
     // Create config directory if it doesn't exist
     if !config_dir.exists() {
         fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+            .map_err(|e| tr!("settings.create_dir", e))?;
         
         // Set directory permissions to 755 (rwxr-xr-x) - macOS
         let perms = fs::Permissions::from_mode(0o755);
         fs::set_permissions(&config_dir, perms)
-            .map_err(|e| format!("Failed to set directory permissions: {}", e))?;
+            .map_err(|e| tr!("file.env_dir_permissions", e))?;
     }
     
     // Write .env file
     let content = format!("OPENROUTER_API_KEY={}\n", key.trim());
     fs::write(&env_path, content)
-        .map_err(|e| format!("Failed to write .env file: {}", e))?;
+        .map_err(|e| tr!("file.env_write", e))?;
     
     // Set file permissions to 600 (rw-------) - macOS
     let perms = fs::Permissions::from_mode(0o600);
     fs::set_permissions(&env_path, perms)
-        .map_err(|e| format!("Failed to set file permissions: {}", e))?;
+        .map_err(|e| tr!("file.env_permissions", e))?;
     
     Ok(())
 }
 
 /// Balance data returned from OpenRouter API
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct BalanceData {
     pub limit: Option<f64>,
     pub usage: Option<f64>,
     pub usage_daily: Option<f64>,
     pub usage_weekly: Option<f64>,
     pub usage_monthly: Option<f64>,
+    /// Usage in the current reset period of the limit (the month unless it resets daily/weekly)
+    pub usage_period: Option<f64>,
     pub remaining: Option<f64>,
+    /// Left of the key limit in its current reset period (OpenRouter's `limit_remaining`)
     pub remaining_monthly: Option<f64>,
+    /// How far through the reset period we are (0..1)
     pub pace_ratio: Option<f64>,
     pub pace_month_target: Option<f64>,
     pub pace_week_target: Option<f64>,
@@ -934,6 +1436,220 @@ pub struct BalanceData {
     pub pace_day_delta_percent: Option<f64>,
     pub pace_status: Option<String>,
     pub label: Option<String>,
+    pub is_provisioning_key: Option<bool>,
+    /// Free-tier keys have much lower request limits and only free models
+    pub is_free_tier: Option<bool>,
+    pub rate_limit: Option<RateLimit>,
+    /// Server-side reset period of `limit`: "daily" | "weekly" | "monthly" (None = never)
+    pub limit_reset: Option<String>,
+    /// Prepaid account credits shared by all keys (None if the endpoint was unavailable)
+    pub account: Option<AccountCredits>,
+    /// Budget of the binding constraint for the reset period: the key limit or usage + account credits left
+    pub effective_limit: Option<f64>,
+    pub binding_constraint: Option<String>, // "key" | "account"
+}
+
+/// Request rate limit reported by /api/v1/key, e.g. 10 requests per "10s"
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RateLimit {
+    #[serde(default)]
+    pub requests: i64,
+    #[serde(default)]
+    pub interval: String,
+}
+
+impl RateLimit {
+    /// Interval like "10s", "1m" or "1h" as a duration
+    fn interval_duration(&self) -> Option<Duration> {
+        let interval = self.interval.trim();
+        let split = interval.find(|c: char| !c.is_ascii_digit()).unwrap_or(interval.len());
+        let amount: u64 = interval[..split].parse().ok()?;
+        let unit = match &interval[split..] {
+            "" | "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86_400,
+            _ => return None,
+        };
+        Some(Duration::from_secs(amount.checked_mul(unit)?))
+    }
+
+    /// Minimum spacing for BP-ESC's own requests: it may use at most
+    /// 1/RATE_LIMIT_SHARE_DIVISOR of the key's request budget
+    fn min_request_gap(&self) -> Option<Duration> {
+        if self.requests <= 0 {
+            return None;
+        }
+        // Limits beyond u32::MAX requests leave no meaningful gap anyway
+        let requests = u32::try_from(self.requests).unwrap_or(u32::MAX);
+        let per_request = self.interval_duration()? / requests;
+        per_request.checked_mul(RATE_LIMIT_SHARE_DIVISOR)
+    }
+}
+
+/// Account-level credits from /api/v1/credits
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AccountCredits {
+    pub total_credits: f64,
+    pub total_usage: f64,
+    pub remaining: f64,
+}
+
+impl BalanceData {
+    /// Anything the menubar can show (monthly, legacy, or limit/usage)
+    fn has_data(&self) -> bool {
+        self.remaining_monthly.is_some()
+            || self.usage_monthly.is_some()
+            || self.remaining.is_some()
+            || self.usage.is_some()
+            || self.limit.is_some()
+    }
+
+    /// Budget of the current reset period the menubar and pace logic work against
+    fn budget_limit(&self) -> Option<f64> {
+        self.effective_limit.or(self.limit)
+    }
+
+    /// Usage counted against `budget_limit`
+    pub(crate) fn budget_usage(&self) -> Option<f64> {
+        self.usage_period.or(self.usage_monthly).or(self.usage)
+    }
+
+    /// Remaining in the current reset period under the binding constraint
+    pub(crate) fn budget_remaining(&self) -> Option<f64> {
+        match (self.effective_limit, self.budget_usage()) {
+            (Some(limit), Some(usage)) => Some(limit - usage),
+            _ => self.remaining_monthly.or(self.remaining),
+        }
+    }
+
+    /// Whether a timeframe ("daily" | "weekly" | "monthly") covers the whole reset period;
+    /// such timeframes show the period's usage and budget instead of a pro-rata share
+    pub(crate) fn spans_period(&self, timeframe: &str) -> bool {
+        let rank = |period: Option<&str>| match period {
+            Some("daily") => 0,
+            Some("weekly") => 1,
+            _ => 2,
+        };
+        rank(Some(timeframe)) >= rank(self.limit_reset.as_deref())
+    }
+
+    /// Usage of a timeframe measured against its budget
+    pub(crate) fn timeframe_usage(&self, timeframe: &str) -> Option<f64> {
+        if self.spans_period(timeframe) {
+            return self.budget_usage();
+        }
+        match timeframe {
+            "daily" => self.usage_daily,
+            _ => self.usage_weekly,
+        }
+    }
+
+    /// Budget of a timeframe: the period budget, or its share for shorter timeframes
+    pub(crate) fn timeframe_budget(&self, timeframe: &str, days_in_month: f64) -> Option<f64> {
+        let limit = self.budget_limit().filter(|limit| *limit > 0.0)?;
+        if self.spans_period(timeframe) {
+            return Some(limit);
+        }
+        Some(limit / self.period_days(days_in_month) * limit_period_days(Some(timeframe), days_in_month))
+    }
+
+    /// Pace target of a timeframe
+    pub(crate) fn timeframe_target(&self, timeframe: &str) -> Option<f64> {
+        match timeframe {
+            "weekly" => self.pace_week_target,
+            "daily" => self.pace_day_target,
+            _ => self.pace_month_target,
+        }
+    }
+
+    /// Days in the reset period (the month for monthly keys and keys that never reset)
+    pub(crate) fn period_days(&self, days_in_month: f64) -> f64 {
+        limit_period_days(self.limit_reset.as_deref(), days_in_month)
+    }
+
+    /// Budget for a whole month, as monthly reports pace against it. A daily or weekly key
+    /// limit allows that many periods' worth; account credits are not spread over periods.
+    pub(crate) fn monthly_budget(&self, days_in_month: f64) -> Option<f64> {
+        let period_days = self.period_days(days_in_month);
+        if period_days == days_in_month {
+            return self.budget_limit();
+        }
+        self.limit.map(|limit| limit * days_in_month / period_days)
+    }
+
+    fn update_pace_deltas(&mut self) {
+        let delta = |usage: Option<f64>, target: Option<f64>| match (usage, target) {
+            (Some(usage), Some(target)) if target > 0.0 => Some(((usage - target) / target) * 100.0),
+            _ => None,
+        };
+        self.pace_month_delta_percent = delta(self.timeframe_usage("monthly"), self.pace_month_target);
+        self.pace_week_delta_percent = delta(self.timeframe_usage("weekly"), self.pace_week_target);
+        self.pace_day_delta_percent = delta(self.timeframe_usage("daily"), self.pace_day_target);
+        self.pace_status = self
+            .pace_month_delta_percent
+            .map(|d| pace_status_from_delta(d, default_pace_warn_threshold()).to_string());
+    }
+
+    /// Count spend seen by the metering proxy before /api/v1/key reports it
+    fn add_spend(&mut self, cost: f64) {
+        for usage in [&mut self.usage, &mut self.usage_daily, &mut self.usage_weekly, &mut self.usage_monthly, &mut self.usage_period] {
+            if let Some(usage) = usage {
+                *usage += cost;
+            }
+        }
+        for remaining in [&mut self.remaining, &mut self.remaining_monthly] {
+            if let Some(remaining) = remaining {
+                *remaining -= cost;
+            }
+        }
+        if let Some(account) = &mut self.account {
+            account.total_usage += cost;
+            account.remaining -= cost;
+        }
+        self.update_pace_deltas();
+    }
+}
+
+/// BP-ESC never uses more than a quarter of a key's request rate limit
+const RATE_LIMIT_SHARE_DIVISOR: u32 = 4;
+
+/// Floor for repeated balance requests when the key reports no rate limit
+const MIN_BALANCE_REQUEST_GAP: Duration = Duration::from_secs(2);
+
+/// Last balance per key (by fingerprint) and how long it shields the API
+#[derive(Default)]
+struct BalanceThrottle {
+    entries: Mutex<std::collections::HashMap<String, (std::time::Instant, Duration, BalanceData)>>,
+}
+
+impl BalanceThrottle {
+    fn recent(&self, api_key: &str) -> Option<BalanceData> {
+        let entries = self.entries.lock().ok()?;
+        let (fetched_at, gap, balance) = entries.get(&key_fingerprint(api_key))?;
+        (fetched_at.elapsed() < *gap).then(|| balance.clone())
+    }
+
+    fn record(&self, api_key: &str, balance: &BalanceData) {
+        let gap = balance
+            .rate_limit
+            .as_ref()
+            .and_then(RateLimit::min_request_gap)
+            .unwrap_or(MIN_BALANCE_REQUEST_GAP)
+            .max(MIN_BALANCE_REQUEST_GAP);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key_fingerprint(api_key), (std::time::Instant::now(), gap, balance.clone()));
+        }
+    }
+
+    /// Largest guard window of any key, so the refresh timer never undercuts it
+    fn max_gap(&self) -> Duration {
+        self.entries
+            .lock()
+            .ok()
+            .and_then(|entries| entries.values().map(|(_, gap, _)| *gap).max())
+            .unwrap_or(MIN_BALANCE_REQUEST_GAP)
+    }
 }
 
 #[derive(Default)]
@@ -941,6 +1657,10 @@ struct MenubarState {
     balance: Mutex<Option<BalanceData>>,
     settings: Mutex<Option<AppSettings>>,
     is_dark: Mutex<bool>,
+    /// Icon scales of the connected monitors, sharpest last (queried on the main thread)
+    scales: Mutex<Vec<f32>>,
+    /// Last fresh fetch of the active key (shown in the tray tooltip)
+    refreshed_at: Mutex<Option<chrono::DateTime<Local>>>,
 }
 
 /// Response from OpenRouter API /api/v1/key endpoint
@@ -950,6 +1670,71 @@ struct OpenRouterResponse {
     error: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenRouterCreditsResponse {
+    data: Option<OpenRouterCreditsData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenRouterCreditsData {
+    total_credits: Option<f64>,
+    total_usage: Option<f64>,
+}
+
+/// Fetch account credits; failures are logged and treated as "unknown" so the
+/// key balance still shows (the endpoint can be unavailable for some key types)
+async fn fetch_account_credits(client: &reqwest::Client, api_key: &str) -> Option<AccountCredits> {
+    let response = client
+        .get("https://openrouter.ai/api/v1/credits")
+        .header("Authorization", format!("Bearer {}", api_key.trim()))
+        .send()
+        .await;
+
+    let response = match response {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            let _ = write_log(&format!("[INFO] Account credits unavailable: status {}", response.status()));
+            return None;
+        }
+        Err(e) => {
+            let _ = write_log(&format!("[INFO] Account credits unavailable: {}", e));
+            return None;
+        }
+    };
+
+    let data = response.json::<OpenRouterCreditsResponse>().await.ok()?.data?;
+    let total_credits = data.total_credits?;
+    let total_usage = data.total_usage.unwrap_or(0.0);
+    Some(AccountCredits {
+        total_credits,
+        total_usage,
+        remaining: total_credits - total_usage,
+    })
+}
+
+/// Pick the tighter of the key limit and the account credits. Account credits do
+/// not reset, so they become a budget of the period's usage so far + what's left.
+fn binding_budget(
+    key_limit: Option<f64>,
+    usage_period: Option<f64>,
+    account: Option<&AccountCredits>,
+) -> (Option<f64>, Option<String>) {
+    let key_remaining = match (key_limit, usage_period) {
+        (Some(limit), Some(usage)) => Some(limit - usage),
+        (Some(limit), None) => Some(limit),
+        _ => None,
+    };
+
+    match (key_remaining, account) {
+        (key_remaining, Some(account)) if key_remaining.map_or(true, |r| account.remaining < r) => (
+            Some(usage_period.unwrap_or(0.0) + account.remaining.max(0.0)),
+            Some("account".to_string()),
+        ),
+        (Some(_), _) => (key_limit, Some("key".to_string())),
+        (None, _) => (None, None),
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenRouterData {
     limit: Option<f64>,
@@ -960,19 +1745,44 @@ struct OpenRouterData {
     limit_remaining: Option<f64>,
     #[serde(default)]
     label: Option<String>,
+    #[serde(default)]
+    is_provisioning_key: Option<bool>,
+    #[serde(default)]
+    is_free_tier: Option<bool>,
+    #[serde(default)]
+    rate_limit: Option<RateLimit>,
+    #[serde(default)]
+    limit_reset: Option<String>,
 }
 
-/// Fetch balance from OpenRouter API
+/// Fetch balance from OpenRouter API. Repeated calls for the same key inside the
+/// rate-limit guard window return the last result instead of hitting the API.
 #[tauri::command]
 async fn fetch_balance(app: AppHandle, api_key: String) -> Result<BalanceData, String> {
+    let throttle = app.try_state::<BalanceThrottle>();
+    if let Some(cached) = throttle.as_ref().and_then(|t| t.recent(&api_key)) {
+        return Ok(cached);
+    }
+
+    let balance = fetch_balance_internal(&api_key).await?;
+    if let Some(throttle) = throttle {
+        throttle.record(&api_key, &balance);
+    }
+    projects::record_balance_sample(&api_key, &balance);
+    tray_status::mark_refreshed(&app, &api_key);
+    Ok(balance)
+}
+
+/// /api/v1/key + /api/v1/credits without an AppHandle (used by the CLI)
+async fn fetch_balance_internal(api_key: &str) -> Result<BalanceData, String> {
     // Validate API key format
-    validate_api_key(&api_key)?;
+    validate_api_key(api_key)?;
     
     // Create HTTP client with timeout
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(10))
         .build()
-        .map_err(|_| "Could not initialize network client. Please restart the app.".to_string())?;
+        .map_err(|_| tr!("net.client"))?;
     
     // Make request to OpenRouter API
     let response = client
@@ -983,11 +1793,11 @@ async fn fetch_balance(app: AppHandle, api_key: String) -> Result<BalanceData, S
         .await
         .map_err(|e| {
             if e.is_timeout() {
-                "Request timed out. Check your internet connection.".to_string()
+                tr!("net.timeout")
             } else if e.is_connect() {
-                "Could not connect to OpenRouter. Check your internet connection.".to_string()
+                tr!("net.connect")
             } else {
-                format!("Network error: {}", e)
+                tr!("net.error", e)
             }
         })?;
     
@@ -995,142 +1805,150 @@ async fn fetch_balance(app: AppHandle, api_key: String) -> Result<BalanceData, S
     let status = response.status();
     
     if status == 401 {
-        return Err("Invalid API key. Please check your key and try again.".to_string());
+        return Err(tr!("api.invalid_key"));
     }
     
     if !status.is_success() {
-        return Err(format!("API request failed with status: {}", status));
+        return Err(tr!("api.status", status));
     }
     
     // Parse JSON response
     let raw_body = response
         .text()
         .await
-        .map_err(|e| format!("Failed to read response body: {}", e))?;
+        .map_err(|e| tr!("net.body", e))?;
     
     // Log raw body for debugging
-    let _ = log_message(app, format!("[INFO] Raw OpenRouter response: {}", raw_body));
+    let _ = write_log(&format!("[INFO] Raw OpenRouter response: {}", raw_body));
 
     let api_response: OpenRouterResponse = serde_json::from_str(&raw_body)
         .map_err(|e| {
             eprintln!("JSON parse error: {}", e);
-            format!("Failed to parse API response: {}. Body: {}", e, raw_body)
+            tr!("api.parse", e, raw_body)
         })?;
     
     // Check for API error
     if let Some(error) = api_response.error {
         eprintln!("OpenRouter API error: {}", error);
-        return Err(format!("API error: {}", error));
+        return Err(tr!("api.error", error));
     }
     
     // Extract data
-    let data = api_response.data
+    let mut data = api_response.data
         .ok_or_else(|| {
             eprintln!("No data field in API response");
-            "API response missing data. Please try again.".to_string()
+            tr!("api.missing_data")
         })?;
     
-    // Calculate remaining balance (legacy) and monthly remaining
+    // OpenRouter reports no reset for some keys; fall back to the period configured per key
+    if data.limit_reset.is_none() {
+        data.limit_reset = configured_limit_reset(api_key);
+    }
+
+    // Several keys can share one prepaid account: pace against whichever runs out first
+    let account = fetch_account_credits(&client, api_key).await;
+    Ok(build_balance_data(data, account))
+}
+
+fn configured_limit_reset(api_key: &str) -> Option<String> {
+    read_settings()
+        .ok()?
+        .api_keys
+        .into_iter()
+        .find(|k| k.key.trim() == api_key.trim())?
+        .limit_reset
+}
+
+/// Length of a limit's reset period in days; keys without one are treated as monthly
+fn limit_period_days(limit_reset: Option<&str>, days_in_month: f64) -> f64 {
+    match limit_reset {
+        Some("daily") => 1.0,
+        Some("weekly") => 7.0,
+        _ => days_in_month,
+    }
+}
+
+/// Derive remaining, pace targets and status from raw key data. Shared by the
+/// active key balance and the team (provisioning key) view.
+fn build_balance_data(data: OpenRouterData, account: Option<AccountCredits>) -> BalanceData {
+    build_balance_data_at(data, account, Local::now())
+}
+
+/// `build_balance_data` at a given moment. Budgets are per reset period: a $10/day key has
+/// $10 today, not a monthly equivalent, and its targets pace that day.
+fn build_balance_data_at(data: OpenRouterData, account: Option<AccountCredits>, now: chrono::DateTime<Local>) -> BalanceData {
+    // How far through the day, week and month we are, using local time + fractional day
+    let day_fraction = (now.hour() as f64 + (now.minute() as f64 / 60.0) + (now.second() as f64 / 3600.0)) / 24.0;
+    let days_in_month = menubar_icon::days_in_month(now.date_naive()) as f64;
+    let elapsed_month_days = (now.day() as f64 - 1.0) + day_fraction;
+    let elapsed_week_days = (now.weekday().num_days_from_monday() as f64 + day_fraction).clamp(0.0, 7.0);
+
+    // Calculate remaining balance (legacy)
     let remaining = match (data.limit, data.usage) {
         (Some(limit), Some(usage)) => Some(limit - usage),
         _ => None,
     };
-    let usage_monthly = match (data.limit, data.limit_remaining) {
-        (Some(limit), Some(limit_remaining)) => Some(limit - limit_remaining),
-        _ => data.usage_monthly,
-    };
-    let remaining_monthly = match (data.limit, usage_monthly) {
-        (Some(limit), Some(usage_monthly)) => Some(limit - usage_monthly),
-        _ => data.limit_remaining,
-    };
 
-    // Pace ratio: how far through the month we are (0..1), using local time + fractional day
-    let now = Local::now();
-    let day_fraction = (now.hour() as f64 + (now.minute() as f64 / 60.0) + (now.second() as f64 / 3600.0)) / 24.0;
-    let day_of_month = now.day() as f64;
-    let days_in_month = {
-        let year = now.year();
-        let month = now.month();
-        let first_next_month = if month == 12 {
-            Local.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).unwrap()
-        } else {
-            Local.with_ymd_and_hms(year, month + 1, 1, 0, 0, 0).unwrap()
-        };
-        let last_this_month = first_next_month - chrono::Duration::days(1);
-        last_this_month.day() as f64
+    let period_days = limit_period_days(data.limit_reset.as_deref(), days_in_month);
+    let elapsed_period_days = match data.limit_reset.as_deref() {
+        Some("daily") => day_fraction,
+        Some("weekly") => elapsed_week_days,
+        _ => elapsed_month_days,
     };
-    let elapsed_days = (day_of_month - 1.0) + day_fraction;
-    let pace_ratio = if days_in_month > 0.0 {
-        Some((elapsed_days / days_in_month).clamp(0.0, 1.0))
-    } else {
-        None
+
+    // limit - limit_remaining is usage within the current reset period
+    let usage_period = match (data.limit, data.limit_remaining) {
+        (Some(limit), Some(limit_remaining)) => Some(limit - limit_remaining),
+        _ => match data.limit_reset.as_deref() {
+            Some("daily") => data.usage_daily,
+            Some("weekly") => data.usage_weekly,
+            _ => data.usage_monthly,
+        },
     };
+    let usage_monthly = if period_days == days_in_month { usage_period.or(data.usage_monthly) } else { data.usage_monthly };
+    let remaining_monthly = data.limit_remaining.or(match (data.limit, usage_period) {
+        (Some(limit), Some(usage)) => Some(limit - usage),
+        _ => None,
+    });
 
-    let (pace_month_target, pace_week_target, pace_day_target, pace_month_delta_percent, pace_week_delta_percent, pace_day_delta_percent) =
-        if let (Some(limit), Some(usage_monthly)) = (data.limit, usage_monthly) {
-            if limit > 0.0 && days_in_month > 0.0 {
-                let daily_budget = limit / days_in_month;
-                let pace_month_target = (daily_budget * elapsed_days).clamp(0.0, limit);
-
-                let weekday_index = now.weekday().num_days_from_monday() as f64;
-                let elapsed_week_days = (weekday_index + day_fraction).clamp(0.0, 7.0);
-                let pace_week_target = (daily_budget * elapsed_week_days).clamp(0.0, limit);
-                let pace_day_target = (daily_budget * day_fraction).clamp(0.0, limit);
-
-                let percent_from_target = |usage: f64, target: f64| {
-                    if target > 0.0 {
-                        Some(((usage - target) / target) * 100.0)
-                    } else {
-                        None
-                    }
-                };
-
-                let pace_month_delta_percent = percent_from_target(usage_monthly, pace_month_target);
-                let pace_week_delta_percent = data
-                    .usage_weekly
-                    .and_then(|usage_weekly| percent_from_target(usage_weekly, pace_week_target));
-                let pace_day_delta_percent = data
-                    .usage_daily
-                    .and_then(|usage_daily| percent_from_target(usage_daily, pace_day_target));
-
-                (
-                    Some(pace_month_target),
-                    Some(pace_week_target),
-                    Some(pace_day_target),
-                    pace_month_delta_percent,
-                    pace_week_delta_percent,
-                    pace_day_delta_percent,
-                )
-            } else {
-                (None, None, None, None, None, None)
-            }
-        } else {
-            (None, None, None, None, None, None)
-        };
+    let (effective_limit, binding_constraint) = binding_budget(data.limit, usage_period, account.as_ref());
 
-    let pace_status = pace_month_delta_percent.map(|delta| {
-        let threshold = default_pace_warn_threshold();
-        pace_status_from_delta(delta, threshold).to_string()
-    });
-    
-    Ok(BalanceData {
+    let pace_ratio = (period_days > 0.0).then(|| (elapsed_period_days / period_days).clamp(0.0, 1.0));
+
+    let mut balance = BalanceData {
         limit: data.limit,
         usage: data.usage,
         usage_daily: data.usage_daily,
         usage_weekly: data.usage_weekly,
         usage_monthly,
+        usage_period,
         remaining,
         remaining_monthly,
         pace_ratio,
-        pace_month_target,
-        pace_week_target,
-        pace_day_target,
-        pace_month_delta_percent,
-        pace_week_delta_percent,
-        pace_day_delta_percent,
-        pace_status,
         label: data.label,
-    })
+        is_provisioning_key: data.is_provisioning_key,
+        is_free_tier: data.is_free_tier,
+        rate_limit: data.rate_limit,
+        limit_reset: data.limit_reset,
+        account,
+        effective_limit,
+        binding_constraint,
+        ..Default::default()
+    };
+
+    if let (Some(limit), Some(_)) = (effective_limit.filter(|l| *l > 0.0), usage_period) {
+        let daily_budget = limit / period_days;
+        // A timeframe spanning the whole reset period paces the period itself
+        let target = |spans_period: bool, elapsed_days: f64| {
+            let elapsed = if spans_period { elapsed_period_days } else { elapsed_days };
+            Some((daily_budget * elapsed).clamp(0.0, limit))
+        };
+        balance.pace_month_target = target(balance.spans_period("monthly"), elapsed_month_days);
+        balance.pace_week_target = target(balance.spans_period("weekly"), elapsed_week_days);
+        balance.pace_day_target = target(balance.spans_period("daily"), day_fraction);
+        balance.update_pace_deltas();
+    }
+    balance
 }
 
 /// Reset settings by deleting the config directory
@@ -1139,7 +1957,7 @@ fn reset_settings(app: AppHandle) -> Result<(), String> {
     let config_dir = get_config_dir()?;
     if config_dir.exists() {
         fs::remove_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to delete config directory: {}", e))?;
+            .map_err(|e| tr!("settings.reset", e))?;
     }
     
     // Unregister shortcuts
@@ -1242,22 +2060,6 @@ fn try_load_font(font_name: &str) -> Option<(FontVec, String)> {
     None
 }
 
-/// Calculate the visual width of text for centering
-fn calculate_text_width(text: &str, font: &FontVec, scale: PxScale) -> i32 {
-    use ab_glyph::{Font, ScaleFont};
-    
-    // Measure actual glyph widths for precise centering
-    let scaled_font = font.as_scaled(scale);
-    let mut width = 0.0;
-    
-    for c in text.chars() {
-        let glyph_id = font.glyph_id(c);
-        width += scaled_font.h_advance(glyph_id);
-    }
-    
-    width as i32
-}
-
 fn pace_status_from_delta(delta_percent: f64, threshold: f64) -> &'static str {
     if delta_percent > threshold {
         "ahead"       // RED: way over pace
@@ -1283,12 +2085,12 @@ fn compute_pace_status(balance: &BalanceData, settings: &AppSettings) -> Option<
     }
 
     let pace_ratio = balance.pace_ratio?;
-    let limit = balance.limit?;
+    let limit = balance.budget_limit()?;
     if limit <= 0.0 {
         return None;
     }
 
-    let usage = balance.usage_monthly.or(balance.usage)?;
+    let usage = balance.budget_usage()?;
     let usage_ratio = (usage / limit) * 100.0;
     let pace_percent = (pace_ratio * 100.0).clamp(0.0, 100.0);
     let delta_percent = usage_ratio - pace_percent;
@@ -1299,6 +2101,7 @@ fn compute_pace_status(balance: &BalanceData, settings: &AppSettings) -> Option<
 #[tauri::command]
 fn update_menubar_display(app_handle: tauri::AppHandle, balance: BalanceData, settings: AppSettings) -> Result<(), String> {
     let is_dark = is_macos_dark_mode(); // must be called on main thread — cache it
+    let scales = display_icon_scales(&app_handle);
     if let Some(state) = app_handle.try_state::<MenubarState>() {
         if let Ok(mut stored) = state.balance.lock() {
             *stored = Some(balance.clone());
@@ -1309,56 +2112,61 @@ fn update_menubar_display(app_handle: tauri::AppHandle, balance: BalanceData, se
         if let Ok(mut stored) = state.is_dark.lock() {
             *stored = is_dark;
         }
+        if let Ok(mut stored) = state.scales.lock() {
+            *stored = scales.clone();
+        }
     }
-    // Calculate daily budget for weekly/daily remaining
-    let limit = balance.limit.unwrap_or(0.0);
-    let daily_budget = if limit > 0.0 {
-        let now = chrono::Local::now();
-        let days_in_month = {
-            let year = now.year();
-            let month = now.month();
-            let first_next_month = if month == 12 {
-                chrono::Local.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).unwrap()
-            } else {
-                chrono::Local.with_ymd_and_hms(year, month + 1, 1, 0, 0, 0).unwrap()
-            };
-            let last_this_month = first_next_month - chrono::Duration::days(1);
-            last_this_month.day() as f64
-        };
-        if days_in_month > 0.0 { limit / days_in_month } else { 0.0 }
+    let icons = render_tray_icon(&balance, &settings, is_dark, &scales)?;
+    if let Some(tray) = app_handle.tray_by_id("main-tray") {
+        set_tray_icon(&tray, icons)?;
+    }
+    tray_status::refresh(&app_handle);
+    pinned_trays::redraw_all(&app_handle);
+    Ok(())
+}
+
+/// Re-render every icon from the last balance and settings, re-querying appearance and
+/// monitor scales; main thread
+fn redraw_menubar_from_cache(app: &AppHandle) -> Result<(), String> {
+    let Some(state) = app.try_state::<MenubarState>() else { return Ok(()) };
+    let balance = state.balance.lock().ok().and_then(|stored| stored.clone());
+    let settings = state.settings.lock().ok().and_then(|stored| stored.clone());
+    match (balance, settings) {
+        (Some(balance), Some(settings)) => update_menubar_display(app.clone(), balance, settings),
+        // Nothing shown yet; the first update queries the scales itself
+        _ => Ok(()),
+    }
+}
+
+/// Icon scales for the connected monitors (1x, 2x, 3x); main thread
+fn display_icon_scales(app: &AppHandle) -> Vec<f32> {
+    let monitors = app.available_monitors().unwrap_or_default();
+    menubar_icon::icon_scales(monitors.iter().map(|m| m.scale_factor()))
+}
+
+/// Scales cached by the last update_menubar_display (also run on display changes), safe from any thread
+fn cached_icon_scales(app: &AppHandle) -> Vec<f32> {
+    app.try_state::<MenubarState>()
+        .and_then(|s| s.scales.lock().ok().map(|scales| scales.clone()))
+        .filter(|scales| !scales.is_empty())
+        .unwrap_or_else(|| vec![MENUBAR_RENDER_SCALE])
+}
+
+/// The menubar icon for a balance as the given settings display it, once per scale
+fn render_tray_icon(balance: &BalanceData, settings: &AppSettings, is_dark: bool, scales: &[f32]) -> Result<Vec<Image<'static>>, String> {
+    // Usage and budget of the selected timeframe (the reset period's share for shorter ones)
+    let timeframe = settings.menubar_timeframe.as_str();
+    let days_in_month = menubar_icon::days_in_month(Local::now().date_naive()) as f64;
+    let usage_value = balance.timeframe_usage(timeframe).unwrap_or(0.0);
+    let budget = balance.timeframe_budget(timeframe, days_in_month).unwrap_or(0.0);
+    let remaining_value = if balance.spans_period(timeframe) {
+        balance.budget_remaining().unwrap_or(0.0)
     } else {
-        0.0
-    };
-    
-    // Get timeframe-specific usage and remaining values
-    let (usage_value, remaining_value) = match settings.menubar_timeframe.as_str() {
-        "weekly" => {
-            let weekly_budget = daily_budget * 7.0;
-            let usage = balance.usage_weekly.unwrap_or(0.0);
-            let remaining = weekly_budget - usage;
-            (usage, remaining)
-        },
-        "daily" => {
-            let usage = balance.usage_daily.unwrap_or(0.0);
-            let remaining = daily_budget - usage;
-            (usage, remaining)
-        },
-        _ => {
-            // "monthly" or fallback
-            let usage = balance.usage_monthly.or(balance.usage).unwrap_or(0.0);
-            let remaining = balance.remaining_monthly.or(balance.remaining).unwrap_or(0.0);
-            (usage, remaining)
-        }
+        budget - usage_value
     };
     
     let display_value = if settings.show_percentage {
-        // Percentage logic: relative to limit (or budget for weekly/daily)
-        let budget = match settings.menubar_timeframe.as_str() {
-            "weekly" => daily_budget * 7.0,
-            "daily" => daily_budget,
-            _ => limit,
-        };
-        
+        // Percentage logic: relative to the timeframe's budget
         if budget > 0.0 {
             if settings.show_remaining {
                 (remaining_value / budget) * 100.0
@@ -1385,302 +2193,105 @@ fn update_menubar_display(app_handle: tauri::AppHandle, balance: BalanceData, se
         display_value.floor()
     };
     
-    // Check if we have valid data (monthly, legacy, or limit/usage exists)
-    let has_data = balance.remaining_monthly.is_some()
-        || balance.usage_monthly.is_some()
-        || balance.remaining.is_some()
-        || balance.usage.is_some()
-        || balance.limit.is_some();
+    let has_data = balance.has_data();
     
-    let icon = generate_hybrid_menubar_icon(final_value, settings.show_percentage, has_data, settings.show_unit, &settings, &balance, is_dark)?;
-    if let Some(tray) = app_handle.tray_by_id("main-tray") {
-        tray.set_icon(Some(icon))
-            .map_err(|e| format!("Failed to update tray icon: {}", e))?;
-        
-        #[cfg(target_os = "macos")]
-        {
-            // Force template mode — Liquid Glass on macOS 26+ breaks effectiveAppearance-based
-            // tint detection, especially on multi-monitor setups. Template mode lets macOS
-            // handle per-monitor tinting automatically. See backlog item A20.226d315.
-            tray.set_icon_as_template(true)
-                .map_err(|e| format!("Failed to set icon template mode: {}", e))?;
-        }
-    }
-    Ok(())
+    scales
+        .iter()
+        .map(|&scale| generate_hybrid_menubar_icon(final_value, settings.show_percentage, has_data, settings.show_unit, settings, balance, is_dark, scale))
+        .collect()
 }
 
-/// Generate hybrid menubar icon with logo and adaptive text color
-fn generate_hybrid_menubar_icon(value: f64, is_percentage: bool, has_data: bool, show_unit: bool, settings: &AppSettings, balance: &BalanceData, is_dark_mode: bool) -> Result<Image<'static>, String> {
-    let scale = MENUBAR_RENDER_SCALE;
-    
-    // Load Logo
-    let logo_data = include_bytes!("../icons/32x32.png");
-    let logo_img = image::load_from_memory(logo_data)
-        .map_err(|e| format!("Failed to load logo: {}", e))?
-        .to_rgba8();
-    
-    let logo_physical_size = (MENUBAR_LOGO_SIZE as f32 * scale) as u32;
-    let logo_scaled = image::imageops::resize(
-        &logo_img,
-        logo_physical_size,
-        logo_physical_size,
-        image::imageops::FilterType::Lanczos3
-    );
-
-    // If no data, show logo only (centered in a standard 22pt-equivalent box for consistency)
-    if !has_data {
-        let canvas_size = (22.0 * scale) as u32;
-        let mut img = image::RgbaImage::new(canvas_size, canvas_size);
-        let x = (canvas_size - logo_physical_size) / 2;
-        let y = (canvas_size - logo_physical_size) / 2;
-        image::imageops::overlay(&mut img, &logo_scaled, x as i64, y as i64);
-        let rgba = img.into_raw();
-        return Ok(Image::new_owned(rgba, canvas_size, canvas_size));
-    }
-
-    // Prepare text
-    let value_text = if settings.decimal_places > 0 {
-        format!("{:.1$}", value, settings.decimal_places as usize)
-    } else {
-        format!("{}", value.round() as i32)
-    };
-    let unit_text = if is_percentage { "%" } else { "$" };
-    
-    // Timeframe indicator (superscript)
-    let is_german = is_german_locale();
-    let timeframe_indicator = match settings.menubar_timeframe.as_str() {
-        "weekly" => if is_german { "W" } else { "W" },
-        "daily" => if is_german { "T" } else { "D" },
-        _ => "M", // monthly - same for both languages
-    };
-    
-    // All menubar text uses Semibold for consistent appearance
-    let (val_font, _) = try_load_font(MENUBAR_VALUE_FONT).ok_or("Value font not found")?;
-    
-    let val_scale = PxScale::from(MENUBAR_VALUE_SIZE * scale);
-    let unt_scale = PxScale::from(MENUBAR_UNIT_SIZE * scale);
-    
-    let show_timeframe_indicator = settings.show_timeframe_indicator;
+/// Show icons rendered at several scales; the last (sharpest) is what non-macOS trays get
+fn set_tray_icon(tray: &tauri::tray::TrayIcon, icons: Vec<Image<'static>>) -> Result<(), String> {
+    let Some(sharpest) = icons.last().cloned() else { return Ok(()) };
+    tray.set_icon(Some(sharpest))
+        .map_err(|e| tr!("app.tray_icon", e))?;
 
-    // Superscript: 60% of value size
-    let sup_scale = PxScale::from(MENUBAR_VALUE_SIZE * 0.6 * scale);
-    
-    let val_width = calculate_text_width(&value_text, &val_font, val_scale);
-    let unt_width = calculate_text_width(unit_text, &val_font, unt_scale);
-    let sup_width = if show_timeframe_indicator {
-        calculate_text_width(timeframe_indicator, &val_font, sup_scale)
-    } else {
-        0
-    };
-    
-    // Calculate total width (logical points then scale)
-    // Layout: [hexagon] [gap] [value] [unit?] [superscript_indicator] [padding]
-    let mut total_width_pts = HEX_SIZE_PTS;
-    
-    if has_data {
-        let mut text_part_width = val_width as f32 / scale;
-        
-        // Both modes: show unit + optional superscript indicator
-        if show_unit {
-            text_part_width += UNIT_VALUE_GAP + (unt_width as f32 / scale);
-        }
-        if show_timeframe_indicator {
-            text_part_width += 2.0 + (sup_width as f32 / scale);
+    #[cfg(target_os = "macos")]
+    {
+        // Force template mode — Liquid Glass on macOS 26+ breaks effectiveAppearance-based
+        // tint detection, especially on multi-monitor setups. Template mode lets macOS
+        // handle per-monitor tinting automatically. See backlog item A20.226d315.
+        tray.set_icon_as_template(true)
+            .map_err(|e| tr!("app.tray_template", e))?;
+        if icons.len() > 1 {
+            set_multi_scale_icon(tray, icons)?;
         }
-        
-        total_width_pts += LOGO_TEXT_GAP + text_part_width + END_PADDING;
     }
-        
-    let canvas_width = (total_width_pts * scale) as u32;
-    let canvas_height = (22.0 * scale) as u32; // Standard macOS height
-    
-    let mut img = image::RgbaImage::new(canvas_width, canvas_height);
-    
-    // 1. Draw Programmatic Hexagon
-    let hex_width = (HEX_SIZE_PTS * scale) as f32;
-    // For a pointy-top hexagon with straight vertical sides:
-    // height = width / cos(30) = width / 0.866
-    let hex_height = hex_width / 0.866;
-    let hex_x_offset = 0.0;
-    let hex_y_offset = (canvas_height as f32 - hex_height) / 2.0;
-
-    // We calculate the 6 points of the hexagon
-    // Pointy top: [width/2, 0], [width, height/4], [width, 3height/4], [width/2, height], [0, 3height/4], [0, height/4]
-    let points = [
-        (hex_x_offset + hex_width / 2.0, hex_y_offset),
-        (hex_x_offset + hex_width, hex_y_offset + hex_height * 0.25),
-        (hex_x_offset + hex_width, hex_y_offset + hex_height * 0.75),
-        (hex_x_offset + hex_width / 2.0, hex_y_offset + hex_height),
-        (hex_x_offset, hex_y_offset + hex_height * 0.75),
-        (hex_x_offset, hex_y_offset + hex_height * 0.25),
-    ];
-
-    // Calculate fill level (rising from bottom)
-    // Percentage used for the fill should be the selected balance % based on timeframe
-    let fill_pct = if has_data {
-        let limit = balance.limit.unwrap_or(0.0);
-        if limit > 0.0 {
-            // Calculate daily budget for weekly/daily
-            let daily_budget = {
-                let now = chrono::Local::now();
-                let days_in_month = {
-                    let year = now.year();
-                    let month = now.month();
-                    let first_next_month = if month == 12 {
-                        chrono::Local.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).unwrap()
-                    } else {
-                        chrono::Local.with_ymd_and_hms(year, month + 1, 1, 0, 0, 0).unwrap()
-                    };
-                    let last_this_month = first_next_month - chrono::Duration::days(1);
-                    last_this_month.day() as f64
-                };
-                if days_in_month > 0.0 { limit / days_in_month } else { 0.0 }
-            };
-            
-            let (usage_val, budget) = match settings.menubar_timeframe.as_str() {
-                "weekly" => {
-                    let weekly_budget = daily_budget * 7.0;
-                    (balance.usage_weekly.unwrap_or(0.0), weekly_budget)
-                },
-                "daily" => {
-                    (balance.usage_daily.unwrap_or(0.0), daily_budget)
-                },
-                _ => {
-                    // monthly
-                    (balance.usage_monthly.or(balance.usage).unwrap_or(0.0), limit)
-                }
-            };
-            
-            let fill_val = if settings.show_remaining {
-                budget - usage_val
-            } else {
-                usage_val
-            };
-            
-            (fill_val as f32 / budget as f32).clamp(0.0, 1.0)
-        } else {
-            1.0f32
-        }
-    } else {
-        0.0f32
-    };
-
-    let border_thickness = (HEX_BORDER_PTS * scale) as i32;
-    // Adaptive stroke/text color based on macOS appearance
-    let stroke_color = if is_dark_mode {
-        Rgba([255, 255, 255, 255])  // White for dark mode
-    } else {
-        Rgba([0, 0, 0, 255])        // Black for light mode
-    };
-    let transparent = Rgba([0, 0, 0, 0]);
-    let fill_color = if settings.menubar_monochrome {
-        Rgba([255, 255, 255, 180])
-    } else {
-        match compute_pace_status(balance, settings) {
-            Some("ahead") => Rgba([239, 68, 68, 200]),
-            Some("behind") => Rgba([234, 179, 8, 210]),
-            Some("on_track") => Rgba([16, 185, 129, 200]),
-            _ => Rgba([255, 255, 255, 128]),
-        }
-    };
+    Ok(())
+}
 
-    // Rasterize Hexagon
-    for y in 0..canvas_height {
-        for x in 0..=hex_width as u32 {
-            if is_inside_hexagon(x as f32, y as f32, &points) {
-                let dist = distance_to_hexagon_border(x as f32, y as f32, &points);
-                
-                if dist < border_thickness as f32 {
-                    // Border
-                    img.put_pixel(x, y, stroke_color);
-                } else {
-                    // Interior - vertical fill logic
-                    let relative_y = (y as f32 - hex_y_offset) / hex_height;
-                    let fill_from_top = !settings.show_remaining;
-                    let is_filled = if fill_from_top {
-                        relative_y < fill_pct
-                    } else {
-                        relative_y > (1.0f32 - fill_pct)
-                    };
-                    if is_filled {
-                        img.put_pixel(x, y, fill_color);
-                    } else {
-                        img.put_pixel(x, y, transparent);
-                    }
+/// Replace the status item image with one NSImage holding a representation per scale, so
+/// AppKit picks the matching one when the menubar moves between 1x and 2x/3x displays
+#[cfg(target_os = "macos")]
+fn set_multi_scale_icon(tray: &tauri::tray::TrayIcon, icons: Vec<Image<'static>>) -> Result<(), String> {
+    use cocoa::base::YES;
+    use cocoa::foundation::NSSize;
+
+    let mut pngs = Vec::new();
+    for icon in &icons {
+        let rgba = image::RgbaImage::from_raw(icon.width(), icon.height(), icon.rgba().to_vec())
+            .ok_or_else(|| tr!("app.tray_icon", "invalid image size"))?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        rgba.write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| tr!("app.tray_icon", e))?;
+        pngs.push(png.into_inner());
+    }
+    // Same point size tray-icon uses for a single image: 18pt high
+    let (width, height) = (icons[0].width() as f64, icons[0].height() as f64);
+    let size = NSSize::new(width / height * 18.0, 18.0);
+
+    tray.with_inner_tray_icon(move |inner| {
+        let Some(item) = inner.ns_status_item() else { return };
+        unsafe {
+            let item = &*item as *const _ as id;
+            let button: id = msg_send![item, button];
+            if button.is_null() {
+                return;
+            }
+            let image: id = msg_send![class!(NSImage), alloc];
+            let image: id = msg_send![image, initWithSize: size];
+            for png in &pngs {
+                let bytes = png.as_ptr() as *const c_void;
+                let data: id = msg_send![class!(NSData), dataWithBytes: bytes length: png.len()];
+                let rep: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: data];
+                if !rep.is_null() {
+                    let _: () = msg_send![rep, setSize: size];
+                    let _: () = msg_send![image, addRepresentation: rep];
                 }
             }
+            let _: () = msg_send![image, setTemplate: YES];
+            let _: () = msg_send![button, setImage: image];
+            let _: () = msg_send![image, release];
         }
-    }
-    
-    if !has_data {
-        let rgba = img.into_raw();
-        return Ok(Image::new_owned(rgba, canvas_width, canvas_height));
-    }
-
-    // 2. Draw Text (White)
-    // Unified: [value] [unit?] [superscript D/W/M]
-    let text_color = stroke_color;
-    let mut current_x = (HEX_SIZE_PTS + LOGO_TEXT_GAP) * scale;
-    
-    let val_y = (canvas_height as f32 - (MENUBAR_VALUE_SIZE * scale)) / 2.0;
-    draw_text_mut(&mut img, text_color, current_x as i32, val_y as i32, val_scale, &val_font, &value_text);
-    current_x += val_width as f32;
-    
-    if show_unit {
-        current_x += UNIT_VALUE_GAP * scale;
-        let unt_y = (canvas_height as f32 - (MENUBAR_UNIT_SIZE * scale)) / 2.0;
-        draw_text_mut(&mut img, text_color, current_x as i32, unt_y as i32, unt_scale, &val_font, unit_text);
-        current_x += unt_width as f32;
-    }
-    
-    if show_timeframe_indicator {
-        // Superscript timeframe indicator (D/W/M)
-        current_x += 2.0 * scale;
-        let sup_y = val_y - (MENUBAR_VALUE_SIZE * 0.3 * scale) + 2.0;
-        draw_text_mut(&mut img, text_color, current_x as i32, sup_y as i32, sup_scale, &val_font, timeframe_indicator);
-    }
-    
-    let rgba = img.into_raw();
-    Ok(Image::new_owned(rgba, canvas_width, canvas_height))
-}
-
-fn is_inside_hexagon(x: f32, y: f32, p: &[(f32, f32); 6]) -> bool {
-    // Simple point-in-polygon for convex hexagon
-    let mut inside = true;
-    for i in 0..6 {
-        let p1 = p[i];
-        let p2 = p[(i + 1) % 6];
-        // Cross product to check side
-        if (p2.0 - p1.0) * (y - p1.1) - (p2.1 - p1.1) * (x - p1.0) < 0.0 {
-            inside = false;
-            break;
-        }
-    }
-    inside
+    })
+    .map_err(|e| tr!("app.tray_icon", e))
 }
 
-fn distance_to_hexagon_border(x: f32, y: f32, p: &[(f32, f32); 6]) -> f32 {
-    let mut min_dist = f32::MAX;
-    for i in 0..6 {
-        let p1 = p[i];
-        let p2 = p[(i + 1) % 6];
-        
-        let dx = p2.0 - p1.0;
-        let dy = p2.1 - p1.1;
-        let l2 = dx * dx + dy * dy;
-        
-        let t = ((x - p1.0) * dx + (y - p1.1) * dy) / l2;
-        let t = t.clamp(0.0, 1.0);
-        
-        let px = p1.0 + t * dx;
-        let py = p1.1 + t * dy;
-        
-        let dist = ((x - px).powi(2) + (y - py).powi(2)).sqrt();
-        if dist < min_dist {
-            min_dist = dist;
+/// Generate hybrid menubar icon with logo and adaptive text color
+#[allow(clippy::too_many_arguments)]
+fn generate_hybrid_menubar_icon(value: f64, is_percentage: bool, has_data: bool, show_unit: bool, settings: &AppSettings, balance: &BalanceData, is_dark_mode: bool, scale: f32) -> Result<Image<'static>, String> {
+    let daily_spend = match (settings.icon_style.as_str(), settings.api_keys.get(settings.active_api_key_index)) {
+        ("sparkline", Some(key)) => {
+            let (fingerprint, today) = (key_fingerprint(&key.key), chrono::Utc::now().date_naive());
+            menubar_icon::renderer().daily_spend(&fingerprint, today, history::samples_appended(), || {
+                history::daily_spend(&fingerprint, today, menubar_icon::SPARKLINE_DAYS)
+            })
         }
-    }
-    min_dist
+        _ => Vec::new(),
+    };
+    let format = number_format::NumberFormat::from_settings(settings);
+    let env = menubar_icon::IconEnv {
+        scale,
+        is_dark_mode,
+        lang: i18n::resolve(&settings.language),
+        days_in_month: menubar_icon::days_in_month(Local::now().date_naive()),
+        daily_spend: &daily_spend,
+        format: &format,
+    };
+    let img = menubar_icon::renderer().render(value, is_percentage, has_data, show_unit, settings, balance, &env)?;
+    let (width, height) = img.dimensions();
+    Ok(Image::new_owned(img.into_raw(), width, height))
 }
 
 
@@ -1688,20 +2299,20 @@ fn distance_to_hexagon_border(x: f32, y: f32, p: &[(f32, f32); 6]) -> f32 {
 fn position_window_below_menubar(window: &tauri::WebviewWindow) -> Result<(), String> {
     // Get the monitor the window is on (or primary monitor)
     let monitor = window.current_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or_else(|| "No monitor available".to_string())?;
+        .map_err(|e| tr!("app.monitor", e))?
+        .ok_or_else(|| tr!("app.no_monitor"))?;
     
     let monitor_pos = monitor.position();
     let monitor_size = monitor.size();
     let window_size = window.outer_size()
-        .map_err(|e| format!("Failed to get window size: {}", e))?;
+        .map_err(|e| tr!("app.window_size", e))?;
     
     // Center both horizontally and vertically on the monitor
     let x = monitor_pos.x + (monitor_size.width as i32 / 2) - (window_size.width as i32 / 2);
     let y = monitor_pos.y + (monitor_size.height as i32 / 2) - (window_size.height as i32 / 2);
     
     window.set_position(PhysicalPosition::new(x, y))
-        .map_err(|e| format!("Failed to set position: {}", e))?;
+        .map_err(|e| tr!("app.window_position", e))?;
     
     Ok(())
 }
@@ -1725,14 +2336,18 @@ fn toggle_window(app: &AppHandle) {
 }
 
 fn update_tray_menu(app: &AppHandle) {
-    let current_tf = read_settings()
-        .map(|s| s.menubar_timeframe)
-        .unwrap_or_else(|_| "monthly".to_string());
+    let settings = read_settings().ok();
+    let current_tf = settings.as_ref()
+        .map(|s| s.menubar_timeframe.clone())
+        .unwrap_or_else(|| "monthly".to_string());
+    let rotation_reminders = settings.as_ref()
+        .map(collect_key_rotation_reminders)
+        .unwrap_or_default();
 
     let is_visible = app.get_webview_window("main")
         .map(|w| w.is_visible().unwrap_or(false))
         .unwrap_or(false);
-    let show_hide_label = if is_visible { "Hide Window" } else { "Show Window" };
+    let show_hide_label = if is_visible { tr!("tray.hide_window") } else { tr!("tray.show_window") };
 
     let show_hide = match MenuItemBuilder::with_id("show_hide", show_hide_label).build(app) {
         Ok(i) => i, Err(_) => return,
@@ -1740,25 +2355,59 @@ fn update_tray_menu(app: &AppHandle) {
     let sep1 = match PredefinedMenuItem::separator(app) {
         Ok(i) => i, Err(_) => return,
     };
-    let daily = match CheckMenuItem::with_id(app, "daily", "Daily", true, current_tf == "daily", None::<&str>) {
+    let mut rotation_items = Vec::new();
+    for reminder in &rotation_reminders {
+        let id = format!("rotate_key:{}", reminder.index);
+        match MenuItemBuilder::with_id(id, rotation_reminder_text(reminder)).build(app) {
+            Ok(i) => rotation_items.push(i),
+            Err(_) => return,
+        }
+    }
+    let sep_rotation = match PredefinedMenuItem::separator(app) {
+        Ok(i) => i, Err(_) => return,
+    };
+    let daily = match CheckMenuItem::with_id(app, "daily", tr!("tray.daily"), true, current_tf == "daily", None::<&str>) {
+        Ok(i) => i, Err(_) => return,
+    };
+    let weekly = match CheckMenuItem::with_id(app, "weekly", tr!("tray.weekly"), true, current_tf == "weekly", None::<&str>) {
+        Ok(i) => i, Err(_) => return,
+    };
+    let monthly = match CheckMenuItem::with_id(app, "monthly", tr!("tray.monthly"), true, current_tf == "monthly", None::<&str>) {
+        Ok(i) => i, Err(_) => return,
+    };
+    let sep_project = match PredefinedMenuItem::separator(app) {
         Ok(i) => i, Err(_) => return,
     };
-    let weekly = match CheckMenuItem::with_id(app, "weekly", "Weekly", true, current_tf == "weekly", None::<&str>) {
+    let project_menu = match projects::tray_submenu(app, settings.as_ref()) {
         Ok(i) => i, Err(_) => return,
     };
-    let monthly = match CheckMenuItem::with_id(app, "monthly", "Monthly", true, current_tf == "monthly", None::<&str>) {
+    let report_menu = match reports::tray_submenu(app) {
         Ok(i) => i, Err(_) => return,
     };
     let sep2 = match PredefinedMenuItem::separator(app) {
         Ok(i) => i, Err(_) => return,
     };
-    let quit = match MenuItemBuilder::with_id("quit", "Quit").build(app) {
+    let quit = match MenuItemBuilder::with_id("quit", tr!("tray.quit")).build(app) {
         Ok(i) => i, Err(_) => return,
     };
 
-    let menu = match Menu::with_items(app, &[
-        &show_hide, &sep1, &daily, &weekly, &monthly, &sep2, &quit
-    ]) {
+    let mut items: Vec<&dyn IsMenuItem<tauri::Wry>> = vec![&show_hide, &sep1];
+    if !rotation_items.is_empty() {
+        for item in &rotation_items {
+            items.push(item);
+        }
+        items.push(&sep_rotation);
+    }
+    items.push(&daily);
+    items.push(&weekly);
+    items.push(&monthly);
+    items.push(&sep_project);
+    items.push(&project_menu);
+    items.push(&report_menu);
+    items.push(&sep2);
+    items.push(&quit);
+
+    let menu = match Menu::with_items(app, &items) {
         Ok(m) => m, Err(_) => return,
     };
 
@@ -1767,6 +2416,12 @@ fn update_tray_menu(app: &AppHandle) {
     }
 }
 
+/// Rebuild the tray menu from any thread (menus must be built on the main thread)
+fn refresh_tray_menu(app: &AppHandle) {
+    let handle = app.clone();
+    let _ = app.run_on_main_thread(move || update_tray_menu(&handle));
+}
+
 fn update_app_shortcut(app: &AppHandle, shortcut_str: &str, enabled: bool) -> Result<(), String> {
     let shortcut_ext = app.global_shortcut();
     
@@ -1780,7 +2435,7 @@ fn update_app_shortcut(app: &AppHandle, shortcut_str: &str, enabled: bool) -> Re
                 if event.state() == ShortcutState::Pressed {
                     toggle_window(app);
                 }
-            }).map_err(|e| format!("Failed to register shortcut: {}", e))?;
+            }).map_err(|e| tr!("app.shortcut", e))?;
         }
     }
     
@@ -1857,10 +2512,10 @@ async fn get_google_access_token(sa_email: &str, private_key_pem: &str) -> Resul
     let pem = private_key_pem.replace("\\n", "\n");
 
     let encoding_key = EncodingKey::from_rsa_pem(pem.as_bytes())
-        .map_err(|e| format!("Invalid private key: {}", e))?;
+        .map_err(|e| tr!("mood.invalid_private_key", e))?;
 
     let jwt = encode(&Header::new(Algorithm::RS256), &claims, &encoding_key)
-        .map_err(|e| format!("JWT encode error: {}", e))?;
+        .map_err(|e| tr!("mood.jwt", e))?;
 
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(15))
@@ -1877,15 +2532,15 @@ async fn get_google_access_token(sa_email: &str, private_key_pem: &str) -> Resul
         .form(&params)
         .send()
         .await
-        .map_err(|e| format!("Token request failed: {}", e))?;
+        .map_err(|e| tr!("mood.token_request", e))?;
 
     let body: Value = resp.json().await
-        .map_err(|e| format!("Token response parse error: {}", e))?;
+        .map_err(|e| tr!("mood.token_parse", e))?;
 
     body["access_token"]
         .as_str()
         .map(|s| s.to_string())
-        .ok_or_else(|| format!("No access_token in response: {}", body))
+        .ok_or_else(|| tr!("mood.no_token", body))
 }
 
 /// Fetch mood rows for a given week number (or current week if None).
@@ -1915,16 +2570,16 @@ async fn fetch_mood_rows(
         .bearer_auth(&token)
         .send()
         .await
-        .map_err(|e| format!("Sheets fetch error: {}", e))?;
+        .map_err(|e| tr!("mood.sheets_fetch", e))?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
-        return Err(format!("Sheets API error {}: {}", status, body));
+        return Err(tr!("mood.sheets_status", status, body));
     }
 
     let body: Value = resp.json().await
-        .map_err(|e| format!("Sheets response parse error: {}", e))?;
+        .map_err(|e| tr!("mood.sheets_parse", e))?;
 
     let mut rows: Vec<MoodRow> = Vec::new();
     if let Some(sheet_rows) = body["values"].as_array() {
@@ -1976,7 +2631,7 @@ async fn fetch_mood_data(
     tag: String,
 ) -> Result<Vec<MoodRow>, String> {
     if sheet_id.is_empty() || sa_email.is_empty() || sa_key.is_empty() {
-        return Err("Mood not configured. Set Sheet ID and service account in Settings.".to_string());
+        return Err(tr!("mood.not_configured"));
     }
     verify_mood_auth(&sheet_id, &sa_email, &sa_key, &or_key, &tag).await?;
     fetch_mood_rows(&sheet_id, &sa_email, &sa_key, week).await
@@ -1997,15 +2652,15 @@ async fn write_mood_entry(
     or_key: String,
 ) -> Result<(), String> {
     if sheet_id.is_empty() || sa_email.is_empty() || sa_key.is_empty() {
-        return Err("Mood not configured. Set Sheet ID and service account in Settings.".to_string());
+        return Err(tr!("mood.not_configured"));
     }
     if name.is_empty() {
-        return Err("Your 3-char name is not set. Check Settings → Mood.".to_string());
+        return Err(tr!("mood.name_missing"));
     }
     // Auth check: key must match tag in Keys sheet
     verify_mood_auth(&sheet_id, &sa_email, &sa_key, &or_key, &name).await?;
     if mood < 1 || mood > 6 {
-        return Err(format!("Invalid mood value: {}. Must be 1-6.", mood));
+        return Err(tr!("mood.invalid_value", mood));
     }
 
     let token = get_google_access_token(&sa_email, &sa_key).await?;
@@ -2047,12 +2702,12 @@ async fn write_mood_entry(
             .json(&row_values)
             .send()
             .await
-            .map_err(|e| format!("Sheets update error: {}", e))?;
+            .map_err(|e| tr!("mood.update", e))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Sheets update failed {}: {}", status, body));
+            return Err(tr!("mood.update_status", status, body));
         }
     } else {
         // APPEND new row
@@ -2066,12 +2721,12 @@ async fn write_mood_entry(
             .json(&row_values)
             .send()
             .await
-            .map_err(|e| format!("Sheets append error: {}", e))?;
+            .map_err(|e| tr!("mood.append", e))?;
 
         if !resp.status().is_success() {
             let status = resp.status();
             let body = resp.text().await.unwrap_or_default();
-            return Err(format!("Sheets append failed {}: {}", status, body));
+            return Err(tr!("mood.append_status", status, body));
         }
     }
 
@@ -2089,10 +2744,10 @@ async fn verify_mood_auth(
     tag: &str,
 ) -> Result<(), String> {
     if or_key.is_empty() {
-        return Err("No OpenRouter API key configured. Set one in Settings → OpenRouter.".to_string());
+        return Err(tr!("mood.no_api_key"));
     }
     if tag.is_empty() {
-        return Err("No tag configured. Set your tag in Settings → Mood.".to_string());
+        return Err(tr!("mood.no_tag"));
     }
 
     let token = get_google_access_token(sa_email, sa_key).await?;
@@ -2112,19 +2767,19 @@ async fn verify_mood_auth(
         .bearer_auth(&token)
         .send()
         .await
-        .map_err(|e| format!("Auth check request failed: {}", e))?;
+        .map_err(|e| tr!("mood.auth_request", e))?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
-        return Err(format!("Auth check failed ({}): {}", status, body));
+        return Err(tr!("mood.auth_status", status, body));
     }
 
     let body: Value = resp.json().await
-        .map_err(|e| format!("Auth response parse error: {}", e))?;
+        .map_err(|e| tr!("mood.auth_parse", e))?;
 
     let rows = body["values"].as_array().ok_or_else(|| {
-        "Keys sheet is empty or missing. Contact your administrator.".to_string()
+        tr!("mood.keys_sheet_empty")
     })?;
 
     let or_key_norm = or_key.trim();
@@ -2139,7 +2794,7 @@ async fn verify_mood_auth(
         }
     }
 
-    Err("Access denied: key and tag combination not found. Check your OpenRouter key and tag in Settings.".to_string())
+    Err(tr!("mood.access_denied"))
 }
 
 /// Test connectivity + auth: verifies SA token, auth key/tag pair, then reads sheet header.
@@ -2152,7 +2807,7 @@ async fn test_mood_connection(
     tag: String,
 ) -> Result<String, String> {
     if sheet_id.is_empty() || sa_email.is_empty() || sa_key.is_empty() {
-        return Err("Sheet ID, service account email, and private key are all required.".to_string());
+        return Err(tr!("mood.all_required"));
     }
 
     // Auth check first
@@ -2176,23 +2831,23 @@ async fn test_mood_connection(
         .bearer_auth(&token)
         .send()
         .await
-        .map_err(|e| format!("Connection test request failed: {}", e))?;
+        .map_err(|e| tr!("mood.test_request", e))?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
-        return Err(format!("Connection test failed ({}): {}", status, body));
+        return Err(tr!("mood.test_status", status, body));
     }
 
     let body: Value = resp.json().await
-        .map_err(|e| format!("Response parse error: {}", e))?;
+        .map_err(|e| tr!("mood.test_parse", e))?;
 
     let header = body["values"]
         .as_array()
         .and_then(|rows| rows.first())
         .and_then(|row| row.as_array())
         .map(|cols| cols.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>().join(", "))
-        .unwrap_or_else(|| "(empty sheet — add a header row)".to_string());
+        .unwrap_or_else(|| tr!("mood.empty_sheet"));
 
     Ok(format!("Auth OK. Sheet header: {}", header))
 }
@@ -2307,103 +2962,20 @@ async fn has_missing_mood_entry(settings: &AppSettings) -> bool {
 }
 
 /// Generate a "MOOD" text-only icon in the same style as the regular menubar icon.
-fn generate_mood_text_icon(is_dark_mode: bool) -> Result<Image<'static>, String> {
-    let scale = MENUBAR_RENDER_SCALE;
-    let (font, _) = try_load_font(MENUBAR_VALUE_FONT).ok_or("Value font not found")?;
-    let text = "MOOD";
-    let text_scale = PxScale::from(MENUBAR_VALUE_SIZE * 0.85 * scale); // slightly smaller to fit
-
-    let text_width = calculate_text_width(text, &font, text_scale);
-    let canvas_width = (text_width as f32 + 8.0 * scale) as u32; // small padding each side
-    let canvas_height = (22.0 * scale) as u32;
-
-    let mut img = image::RgbaImage::new(canvas_width, canvas_height);
-    let text_color = if is_dark_mode {
-        Rgba([255, 255, 255, 255])
-    } else {
-        Rgba([0, 0, 0, 255])
-    };
-
-    let x = (4.0 * scale) as i32;
-    let y = ((canvas_height as f32 - MENUBAR_VALUE_SIZE * 0.85 * scale) / 2.0) as i32;
-    draw_text_mut(&mut img, text_color, x, y, text_scale, &font, text);
-
-    let rgba = img.into_raw();
-    Ok(Image::new_owned(rgba, canvas_width, canvas_height))
+fn generate_mood_text_icon(is_dark_mode: bool, scale: f32) -> Result<Image<'static>, String> {
+    let img = menubar_icon::renderer().render_mood_text(is_dark_mode, scale)?;
+    let (width, height) = img.dimensions();
+    Ok(Image::new_owned(img.into_raw(), width, height))
 }
 
 /// Generate the normal menubar icon from cached state without calling set_icon_as_template.
-fn generate_normal_icon_from_state(app: &AppHandle) -> Option<Image<'static>> {
+/// Blink frames go through tray.set_icon, which takes one image: the sharpest scale.
+fn generate_normal_icon_from_state(app: &AppHandle, scale: f32) -> Option<Image<'static>> {
     let state = app.try_state::<MenubarState>()?;
     let balance = state.balance.lock().ok()?.clone()?;
     let settings = state.settings.lock().ok()?.clone()?;
     let is_dark = *state.is_dark.lock().ok()?; // cached on main thread — safe to read here
-
-    let limit = balance.limit.unwrap_or(0.0);
-
-    // Minimal value calculation to regenerate the icon
-    let has_data = balance.remaining_monthly.is_some()
-        || balance.usage_monthly.is_some()
-        || balance.remaining.is_some()
-        || balance.usage.is_some()
-        || balance.limit.is_some();
-
-    let daily_budget = if limit > 0.0 {
-        let now = chrono::Local::now();
-        let year = now.year();
-        let month = now.month();
-        let first_next = if month == 12 {
-            chrono::Local.with_ymd_and_hms(year + 1, 1, 1, 0, 0, 0).unwrap()
-        } else {
-            chrono::Local.with_ymd_and_hms(year, month + 1, 1, 0, 0, 0).unwrap()
-        };
-        let days = (first_next - chrono::Duration::days(1)).day() as f64;
-        limit / days
-    } else { 0.0 };
-
-    let (usage_value, remaining_value) = match settings.menubar_timeframe.as_str() {
-        "weekly" => {
-            let wb = daily_budget * 7.0;
-            let u = balance.usage_weekly.unwrap_or(0.0);
-            (u, wb - u)
-        },
-        "daily" => {
-            let u = balance.usage_daily.unwrap_or(0.0);
-            (u, daily_budget - u)
-        },
-        _ => {
-            let u = balance.usage_monthly.or(balance.usage).unwrap_or(0.0);
-            let r = balance.remaining_monthly.or(balance.remaining).unwrap_or(0.0);
-            (u, r)
-        }
-    };
-
-    let budget = match settings.menubar_timeframe.as_str() {
-        "weekly" => daily_budget * 7.0,
-        "daily"  => daily_budget,
-        _        => limit,
-    };
-
-    let display_value = if settings.show_percentage {
-        if budget > 0.0 {
-            if settings.show_remaining { (remaining_value / budget) * 100.0 }
-            else { (usage_value / budget) * 100.0 }
-        } else { 0.0 }
-    } else {
-        if settings.show_remaining { remaining_value } else { usage_value }
-    };
-
-    let final_value = if settings.decimal_places > 0 {
-        let f = 10.0f64.powi(settings.decimal_places as i32);
-        (display_value * f).round() / f
-    } else {
-        display_value.floor()
-    };
-
-    generate_hybrid_menubar_icon(
-        final_value, settings.show_percentage, has_data,
-        settings.show_unit, &settings, &balance, is_dark
-    ).ok()
+    render_tray_icon(&balance, &settings, is_dark, &[scale]).ok()?.pop()
 }
 
 /// Start the mood blink loop — alternates every 1s between normal icon and MOOD text.
@@ -2421,23 +2993,27 @@ fn start_mood_blink(app: AppHandle, blink_state: Arc<MoodBlinkState>) {
             let is_dark = app.try_state::<MenubarState>()
                 .and_then(|s| s.is_dark.lock().ok().map(|g| *g))
                 .unwrap_or(false);
+            let scale = cached_icon_scales(&app).last().copied().unwrap_or(MENUBAR_RENDER_SCALE);
             let icon = if show_mood_text {
-                generate_mood_text_icon(is_dark).ok()
+                generate_mood_text_icon(is_dark, scale).ok()
             } else {
-                generate_normal_icon_from_state(&app)
+                generate_normal_icon_from_state(&app, scale)
             };
             if let (Some(icon), Some(tray)) = (icon, app.tray_by_id("main-tray")) {
                 let _ = tray.set_icon(Some(icon));
             }
+            tray_status::refresh(&app);
             show_mood_text = !show_mood_text;
             tokio::time::sleep(Duration::from_secs(1)).await;
         }
         // Restore normal icon when blink stops
-        if let Some(icon) = generate_normal_icon_from_state(&app) {
+        let scale = cached_icon_scales(&app).last().copied().unwrap_or(MENUBAR_RENDER_SCALE);
+        if let Some(icon) = generate_normal_icon_from_state(&app, scale) {
             if let Some(tray) = app.tray_by_id("main-tray") {
                 let _ = tray.set_icon(Some(icon));
             }
         }
+        tray_status::refresh(&app);
     });
 }
 
@@ -2478,6 +3054,11 @@ fn check_and_update_mood_blink(app: AppHandle) {
 }
 
 fn main() {
+  // `bp-esc cli ...` runs headless and never starts the UI
+  if let Some(code) = cli::run_from_args() {
+    std::process::exit(code);
+  }
+
   let mut builder = tauri::Builder::default()
     .plugin(tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec!["--quiet"])))
     .plugin(tauri_plugin_window_state::Builder::default().build())
@@ -2492,7 +3073,10 @@ fn main() {
   builder
     .manage(AutoRefreshState::new())
     .manage(MenubarState::default())
+    .manage(BalanceThrottle::default())
+    .manage(proxy::ProxyState::default())
     .manage(MoodBlinkState::default())
+    .manage(pinned_trays::PinnedTrayState::default())
     .setup(|app| {
       #[cfg(target_os = "macos")]
       {
@@ -2508,6 +3092,16 @@ fn main() {
         }
       });
 
+      // Metering proxy, if enabled
+      let app_handle_proxy = app.app_handle().clone();
+      tauri::async_runtime::spawn(async move {
+        if let Ok(settings) = read_settings() {
+          if let Err(e) = proxy::apply_settings(&app_handle_proxy, &settings).await {
+            let _ = write_log(&format!("[Proxy] {}", e));
+          }
+        }
+      });
+
       // Startup mood blink check (delayed slightly to let tray settle)
       let app_handle2 = app.app_handle().clone();
       tauri::async_runtime::spawn(async move {
@@ -2516,30 +3110,14 @@ fn main() {
       });
       
       // Create a minimal placeholder menu (will be replaced after tray is built)
-      let quit_placeholder = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-      let show_placeholder = MenuItemBuilder::with_id("show_hide", "Show").build(app)?;
+      let quit_placeholder = MenuItemBuilder::with_id("quit", tr!("tray.quit")).build(app)?;
+      let show_placeholder = MenuItemBuilder::with_id("show_hide", tr!("tray.show")).build(app)?;
       let menu = Menu::with_items(app, &[&show_placeholder, &quit_placeholder])?;
       
       // Create tray icon
   let is_dark = is_macos_dark_mode();
-  let initial_icon = generate_hybrid_menubar_icon(0.0, true, false, true, &AppSettings::default(), &BalanceData {
-      limit: None,
-      usage: None,
-      usage_daily: None,
-      usage_weekly: None,
-      usage_monthly: None,
-      remaining: None,
-      remaining_monthly: None,
-      pace_ratio: None,
-      pace_month_target: None,
-      pace_week_target: None,
-      pace_day_target: None,
-      pace_month_delta_percent: None,
-      pace_week_delta_percent: None,
-      pace_day_delta_percent: None,
-      pace_status: None,
-      label: None,
-  }, is_dark).ok();
+  let scale = display_icon_scales(app.app_handle()).last().copied().unwrap_or(MENUBAR_RENDER_SCALE);
+  let initial_icon = generate_hybrid_menubar_icon(0.0, true, false, true, &AppSettings::default(), &BalanceData::default(), is_dark, scale).ok();
       let _tray = TrayIconBuilder::with_id("main-tray")
         .icon(initial_icon.unwrap_or_else(|| Image::from_bytes(include_bytes!("../icons/32x32.png")).unwrap()))
         .menu(&menu)
@@ -2577,6 +3155,24 @@ fn main() {
               }
               update_tray_menu(app.app_handle());
             }
+            id if id.starts_with("report:") => {
+              reports::handle_menu_event(id);
+            }
+            id if id.starts_with("project") => {
+              projects::handle_menu_event(app.app_handle(), id);
+            }
+            id if id.starts_with("pin:") => {
+              pinned_trays::handle_menu_event(app.app_handle(), id);
+            }
+            id if id.starts_with("rotate_key:") => {
+              // Open the key list so the user can paste the replacement key
+              if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                let _ = window.emit("navigate-tab", "settings-openrouter");
+              }
+              update_tray_menu(app.app_handle());
+            }
             _ => {}
           }
         })
@@ -2595,6 +3191,13 @@ fn main() {
 
       // Now replace placeholder menu with fully configured menu (icons, checkmarks, separators)
       update_tray_menu(app.app_handle());
+
+      // One extra icon per pinned key, fetched right away
+      pinned_trays::sync(app.app_handle());
+      let app_handle_pinned = app.app_handle().clone();
+      tauri::async_runtime::spawn(async move {
+        pinned_trays::refresh_new(&app_handle_pinned).await;
+      });
       
       // Set up KVO observation for menubar appearance changes (wallpaper-driven tint)
       #[cfg(target_os = "macos")]
@@ -2650,21 +3253,19 @@ fn main() {
 
       #[cfg(target_os = "macos")]
       if let WindowEvent::ThemeChanged(_) = event {
-        let app_handle = window.app_handle();
-        if let Some(state) = app_handle.try_state::<MenubarState>() {
-          let balance = state.balance.lock().ok().and_then(|stored| stored.clone());
-          let settings = state.settings.lock().ok().and_then(|stored| stored.clone());
-          if let (Some(balance), Some(settings)) = (balance, settings) {
-            let _ = update_menubar_display(app_handle.clone(), balance, settings);
-          }
-        }
+        let _ = redraw_menubar_from_cache(window.app_handle());
+      }
+
+      // Moved to a display with another scale; the AppKit observer covers hidden-window changes
+      if let WindowEvent::ScaleFactorChanged { .. } = event {
+        let _ = redraw_menubar_from_cache(window.app_handle());
       }
     })
     .invoke_handler(tauri::generate_handler![
         read_api_key, 
         save_api_key,
         read_settings,
-        read_opencode_openrouter_key,
+        opencode::read_opencode_openrouter_key,
         copy_to_clipboard,
         save_settings,
         reset_settings,
@@ -2686,10 +3287,122 @@ fn main() {
         fetch_mood_data,
         write_mood_entry,
         test_mood_connection,
-        notify_mood_entered
+        notify_mood_entered,
+        get_key_rotation_reminders,
+        set_successor_key,
+        rotate_api_key,
+        verify_api_key,
+        add_api_key,
+        set_provisioning_key,
+        set_key_limit_reset,
+        pinned_trays::set_key_pin,
+        opencode::get_opencode_sync_status,
+        opencode::sync_opencode_key,
+        opencode::adopt_opencode_key,
+        opencode::dismiss_opencode_key_change,
+        discovery::discover_api_keys,
+        discovery::import_discovered_keys,
+        env_export::get_env_export_status,
+        env_export::export_active_key_env,
+        activity::get_model_breakdown,
+        pricing::estimate_job_cost,
+        pricing::get_model_prices,
+        proxy::get_proxy_status,
+        proxy::set_proxy_enabled,
+        proxy::set_proxy_guard,
+        proxy::override_proxy_guard,
+        menubar_format::preview_menubar_template,
+        number_format::get_number_format,
+        history::get_proxy_spend,
+        history::get_project_spend,
+        projects::set_current_project,
+        reports::generate_report,
+        team::list_team_keys,
+        team::create_team_key,
+        team::update_team_key_limit,
+        team::set_team_key_disabled
     ])
 
 
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Wednesday noon: half a day, 2.5 days of the week and 13.5 of October's 31 days gone
+    fn wednesday_noon() -> chrono::DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 14, 12, 0, 0).unwrap()
+    }
+
+    fn key_data(limit: f64, limit_remaining: f64, limit_reset: &str) -> OpenRouterData {
+        OpenRouterData {
+            limit: Some(limit),
+            usage: Some(500.0),
+            usage_daily: Some(4.0),
+            usage_weekly: Some(20.0),
+            usage_monthly: Some(50.0),
+            limit_remaining: Some(limit_remaining),
+            label: None,
+            is_provisioning_key: None,
+            is_free_tier: None,
+            rate_limit: None,
+            limit_reset: Some(limit_reset.to_string()),
+        }
+    }
+
+    fn close(actual: Option<f64>, expected: f64) -> bool {
+        actual.is_some_and(|a| (a - expected).abs() < 1e-9)
+    }
+
+    #[test]
+    fn daily_keys_budget_and_pace_the_current_day() {
+        let balance = build_balance_data_at(key_data(10.0, 2.0, "daily"), None, wednesday_noon());
+        assert!(close(balance.usage_period, 8.0));
+        assert!(close(balance.remaining_monthly, 2.0));
+        assert!(close(balance.budget_remaining(), 2.0));
+        assert!(close(balance.usage_monthly, 50.0));
+        assert!(close(balance.pace_ratio, 0.5));
+        // Every timeframe spans the day, so all of them pace the day's $10
+        for timeframe in ["daily", "weekly", "monthly"] {
+            assert!(close(balance.timeframe_target(timeframe), 5.0), "{}", timeframe);
+            assert!(close(balance.timeframe_budget(timeframe, 31.0), 10.0), "{}", timeframe);
+            assert!(close(balance.timeframe_usage(timeframe), 8.0), "{}", timeframe);
+        }
+        assert!(close(balance.pace_month_delta_percent, 60.0));
+        assert!(close(balance.monthly_budget(31.0), 310.0));
+
+        // Shared account credits that run out first cap the day too
+        let account = AccountCredits { total_credits: 100.0, total_usage: 99.0, remaining: 1.0 };
+        let balance = build_balance_data_at(key_data(10.0, 2.0, "daily"), Some(account), wednesday_noon());
+        assert_eq!(balance.binding_constraint.as_deref(), Some("account"));
+        assert!(close(balance.budget_remaining(), 1.0));
+    }
+
+    #[test]
+    fn weekly_keys_budget_and_pace_the_current_week() {
+        let balance = build_balance_data_at(key_data(70.0, 50.0, "weekly"), None, wednesday_noon());
+        assert!(close(balance.usage_period, 20.0));
+        assert!(close(balance.budget_remaining(), 50.0));
+        assert!(close(balance.pace_ratio, 2.5 / 7.0));
+        assert!(close(balance.pace_week_target, 25.0));
+        assert!(close(balance.pace_month_target, 25.0));
+        assert!(close(balance.pace_day_target, 5.0));
+        assert!(close(balance.pace_week_delta_percent, -20.0));
+        assert!(close(balance.pace_day_delta_percent, -20.0));
+        assert!(close(balance.timeframe_budget("daily", 31.0), 10.0));
+        assert!(close(balance.timeframe_budget("monthly", 31.0), 70.0));
+        assert!(close(balance.timeframe_usage("daily"), 4.0));
+
+        // Monthly keys keep pacing the month
+        let balance = build_balance_data_at(key_data(310.0, 210.0, "monthly"), None, wednesday_noon());
+        assert!(close(balance.usage_monthly, 100.0));
+        assert!(close(balance.pace_month_target, 135.0));
+        assert!(close(balance.pace_week_target, 25.0));
+        assert!(close(balance.timeframe_budget("weekly", 31.0), 70.0));
+        assert!(close(balance.budget_remaining(), 210.0));
+    }
+}
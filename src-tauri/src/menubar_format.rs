@@ -0,0 +1,443 @@
+// Menubar text templates
+// A user template like "{remaining_pct}% · {pace_delta:+}%" replaces the value, unit and
+// timeframe superscript next to the gauge. Placeholders cover every BalanceData field plus
+// values derived for the selected timeframe; `{name:+.1}` forces a sign and sets the
+// decimals. Templates are validated when settings are saved, so drawing never fails.
+
+use chrono::Local;
+
+use crate::i18n::{self, tr, Lang};
+use crate::number_format::NumberFormat;
+use crate::{menubar_icon, read_settings, AppSettings, BalanceData};
+
+/// Longest template accepted (characters, before substitution)
+const MAX_TEMPLATE_CHARS: usize = 120;
+/// Most decimals a `{name:.N}` option may ask for
+const MAX_DECIMALS: usize = 4;
+/// Shown for values the balance does not have (no limit, no account credits, ...)
+const MISSING: &str = "–";
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    /// USD amounts, shown in the display currency; `decimal_places` of the settings by default
+    Money,
+    /// Percentages; `decimal_places` by default
+    Percent,
+    /// 0–1 fractions, two decimals by default
+    Ratio,
+    /// Whole numbers by default
+    Count,
+    Text,
+}
+
+/// Every placeholder and how it is formatted
+const FIELDS: &[(&str, Kind)] = &[
+    // BalanceData
+    ("limit", Kind::Money),
+    ("usage", Kind::Money),
+    ("usage_daily", Kind::Money),
+    ("usage_weekly", Kind::Money),
+    ("usage_monthly", Kind::Money),
+    ("remaining", Kind::Money),
+    ("remaining_monthly", Kind::Money),
+    ("effective_limit", Kind::Money),
+    ("pace_ratio", Kind::Ratio),
+    ("pace_month_target", Kind::Money),
+    ("pace_week_target", Kind::Money),
+    ("pace_day_target", Kind::Money),
+    ("pace_month_delta_percent", Kind::Percent),
+    ("pace_week_delta_percent", Kind::Percent),
+    ("pace_day_delta_percent", Kind::Percent),
+    ("pace_status", Kind::Text),
+    ("label", Kind::Text),
+    ("is_provisioning_key", Kind::Text),
+    ("is_free_tier", Kind::Text),
+    ("rate_limit", Kind::Text),
+    ("rate_limit_requests", Kind::Count),
+    ("limit_reset", Kind::Text),
+    ("binding_constraint", Kind::Text),
+    ("account_credits", Kind::Money),
+    ("account_usage", Kind::Money),
+    ("account_remaining", Kind::Money),
+    // Derived for the selected timeframe
+    ("value", Kind::Money),
+    ("unit", Kind::Text),
+    ("currency", Kind::Text),
+    ("timeframe", Kind::Text),
+    ("budget", Kind::Money),
+    ("used", Kind::Money),
+    ("left", Kind::Money),
+    ("used_pct", Kind::Percent),
+    ("remaining_pct", Kind::Percent),
+    ("pace_target", Kind::Money),
+    ("pace_delta", Kind::Percent),
+    // Derived for the reset period (the month unless the limit resets daily/weekly)
+    ("days_left", Kind::Count),
+    ("forecast", Kind::Money),
+    ("forecast_pct", Kind::Percent),
+    ("project", Kind::Text),
+];
+
+struct Placeholder {
+    name: &'static str,
+    kind: Kind,
+    sign: bool,
+    decimals: Option<usize>,
+}
+
+enum Part {
+    Literal(String),
+    Field(Placeholder),
+}
+
+/// A parsed template
+pub(crate) struct Template {
+    parts: Vec<Part>,
+}
+
+/// What placeholders are filled from
+pub(crate) struct FormatContext<'a> {
+    pub balance: &'a BalanceData,
+    pub settings: &'a AppSettings,
+    pub format: &'a NumberFormat,
+    pub days_in_month: u32,
+    /// Language of the timeframe letter
+    pub lang: Lang,
+}
+
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+/// Parse and validate a template; `{{` and `}}` are literal braces
+pub(crate) fn parse(template: &str) -> Result<Template, String> {
+    if template.chars().count() > MAX_TEMPLATE_CHARS {
+        return Err(tr!("template.too_long", MAX_TEMPLATE_CHARS));
+    }
+    if template.contains(['\n', '\r']) {
+        return Err(tr!("template.single_line"));
+    }
+
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut inner = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some('{') | None => return Err(tr!("template.unclosed", format!("{{{}", inner))),
+                        Some(c) => inner.push(c),
+                    }
+                }
+                if !literal.is_empty() {
+                    parts.push(Part::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(Part::Field(placeholder(&inner)?));
+            }
+            '}' => return Err(tr!("template.unmatched")),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+    Ok(Template { parts })
+}
+
+fn placeholder(inner: &str) -> Result<Placeholder, String> {
+    let (name, spec) = match inner.split_once(':') {
+        Some((name, spec)) => (name.trim(), Some(spec.trim())),
+        None => (inner.trim(), None),
+    };
+    let &(name, kind) = FIELDS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .ok_or_else(|| tr!("template.unknown", format!("{{{}}}", name)))?;
+    let mut placeholder = Placeholder { name, kind, sign: false, decimals: None };
+
+    let Some(spec) = spec else { return Ok(placeholder) };
+    if kind == Kind::Text {
+        return Err(tr!("template.no_options", format!("{{{}}}", name)));
+    }
+    let invalid = || tr!("template.invalid_format", spec, format!("{{{}}}", name));
+    let rest = match spec.strip_prefix('+') {
+        Some(rest) => {
+            placeholder.sign = true;
+            rest
+        }
+        None => spec,
+    };
+    if !rest.is_empty() {
+        let decimals: usize = rest.strip_prefix('.').and_then(|d| d.parse().ok()).ok_or_else(invalid)?;
+        if decimals > MAX_DECIMALS {
+            return Err(tr!("template.max_decimals", MAX_DECIMALS, format!("{{{}}}", name)));
+        }
+        placeholder.decimals = Some(decimals);
+    }
+    Ok(placeholder)
+}
+
+impl Template {
+    /// True for an empty template, which keeps the classic value/unit/superscript layout
+    pub(crate) fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    pub(crate) fn render(&self, ctx: &FormatContext) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Field(placeholder) => match value(placeholder.name, ctx) {
+                    Some(Value::Number(number)) => format_number(number, placeholder, ctx),
+                    Some(Value::Text(text)) => text,
+                    None => MISSING.to_string(),
+                },
+            })
+            .collect()
+    }
+}
+
+fn format_number(number: f64, placeholder: &Placeholder, ctx: &FormatContext) -> String {
+    let decimals = placeholder.decimals.unwrap_or(match placeholder.kind {
+        Kind::Ratio => 2,
+        Kind::Count => 0,
+        _ => ctx.settings.decimal_places as usize,
+    });
+    // `value` is a percentage or an amount depending on the unit setting
+    let is_money = placeholder.kind == Kind::Money && !(placeholder.name == "value" && ctx.settings.show_percentage);
+    let number = if is_money { ctx.format.convert(number) } else { number };
+    let factor = 10f64.powi(decimals as i32);
+    // Values that round to zero print without a sign ("-0" reads as a bug)
+    let number = if (number * factor).round() == 0.0 { 0.0 } else { number };
+    let text = ctx.format.number(number, decimals);
+    if placeholder.sign && number >= 0.0 {
+        format!("+{}", text)
+    } else {
+        text
+    }
+}
+
+/// Usage, budget and remaining dollars of the selected timeframe (budget None without a limit)
+pub(crate) fn timeframe_amounts(ctx: &FormatContext) -> (f64, Option<f64>, Option<f64>) {
+    let balance = ctx.balance;
+    let timeframe = ctx.settings.menubar_timeframe.as_str();
+    let used = balance.timeframe_usage(timeframe).unwrap_or(0.0);
+    let budget = balance.timeframe_budget(timeframe, ctx.days_in_month.max(1) as f64);
+    let left = if balance.spans_period(timeframe) {
+        budget.and(balance.budget_remaining())
+    } else {
+        budget.map(|budget| budget - used)
+    };
+    (used, budget, left)
+}
+
+fn value(name: &str, ctx: &FormatContext) -> Option<Value> {
+    let balance = ctx.balance;
+    let settings = ctx.settings;
+    let number = |n: Option<f64>| n.map(Value::Number);
+    let text = |t: Option<&str>| t.map(|t| Value::Text(t.to_string()));
+    let flag = |b: Option<bool>| b.map(|b| Value::Text(if b { "yes" } else { "no" }.to_string()));
+    let percent_of = |part: Option<f64>, whole: Option<f64>| match (part, whole) {
+        (Some(part), Some(whole)) if whole > 0.0 => Some(part / whole * 100.0),
+        _ => None,
+    };
+    let (used, budget, left) = timeframe_amounts(ctx);
+    let forecast = match (balance.budget_usage(), balance.pace_ratio) {
+        (Some(usage), Some(ratio)) if ratio > 0.0 => Some(usage / ratio),
+        _ => None,
+    };
+
+    match name {
+        "limit" => number(balance.limit),
+        "usage" => number(balance.usage),
+        "usage_daily" => number(balance.usage_daily),
+        "usage_weekly" => number(balance.usage_weekly),
+        "usage_monthly" => number(balance.usage_monthly),
+        "remaining" => number(balance.remaining),
+        "remaining_monthly" => number(balance.remaining_monthly),
+        "effective_limit" => number(balance.effective_limit),
+        "pace_ratio" => number(balance.pace_ratio),
+        "pace_month_target" => number(balance.pace_month_target),
+        "pace_week_target" => number(balance.pace_week_target),
+        "pace_day_target" => number(balance.pace_day_target),
+        "pace_month_delta_percent" => number(balance.pace_month_delta_percent),
+        "pace_week_delta_percent" => number(balance.pace_week_delta_percent),
+        "pace_day_delta_percent" => number(balance.pace_day_delta_percent),
+        "pace_status" => text(balance.pace_status.as_deref()),
+        "label" => text(balance.label.as_deref()),
+        "is_provisioning_key" => flag(balance.is_provisioning_key),
+        "is_free_tier" => flag(balance.is_free_tier),
+        "rate_limit" => balance
+            .rate_limit
+            .as_ref()
+            .map(|limit| Value::Text(format!("{}/{}", limit.requests, limit.interval))),
+        "rate_limit_requests" => balance.rate_limit.as_ref().map(|limit| Value::Number(limit.requests as f64)),
+        "limit_reset" => text(balance.limit_reset.as_deref()),
+        "binding_constraint" => text(balance.binding_constraint.as_deref()),
+        "account_credits" => number(balance.account.as_ref().map(|a| a.total_credits)),
+        "account_usage" => number(balance.account.as_ref().map(|a| a.total_usage)),
+        "account_remaining" => number(balance.account.as_ref().map(|a| a.remaining)),
+        "value" => {
+            let shown = if settings.show_remaining { left } else { Some(used) };
+            if settings.show_percentage {
+                number(percent_of(shown, budget))
+            } else {
+                number(shown)
+            }
+        }
+        "unit" => text(Some(if settings.show_percentage { "%" } else { ctx.format.symbol.as_str() })),
+        "currency" => text(Some(ctx.format.symbol.as_str())),
+        "timeframe" => text(Some(i18n::text(ctx.lang, match settings.menubar_timeframe.as_str() {
+            "weekly" => "menubar.weekly",
+            "daily" => "menubar.daily",
+            _ => "menubar.monthly",
+        }))),
+        "budget" => number(budget),
+        "used" => number(Some(used)),
+        "left" => number(left),
+        "used_pct" => number(percent_of(Some(used), budget)),
+        "remaining_pct" => number(percent_of(left, budget)),
+        "pace_target" => number(balance.timeframe_target(&settings.menubar_timeframe)),
+        "pace_delta" => number(match settings.menubar_timeframe.as_str() {
+            "weekly" => balance.pace_week_delta_percent,
+            "daily" => balance.pace_day_delta_percent,
+            _ => balance.pace_month_delta_percent,
+        }),
+        "days_left" => number(balance.pace_ratio.map(|ratio| balance.period_days(ctx.days_in_month as f64) * (1.0 - ratio))),
+        "forecast" => number(forecast),
+        "forecast_pct" => number(percent_of(forecast, balance.budget_limit())),
+        "project" => text(settings.current_project.as_deref()),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Validate a template and fill it from a balance (None = the placeholders only)
+#[tauri::command]
+pub fn preview_menubar_template(template: String, balance: Option<BalanceData>) -> Result<String, String> {
+    let parsed = parse(&template)?;
+    let settings = read_settings().unwrap_or_default();
+    let balance = balance.unwrap_or_default();
+    let format = NumberFormat::from_settings(&settings);
+    let ctx = FormatContext {
+        balance: &balance,
+        settings: &settings,
+        format: &format,
+        days_in_month: menubar_icon::days_in_month(Local::now().date_naive()),
+        lang: i18n::resolve(&settings.language),
+    };
+    Ok(parsed.render(&ctx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> BalanceData {
+        BalanceData {
+            limit: Some(100.0),
+            usage: Some(40.0),
+            usage_monthly: Some(40.0),
+            usage_weekly: Some(12.0),
+            usage_daily: Some(3.0),
+            remaining: Some(60.0),
+            remaining_monthly: Some(60.0),
+            pace_ratio: Some(0.5),
+            pace_month_target: Some(50.0),
+            pace_month_delta_percent: Some(-20.0),
+            pace_week_delta_percent: Some(12.345),
+            label: Some("Work".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn render(template: &str, settings: &AppSettings) -> String {
+        let balance = balance();
+        let format = NumberFormat::from_settings(settings);
+        let ctx = FormatContext { balance: &balance, settings, format: &format, days_in_month: 30, lang: Lang::En };
+        parse(template).unwrap().render(&ctx)
+    }
+
+    #[test]
+    fn fills_fields_and_derived_values() {
+        let settings = AppSettings::default();
+        assert_eq!(render("{remaining_pct}% · {pace_delta:+}%", &settings), "60% · -20%");
+        assert_eq!(render("{label}: ${used:.2} of ${budget}", &settings), "Work: $40.00 of $100");
+        assert_eq!(render("{value}{unit}{timeframe}", &settings), "60%M");
+        assert_eq!(render("{days_left}d, forecast ${forecast}", &settings), "15d, forecast $80");
+        assert_eq!(render("{{literal}} {pace_ratio}", &settings), "{literal} 0.50");
+    }
+
+    #[test]
+    fn follows_timeframe_and_decimal_settings() {
+        let settings = AppSettings {
+            menubar_timeframe: "weekly".to_string(),
+            decimal_places: 1,
+            show_percentage: false,
+            ..Default::default()
+        };
+        // Weekly budget: 100 / 30 * 7 = 23.33
+        assert_eq!(render("{value}{unit} {pace_delta:+.0}", &settings), "11.3$ +12");
+        assert_eq!(render("{used_pct}", &settings), "51.4");
+    }
+
+    #[test]
+    fn converts_money_but_not_percentages() {
+        let settings = AppSettings {
+            number_locale: "de".to_string(),
+            display_currency: "EUR".to_string(),
+            exchange_rate: 0.9,
+            decimal_places: 2,
+            ..Default::default()
+        };
+        assert_eq!(render("{left}{currency} {remaining_pct}% {value}{unit}", &settings), "54,00€ 60,00% 60,00%");
+        let settings = AppSettings { show_percentage: false, ..settings };
+        assert_eq!(render("{value}{unit}", &settings), "54,00€");
+    }
+
+    #[test]
+    fn missing_values_and_sign_of_zero() {
+        let settings = AppSettings::default();
+        assert_eq!(render("{account_remaining} {rate_limit} {project}", &settings), "– – –");
+        let ctx_balance = BalanceData { pace_month_delta_percent: Some(-0.2), ..balance() };
+        let format = NumberFormat::default();
+        let ctx = FormatContext {
+            balance: &ctx_balance,
+            settings: &settings,
+            format: &format,
+            days_in_month: 30,
+            lang: Lang::En,
+        };
+        assert_eq!(parse("{pace_delta:+}").unwrap().render(&ctx), "+0");
+    }
+
+    #[test]
+    fn rejects_invalid_templates() {
+        let error = |template: &str| parse(template).err().unwrap_or_default();
+        assert!(error("{nope}").contains("Unknown placeholder '{nope}'"));
+        assert!(error("{usage").contains("Unclosed"));
+        assert!(error("usage}").contains("Unmatched"));
+        assert!(error("{label:+}").contains("takes no format options"));
+        assert!(error("{usage:x}").contains("Invalid format"));
+        assert!(error("{usage:.9}").contains("At most"));
+        assert!(error("a\nb").contains("single line"));
+        assert!(error(&"x".repeat(MAX_TEMPLATE_CHARS + 1)).contains("limited"));
+        assert!(parse("").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1210 @@
+// Menubar icon renderer
+// Draws a gauge (hexagon, ring, bar or 7-day sparkline, see `IconStyle`) and the value
+// text into an RGBA image from the balance, the settings and an injected font. Nothing here asks the system: font, appearance, locale and month
+// length come in through `IconEnv`, so the output is identical on macOS and Linux and is
+// pinned by the golden images in tests/snapshots/menubar/.
+// `IconRenderer` loads the font and logo once and keeps rasterised text and recent icons:
+// the mood blink redraws every second, mostly the same two images.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use ab_glyph::{point, Font, FontVec, GlyphId, PxScale, ScaleFont};
+use chrono::{Datelike, NaiveDate};
+use image::{Rgba, RgbaImage};
+use imageproc::pixelops::weighted_sum;
+
+use crate::i18n::{self, Lang};
+use crate::menubar_format::{self, FormatContext};
+use crate::number_format::NumberFormat;
+use crate::{
+    compute_pace_status, try_load_font, AppSettings, BalanceData, END_PADDING, HEX_BORDER_PTS, HEX_SIZE_PTS,
+    LOGO_TEXT_GAP, MENUBAR_LOGO_SIZE, MENUBAR_RENDER_SCALE, MENUBAR_UNIT_SIZE, MENUBAR_VALUE_FONT, MENUBAR_VALUE_SIZE,
+    UNIT_VALUE_GAP,
+};
+
+/// Open-licensed fallback (DejaVu Sans Bold, see fonts/LICENSE-DejaVu.txt) for systems
+/// without SF Pro Rounded; also the font of the snapshot tests
+const FALLBACK_FONT: &[u8] = include_bytes!("../fonts/DejaVuSans-Bold.ttf");
+
+pub(crate) fn fallback_font() -> FontVec {
+    FontVec::try_from_vec(FALLBACK_FONT.to_vec()).expect("embedded font is a valid TrueType file")
+}
+
+/// SF Pro Rounded (or Helvetica/Arial) when installed, the embedded font otherwise
+pub(crate) fn menubar_font() -> FontVec {
+    try_load_font(MENUBAR_VALUE_FONT)
+        .map(|(font, _)| font)
+        .unwrap_or_else(fallback_font)
+}
+
+/// What the renderer would otherwise read from the system
+pub(crate) struct IconEnv<'a> {
+    /// Pixels per point: 1, 2 or 3, from the displays the menubar is on
+    pub scale: f32,
+    pub is_dark_mode: bool,
+    /// Language of the timeframe letter (T instead of D in German)
+    pub lang: Lang,
+    /// Length of the current month, for the weekly/daily share of the budget
+    pub days_in_month: u32,
+    /// Daily spend of the active key, oldest first (sparkline style only)
+    pub daily_spend: &'a [f64],
+    /// Separators, compact numbers and display currency
+    pub format: &'a NumberFormat,
+}
+
+// ============================================================================
+// RENDERER CONTEXT
+// ============================================================================
+
+/// Rendered icons kept; the mood blink alternates between two
+const IMAGE_CACHE_SIZE: usize = 8;
+/// Rasterised text runs kept; units and timeframe letters repeat, values change slowly
+const GLYPH_RUN_CACHE_SIZE: usize = 64;
+/// Sparkline series kept, one per key on the menubar (main icon and pinned keys)
+const SPEND_SERIES_CACHE_SIZE: usize = 8;
+
+/// Small most-recently-used list; the caches hold a handful of entries, so a scan is fine
+struct Recent<K, V> {
+    entries: VecDeque<(K, V)>,
+    capacity: usize,
+}
+
+impl<K: PartialEq, V: Clone> Recent<K, V> {
+    fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    fn get(&mut self, key: &K) -> Option<V> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        let value = entry.1.clone();
+        self.entries.push_front(entry);
+        Some(value)
+    }
+
+    fn insert(&mut self, key: K, value: V) {
+        self.entries.retain(|(k, _)| *k != key);
+        self.entries.push_front((key, value));
+        self.entries.truncate(self.capacity);
+    }
+}
+
+/// Coverage of one line of text in draw order, relative to the pen position. Replaying it
+/// blends exactly like imageproc's `draw_text_mut`, overlapping glyph edges included.
+struct GlyphRun {
+    coverage: Vec<(i32, i32, f32)>,
+}
+
+impl GlyphRun {
+    /// Same layout as `draw_text_mut`: baseline at the ascent, kerning after the advance
+    fn rasterise(font: &FontVec, scale: PxScale, text: &str) -> Self {
+        let scaled = font.as_scaled(scale);
+        let mut coverage = Vec::new();
+        let mut x = 0.0f32;
+        let mut last: Option<GlyphId> = None;
+        for c in text.chars() {
+            let glyph_id = font.glyph_id(c);
+            let glyph = glyph_id.with_scale_and_position(scale, point(x, scaled.ascent()));
+            x += scaled.h_advance(glyph_id);
+            if let Some(outlined) = font.outline_glyph(glyph) {
+                if let Some(last) = last {
+                    x += scaled.kern(glyph_id, last);
+                }
+                last = Some(glyph_id);
+                let bounds = outlined.px_bounds();
+                let (left, top) = (bounds.min.x.round() as i32, bounds.min.y.round() as i32);
+                outlined.draw(|gx, gy, gv| coverage.push((gx as i32 + left, gy as i32 + top, gv.clamp(0.0, 1.0))));
+            }
+        }
+        Self { coverage }
+    }
+
+    fn draw(&self, img: &mut RgbaImage, color: Rgba<u8>, x: i32, y: i32) {
+        let (width, height) = (img.width() as i32, img.height() as i32);
+        for &(gx, gy, gv) in &self.coverage {
+            let (px, py) = (gx + x, gy + y);
+            if (0..width).contains(&px) && (0..height).contains(&py) {
+                let pixel = img.get_pixel_mut(px as u32, py as u32);
+                *pixel = weighted_sum(*pixel, color, 1.0 - gv, gv);
+            }
+        }
+    }
+}
+
+/// Font, logo and recent output, loaded once and shared by every redraw
+pub(crate) struct IconRenderer {
+    font: FontVec,
+    logo: RgbaImage,
+    runs: Mutex<Recent<(String, u32), Arc<GlyphRun>>>,
+    images: Mutex<Recent<u64, RgbaImage>>,
+    /// Daily spend per (key fingerprint, day, history generation)
+    spend: Mutex<Recent<(String, NaiveDate, u64), Vec<f64>>>,
+}
+
+/// The renderer of the running app, with the system font found on first use
+pub(crate) fn renderer() -> &'static IconRenderer {
+    static RENDERER: OnceLock<IconRenderer> = OnceLock::new();
+    RENDERER.get_or_init(|| IconRenderer::new(menubar_font()))
+}
+
+/// Everything `render` reads, hashed; settings, balance and format go in as JSON
+#[allow(clippy::too_many_arguments)]
+fn image_key(
+    value: f64,
+    is_percentage: bool,
+    has_data: bool,
+    show_unit: bool,
+    settings: &AppSettings,
+    balance: &BalanceData,
+    env: &IconEnv,
+) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.to_bits().hash(&mut hasher);
+    (is_percentage, has_data, show_unit, env.is_dark_mode, env.lang, env.days_in_month).hash(&mut hasher);
+    env.scale.to_bits().hash(&mut hasher);
+    env.daily_spend.iter().for_each(|spend| spend.to_bits().hash(&mut hasher));
+    serde_json::to_string(settings).unwrap_or_default().hash(&mut hasher);
+    serde_json::to_string(balance).unwrap_or_default().hash(&mut hasher);
+    serde_json::to_string(env.format).unwrap_or_default().hash(&mut hasher);
+    hasher.finish()
+}
+
+impl IconRenderer {
+    pub(crate) fn new(font: FontVec) -> Self {
+        let logo = image::load_from_memory(include_bytes!("../icons/32x32.png"))
+            .expect("bundled logo is a valid PNG")
+            .to_rgba8();
+        Self {
+            font,
+            logo,
+            runs: Mutex::new(Recent::new(GLYPH_RUN_CACHE_SIZE)),
+            images: Mutex::new(Recent::new(IMAGE_CACHE_SIZE)),
+            spend: Mutex::new(Recent::new(SPEND_SERIES_CACHE_SIZE)),
+        }
+    }
+
+    /// Sparkline series of a key, loaded once per day and `generation` (bumped by the
+    /// history store on every new sample). `load` reads the history; the renderer doesn't.
+    pub(crate) fn daily_spend(
+        &self,
+        key_fingerprint: &str,
+        today: NaiveDate,
+        generation: u64,
+        load: impl FnOnce() -> Vec<f64>,
+    ) -> Vec<f64> {
+        let key = (key_fingerprint.to_string(), today, generation);
+        if let Some(spend) = self.spend.lock().ok().and_then(|mut spend| spend.get(&key)) {
+            return spend;
+        }
+        let spend = load();
+        if let Ok(mut cache) = self.spend.lock() {
+            cache.insert(key, spend.clone());
+        }
+        spend
+    }
+
+    /// `draw_text_mut` with the rasterised run taken from the cache when possible
+    fn draw_text(&self, img: &mut RgbaImage, color: Rgba<u8>, x: i32, y: i32, scale: PxScale, text: &str) {
+        let key = (text.to_string(), scale.y.to_bits());
+        let cached = self.runs.lock().ok().and_then(|mut runs| runs.get(&key));
+        let run = cached.unwrap_or_else(|| {
+            let run = Arc::new(GlyphRun::rasterise(&self.font, scale, text));
+            if let Ok(mut runs) = self.runs.lock() {
+                runs.insert(key, run.clone());
+            }
+            run
+        });
+        run.draw(img, color, x, y);
+    }
+
+    /// Return a recent image for the same key or render and remember it
+    fn cached(&self, key: u64, render: impl FnOnce() -> Result<RgbaImage, String>) -> Result<RgbaImage, String> {
+        if let Some(image) = self.images.lock().ok().and_then(|mut images| images.get(&key)) {
+            return Ok(image);
+        }
+        let image = render()?;
+        if let Ok(mut images) = self.images.lock() {
+            images.insert(key, image.clone());
+        }
+        Ok(image)
+    }
+}
+
+/// Scales to render for the given monitor scale factors: whole factors from 1x to 3x,
+/// fractional ones rounded up (a 1.5x display gets the 2x icon, scaled down), sharpest last
+pub(crate) fn icon_scales(monitor_scales: impl IntoIterator<Item = f64>) -> Vec<f32> {
+    let mut scales: Vec<f32> = monitor_scales
+        .into_iter()
+        .filter(|factor| factor.is_finite() && *factor > 0.0)
+        .map(|factor| (factor - 0.01).ceil().clamp(1.0, 3.0) as f32)
+        .collect();
+    scales.sort_by(f32::total_cmp);
+    scales.dedup();
+    if scales.is_empty() {
+        scales.push(MENUBAR_RENDER_SCALE);
+    }
+    scales
+}
+
+pub(crate) fn days_in_month(date: NaiveDate) -> u32 {
+    let first = date.with_day(1).unwrap_or(date);
+    let next = first.checked_add_months(chrono::Months::new(1)).unwrap_or(first);
+    (next - first).num_days() as u32
+}
+
+/// Calculate the visual width of text for centering
+pub(crate) fn calculate_text_width(text: &str, font: &FontVec, scale: PxScale) -> i32 {
+    use ab_glyph::{Font, ScaleFont};
+
+    // Measure actual glyph widths for precise centering
+    let scaled_font = font.as_scaled(scale);
+    let mut width = 0.0;
+
+    for c in text.chars() {
+        let glyph_id = font.glyph_id(c);
+        width += scaled_font.h_advance(glyph_id);
+    }
+
+    width as i32
+}
+
+// ============================================================================
+// STYLES
+// ============================================================================
+
+/// Ring gauge: outer diameter and width of the ring
+const RING_SIZE_PTS: f32 = 16.0;
+const RING_WIDTH_PTS: f32 = 4.5;
+/// Bar: length, height and how far the pace tick reaches past the bar
+const BAR_WIDTH_PTS: f32 = 22.0;
+const BAR_HEIGHT_PTS: f32 = 7.0;
+const BAR_TICK_OVERHANG_PTS: f32 = 3.0;
+/// Widest template text; longer text is cut and ends with "…"
+const TEMPLATE_MAX_WIDTH_PTS: f32 = 140.0;
+/// Thickness of the pace marker line inside the hexagon
+const PACE_MARKER_PTS: f32 = 1.0;
+/// Sparkline: box of the 7-day curve and its line width
+const SPARKLINE_WIDTH_PTS: f32 = 22.0;
+const SPARKLINE_HEIGHT_PTS: f32 = 14.0;
+const SPARKLINE_LINE_PTS: f32 = 1.5;
+/// Days shown by the sparkline (today included)
+pub(crate) const SPARKLINE_DAYS: usize = 7;
+
+/// What every style draws from; built once per render by `gauge`
+pub(crate) struct Gauge<'a> {
+    /// Share of the timeframe's budget shown (remaining or used), 0–1
+    pub fill: f32,
+    /// The hexagon holds remaining shares at the bottom and used shares at the top
+    pub shows_remaining: bool,
+    /// Where `fill` would be if spending exactly on pace, 0–1
+    pub pace: Option<f32>,
+    /// Draw `pace` as a line across the hexagon (the bar always shows its tick)
+    pub pace_marker: bool,
+    pub fill_color: Rgba<u8>,
+    pub stroke_color: Rgba<u8>,
+    /// Daily spend of the active key, oldest first
+    pub daily_spend: &'a [f64],
+}
+
+/// One way of drawing the gauge left of the value text
+pub(crate) trait IconStyle {
+    /// Width of the gauge in points
+    fn width_pts(&self) -> f32;
+    /// Draw into the left `width_pts() * scale` pixels, vertically centered
+    fn draw(&self, img: &mut RgbaImage, gauge: &Gauge, scale: f32);
+}
+
+/// Style for the `icon_style` setting; unknown names fall back to the hexagon
+pub(crate) fn icon_style(name: &str) -> &'static dyn IconStyle {
+    match name {
+        "ring" => &Ring,
+        "bar" => &Bar,
+        "sparkline" => &Sparkline,
+        _ => &Hexagon,
+    }
+}
+
+/// Hexagon filled like a glass, the default style
+struct Hexagon;
+
+impl IconStyle for Hexagon {
+    fn width_pts(&self) -> f32 {
+        HEX_SIZE_PTS
+    }
+
+    fn draw(&self, img: &mut RgbaImage, gauge: &Gauge, scale: f32) {
+        let canvas_height = img.height();
+        let hex_width = HEX_SIZE_PTS * scale;
+        // For a pointy-top hexagon with straight vertical sides:
+        // height = width / cos(30) = width / 0.866
+        let hex_height = hex_width / 0.866;
+        let hex_x_offset = 0.0;
+        let hex_y_offset = (canvas_height as f32 - hex_height) / 2.0;
+
+        // We calculate the 6 points of the hexagon
+        // Pointy top: [width/2, 0], [width, height/4], [width, 3height/4], [width/2, height], [0, 3height/4], [0, height/4]
+        let points = [
+            (hex_x_offset + hex_width / 2.0, hex_y_offset),
+            (hex_x_offset + hex_width, hex_y_offset + hex_height * 0.25),
+            (hex_x_offset + hex_width, hex_y_offset + hex_height * 0.75),
+            (hex_x_offset + hex_width / 2.0, hex_y_offset + hex_height),
+            (hex_x_offset, hex_y_offset + hex_height * 0.75),
+            (hex_x_offset, hex_y_offset + hex_height * 0.25),
+        ];
+
+        let border_thickness = (HEX_BORDER_PTS * scale) as i32;
+        let transparent = Rgba([0, 0, 0, 0]);
+
+        // Both fill directions put the surface at the used share from the top, so the
+        // on-pace surface sits there too; the gap to the fill is the pace delta
+        let marker_y = gauge.pace.filter(|_| gauge.pace_marker).map(|pace| {
+            let used = if gauge.shows_remaining { 1.0 - pace } else { pace };
+            hex_y_offset + used * hex_height
+        });
+        let marker_half = PACE_MARKER_PTS * scale / 2.0;
+
+        // Rasterize Hexagon
+        for y in 0..canvas_height {
+            for x in 0..=hex_width as u32 {
+                if is_inside_hexagon(x as f32, y as f32, &points) {
+                    let dist = distance_to_hexagon_border(x as f32, y as f32, &points);
+
+                    if dist < border_thickness as f32 {
+                        // Border
+                        img.put_pixel(x, y, gauge.stroke_color);
+                    } else if marker_y.is_some_and(|m| (y as f32 + 0.5 - m).abs() < marker_half) {
+                        img.put_pixel(x, y, gauge.stroke_color);
+                    } else {
+                        // Interior - vertical fill logic
+                        let relative_y = (y as f32 - hex_y_offset) / hex_height;
+                        let is_filled = if gauge.shows_remaining {
+                            relative_y > (1.0f32 - gauge.fill)
+                        } else {
+                            relative_y < gauge.fill
+                        };
+                        if is_filled {
+                            img.put_pixel(x, y, gauge.fill_color);
+                        } else {
+                            img.put_pixel(x, y, transparent);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Ring gauge filled clockwise from 12 o'clock
+struct Ring;
+
+impl IconStyle for Ring {
+    fn width_pts(&self) -> f32 {
+        RING_SIZE_PTS
+    }
+
+    fn draw(&self, img: &mut RgbaImage, gauge: &Gauge, scale: f32) {
+        let outer = RING_SIZE_PTS * scale / 2.0;
+        let inner = outer - RING_WIDTH_PTS * scale;
+        let border = HEX_BORDER_PTS * scale;
+        let (cx, cy) = (outer, img.height() as f32 / 2.0);
+
+        for y in 0..img.height() {
+            for x in 0..(RING_SIZE_PTS * scale).ceil() as u32 {
+                let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                let r = (dx * dx + dy * dy).sqrt();
+                if r > outer || r < inner {
+                    continue;
+                }
+                if outer - r < border || r - inner < border {
+                    img.put_pixel(x, y, gauge.stroke_color);
+                    continue;
+                }
+                // Clockwise angle from 12 o'clock as a share of the full turn
+                let turn = (dx.atan2(-dy) / std::f32::consts::TAU).rem_euclid(1.0);
+                if turn < gauge.fill {
+                    img.put_pixel(x, y, gauge.fill_color);
+                }
+            }
+        }
+    }
+}
+
+/// Horizontal bar filled from the left, with a tick where the fill should be on pace
+struct Bar;
+
+impl IconStyle for Bar {
+    fn width_pts(&self) -> f32 {
+        BAR_WIDTH_PTS
+    }
+
+    fn draw(&self, img: &mut RgbaImage, gauge: &Gauge, scale: f32) {
+        let width = BAR_WIDTH_PTS * scale;
+        let height = BAR_HEIGHT_PTS * scale;
+        let top = (img.height() as f32 - height) / 2.0;
+        let border = HEX_BORDER_PTS * scale;
+
+        for y in top as u32..(top + height) as u32 {
+            for x in 0..width as u32 {
+                let (fx, fy) = (x as f32, y as f32 - top);
+                let on_border = fx < border || fx >= width - border || fy < border || fy >= height - border;
+                if on_border {
+                    img.put_pixel(x, y, gauge.stroke_color);
+                } else if (fx - border) / (width - 2.0 * border) < gauge.fill {
+                    img.put_pixel(x, y, gauge.fill_color);
+                }
+            }
+        }
+
+        if let Some(pace) = gauge.pace {
+            let tick_x = (border + pace * (width - 2.0 * border) - border / 2.0).clamp(0.0, width - border);
+            let overhang = BAR_TICK_OVERHANG_PTS * scale;
+            let y_range = (top - overhang).max(0.0) as u32..(top + height + overhang).min(img.height() as f32) as u32;
+            for y in y_range {
+                for x in tick_x as u32..(tick_x + border) as u32 {
+                    img.put_pixel(x, y, gauge.stroke_color);
+                }
+            }
+        }
+    }
+}
+
+/// Daily spend of the last week as a line over a filled area, scaled to the busiest day
+struct Sparkline;
+
+impl IconStyle for Sparkline {
+    fn width_pts(&self) -> f32 {
+        SPARKLINE_WIDTH_PTS
+    }
+
+    fn draw(&self, img: &mut RgbaImage, gauge: &Gauge, scale: f32) {
+        let line = SPARKLINE_LINE_PTS * scale;
+        let width = SPARKLINE_WIDTH_PTS * scale;
+        let height = SPARKLINE_HEIGHT_PTS * scale;
+        let top = (img.height() as f32 - height) / 2.0;
+        let bottom = top + height - line / 2.0;
+
+        let max = gauge.daily_spend.iter().cloned().fold(0.0, f64::max);
+        let steps = gauge.daily_spend.len().saturating_sub(1).max(1) as f32;
+        let points: Vec<(f32, f32)> = gauge
+            .daily_spend
+            .iter()
+            .enumerate()
+            .map(|(i, spend)| {
+                let level = if max > 0.0 { (spend / max) as f32 } else { 0.0 };
+                let x = line / 2.0 + i as f32 * (width - line) / steps;
+                (x, bottom - level * (height - line))
+            })
+            .collect();
+
+        for y in top.max(0.0) as u32..(top + height).ceil() as u32 {
+            for x in 0..width as u32 {
+                let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+                let near_line = points
+                    .windows(2)
+                    .any(|segment| distance_to_segment(fx, fy, segment[0], segment[1]) <= line / 2.0);
+                if near_line {
+                    img.put_pixel(x, y, gauge.stroke_color);
+                } else if fy > curve_y(&points, fx).unwrap_or(f32::MAX) && fy < bottom {
+                    img.put_pixel(x, y, gauge.fill_color);
+                }
+            }
+        }
+    }
+}
+
+/// Height of the polyline at `x` (None outside of it)
+fn curve_y(points: &[(f32, f32)], x: f32) -> Option<f32> {
+    points.windows(2).find(|s| x >= s[0].0 && x <= s[1].0).map(|s| {
+        let t = (x - s[0].0) / (s[1].0 - s[0].0);
+        s[0].1 + t * (s[1].1 - s[0].1)
+    })
+}
+
+// ============================================================================
+// RENDERING
+// ============================================================================
+
+/// Fill colour by pace status (white when monochrome or unknown)
+fn fill_color(settings: &AppSettings, balance: &BalanceData) -> Rgba<u8> {
+    if settings.menubar_monochrome {
+        return Rgba([255, 255, 255, 180]);
+    }
+    match compute_pace_status(balance, settings) {
+        Some("ahead") => Rgba([239, 68, 68, 200]),
+        Some("behind") => Rgba([234, 179, 8, 210]),
+        Some("on_track") => Rgba([16, 185, 129, 200]),
+        _ => Rgba([255, 255, 255, 128]),
+    }
+}
+
+/// Fill level and pace position of the selected timeframe's share of the budget
+fn budget_share(settings: &AppSettings, balance: &BalanceData, days_in_month: u32) -> (f32, Option<f32>) {
+    let timeframe = settings.menubar_timeframe.as_str();
+    let Some(budget) = balance.timeframe_budget(timeframe, days_in_month.max(1) as f64) else {
+        return (1.0, None);
+    };
+    let usage_val = balance.timeframe_usage(timeframe).unwrap_or(0.0);
+    let target = balance.timeframe_target(timeframe);
+
+    let share = |used: f64| {
+        let shown = if settings.show_remaining { budget - used } else { used };
+        (shown as f32 / budget as f32).clamp(0.0, 1.0)
+    };
+    (share(usage_val), target.map(share))
+}
+
+impl IconRenderer {
+    /// Hybrid menubar icon: the gauge of the selected style (hexagon by default) showing
+    /// the timeframe's share of the budget, then value, unit and timeframe superscript
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        value: f64,
+        is_percentage: bool,
+        has_data: bool,
+        show_unit: bool,
+        settings: &AppSettings,
+        balance: &BalanceData,
+        env: &IconEnv,
+    ) -> Result<RgbaImage, String> {
+        let key = image_key(value, is_percentage, has_data, show_unit, settings, balance, env);
+        self.cached(key, || self.render_uncached(value, is_percentage, has_data, show_unit, settings, balance, env))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_uncached(
+        &self,
+        value: f64,
+        is_percentage: bool,
+        has_data: bool,
+        show_unit: bool,
+        settings: &AppSettings,
+        balance: &BalanceData,
+        env: &IconEnv,
+    ) -> Result<RgbaImage, String> {
+        let scale = env.scale;
+
+        // If no data, show logo only (centered in a standard 22pt-equivalent box for consistency)
+        if !has_data {
+            let logo_physical_size = (MENUBAR_LOGO_SIZE as f32 * scale) as u32;
+            let logo_scaled = image::imageops::resize(
+                &self.logo,
+                logo_physical_size,
+                logo_physical_size,
+                image::imageops::FilterType::Lanczos3
+            );
+
+            let canvas_size = (22.0 * scale) as u32;
+            let mut img = RgbaImage::new(canvas_size, canvas_size);
+            let x = (canvas_size - logo_physical_size) / 2;
+            let y = (canvas_size - logo_physical_size) / 2;
+            image::imageops::overlay(&mut img, &logo_scaled, x as i64, y as i64);
+            return Ok(img);
+        }
+
+        let style = icon_style(&settings.icon_style);
+        // Adaptive stroke/text color based on macOS appearance
+        let stroke_color = if env.is_dark_mode {
+            Rgba([255, 255, 255, 255])  // White for dark mode
+        } else {
+            Rgba([0, 0, 0, 255])        // Black for light mode
+        };
+        let (fill, pace) = budget_share(settings, balance, env.days_in_month);
+        let gauge = Gauge {
+            fill,
+            shows_remaining: settings.show_remaining,
+            pace,
+            pace_marker: settings.show_pace_marker,
+            fill_color: fill_color(settings, balance),
+            stroke_color,
+            daily_spend: env.daily_spend,
+        };
+
+        // A user template replaces value, unit and superscript (invalid ones never get saved;
+        // a hand-edited one falls back to the classic layout)
+        if let Some(template) = menubar_format::parse(&settings.menubar_template).ok().filter(|t| !t.is_empty()) {
+            let ctx = FormatContext {
+                balance,
+                settings,
+                format: env.format,
+                days_in_month: env.days_in_month,
+                lang: env.lang,
+            };
+            return Ok(self.render_text_layout(style, &gauge, &template.render(&ctx), scale));
+        }
+
+        // Prepare text
+        let shown = if is_percentage { value } else { env.format.convert(value) };
+        // Whole numbers round half away from zero ("{:.0}" rounds half to even); + 0.0 drops "-0"
+        let shown = if settings.decimal_places == 0 { shown.round() + 0.0 } else { shown };
+        let value_text = env.format.number(shown, settings.decimal_places as usize);
+        let unit_text = if is_percentage { "%" } else { env.format.symbol.as_str() };
+
+        // Timeframe indicator (superscript)
+        let timeframe_indicator = i18n::text(env.lang, match settings.menubar_timeframe.as_str() {
+            "weekly" => "menubar.weekly",
+            "daily" => "menubar.daily",
+            _ => "menubar.monthly",
+        });
+
+        // All menubar text uses Semibold for consistent appearance
+        let val_font = &self.font;
+
+        let val_scale = PxScale::from(MENUBAR_VALUE_SIZE * scale);
+        let unt_scale = PxScale::from(MENUBAR_UNIT_SIZE * scale);
+
+        let show_timeframe_indicator = settings.show_timeframe_indicator;
+
+        // Superscript: 60% of value size
+        let sup_scale = PxScale::from(MENUBAR_VALUE_SIZE * 0.6 * scale);
+
+        let val_width = calculate_text_width(&value_text, val_font, val_scale);
+        let unt_width = calculate_text_width(unit_text, val_font, unt_scale);
+        let sup_width = if show_timeframe_indicator {
+            calculate_text_width(timeframe_indicator, val_font, sup_scale)
+        } else {
+            0
+        };
+
+        // Calculate total width (logical points then scale)
+        // Layout: [gauge] [gap] [value] [unit?] [superscript_indicator] [padding]
+        let mut text_part_width = val_width as f32 / scale;
+
+        // Both modes: show unit + optional superscript indicator
+        if show_unit {
+            text_part_width += UNIT_VALUE_GAP + (unt_width as f32 / scale);
+        }
+        if show_timeframe_indicator {
+            text_part_width += 2.0 + (sup_width as f32 / scale);
+        }
+        let total_width_pts = style.width_pts() + LOGO_TEXT_GAP + text_part_width + END_PADDING;
+
+        let canvas_width = (total_width_pts * scale) as u32;
+        let canvas_height = (22.0 * scale) as u32; // Standard macOS height
+
+        let mut img = RgbaImage::new(canvas_width, canvas_height);
+
+        // 1. Draw the gauge
+        style.draw(&mut img, &gauge, scale);
+
+        // 2. Draw Text (White)
+        // Unified: [value] [unit?] [superscript D/W/M]
+        let text_color = stroke_color;
+        let mut current_x = (style.width_pts() + LOGO_TEXT_GAP) * scale;
+
+        let val_y = (canvas_height as f32 - (MENUBAR_VALUE_SIZE * scale)) / 2.0;
+        self.draw_text(&mut img, text_color, current_x as i32, val_y as i32, val_scale, &value_text);
+        current_x += val_width as f32;
+
+        if show_unit {
+            current_x += UNIT_VALUE_GAP * scale;
+            let unt_y = (canvas_height as f32 - (MENUBAR_UNIT_SIZE * scale)) / 2.0;
+            self.draw_text(&mut img, text_color, current_x as i32, unt_y as i32, unt_scale, unit_text);
+            current_x += unt_width as f32;
+        }
+
+        if show_timeframe_indicator {
+            // Superscript timeframe indicator (D/W/M)
+            current_x += 2.0 * scale;
+            let sup_y = val_y - (MENUBAR_VALUE_SIZE * 0.3 * scale) + 2.0;
+            self.draw_text(&mut img, text_color, current_x as i32, sup_y as i32, sup_scale, timeframe_indicator);
+        }
+
+        Ok(img)
+    }
+
+    /// Gauge plus one line of template text, cut to `TEMPLATE_MAX_WIDTH_PTS`
+    fn render_text_layout(&self, style: &dyn IconStyle, gauge: &Gauge, text: &str, scale: f32) -> RgbaImage {
+        let font = &self.font;
+        let text_scale = PxScale::from(MENUBAR_VALUE_SIZE * scale);
+        let text = fit_text(text.trim(), font, text_scale, TEMPLATE_MAX_WIDTH_PTS * scale);
+        let text_width = calculate_text_width(&text, font, text_scale) as f32 / scale;
+
+        let mut total_width_pts = style.width_pts() + END_PADDING;
+        if !text.is_empty() {
+            total_width_pts += LOGO_TEXT_GAP + text_width;
+        }
+        let canvas_height = (22.0 * scale) as u32;
+        let mut img = RgbaImage::new((total_width_pts * scale) as u32, canvas_height);
+        style.draw(&mut img, gauge, scale);
+
+        let x = (style.width_pts() + LOGO_TEXT_GAP) * scale;
+        let y = (canvas_height as f32 - (MENUBAR_VALUE_SIZE * scale)) / 2.0;
+        self.draw_text(&mut img, gauge.stroke_color, x as i32, y as i32, text_scale, &text);
+        img
+    }
+
+    /// "MOOD" in place of the value, shown by the mood blink
+    pub(crate) fn render_mood_text(&self, is_dark_mode: bool, scale: f32) -> Result<RgbaImage, String> {
+        let mut hasher = DefaultHasher::new();
+        ("MOOD", is_dark_mode, scale.to_bits()).hash(&mut hasher);
+        self.cached(hasher.finish(), || {
+            let text = "MOOD";
+            let text_scale = PxScale::from(MENUBAR_VALUE_SIZE * 0.85 * scale); // slightly smaller to fit
+
+            let text_width = calculate_text_width(text, &self.font, text_scale);
+            let canvas_width = (text_width as f32 + 8.0 * scale) as u32; // small padding each side
+            let canvas_height = (22.0 * scale) as u32;
+
+            let mut img = RgbaImage::new(canvas_width, canvas_height);
+            let text_color = if is_dark_mode {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            };
+
+            let x = (4.0 * scale) as i32;
+            let y = ((canvas_height as f32 - MENUBAR_VALUE_SIZE * 0.85 * scale) / 2.0) as i32;
+            self.draw_text(&mut img, text_color, x, y, text_scale, text);
+            Ok(img)
+        })
+    }
+}
+
+/// Cut text that is wider than `max_width` pixels and end it with "…"
+pub(crate) fn fit_text(text: &str, font: &FontVec, scale: PxScale, max_width: f32) -> String {
+    if calculate_text_width(text, font, scale) as f32 <= max_width {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let cut = format!("{}…", chars.iter().collect::<String>().trim_end());
+        if calculate_text_width(&cut, font, scale) as f32 <= max_width {
+            return cut;
+        }
+    }
+    "…".to_string()
+}
+
+fn is_inside_hexagon(x: f32, y: f32, p: &[(f32, f32); 6]) -> bool {
+    // Simple point-in-polygon for convex hexagon
+    let mut inside = true;
+    for i in 0..6 {
+        let p1 = p[i];
+        let p2 = p[(i + 1) % 6];
+        // Cross product to check side
+        if (p2.0 - p1.0) * (y - p1.1) - (p2.1 - p1.1) * (x - p1.0) < 0.0 {
+            inside = false;
+            break;
+        }
+    }
+    inside
+}
+
+fn distance_to_hexagon_border(x: f32, y: f32, p: &[(f32, f32); 6]) -> f32 {
+    (0..6)
+        .map(|i| distance_to_segment(x, y, p[i], p[(i + 1) % 6]))
+        .fold(f32::MAX, f32::min)
+}
+
+fn distance_to_segment(x: f32, y: f32, p1: (f32, f32), p2: (f32, f32)) -> f32 {
+    let dx = p2.0 - p1.0;
+    let dy = p2.1 - p1.1;
+    let l2 = dx * dx + dy * dy;
+
+    let t = ((x - p1.0) * dx + (y - p1.1) * dy) / l2;
+    let t = t.clamp(0.0, 1.0);
+
+    let px = p1.0 + t * dx;
+    let py = p1.1 + t * dy;
+
+    ((x - px).powi(2) + (y - py).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Run with UPDATE_SNAPSHOTS=1 to rewrite the golden images after an intended change
+    const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+    /// Anti-aliasing may differ by a step or two between CPUs
+    const CHANNEL_TOLERANCE: u8 = 3;
+
+    fn snapshot_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/menubar")
+    }
+
+    /// Compare with the stored PNG; returns a description of the mismatch
+    fn check_snapshot(name: &str, actual: &RgbaImage) -> Result<(), String> {
+        let path = snapshot_dir().join(format!("{}.png", name));
+        if std::env::var_os(UPDATE_ENV).is_some() {
+            std::fs::create_dir_all(snapshot_dir()).map_err(|e| e.to_string())?;
+            return actual.save(&path).map_err(|e| format!("{}: {}", name, e));
+        }
+
+        let expected = image::open(&path)
+            .map_err(|e| format!("{}: no snapshot at {} ({}); run with {}=1", name, path.display(), e, UPDATE_ENV))?
+            .to_rgba8();
+        let failed = std::env::temp_dir().join(format!("bpesc-snapshot-{}.png", name));
+        if expected.dimensions() != actual.dimensions() {
+            let _ = actual.save(&failed);
+            return Err(format!(
+                "{}: size {:?} != snapshot {:?} (actual image: {})",
+                name,
+                actual.dimensions(),
+                expected.dimensions(),
+                failed.display()
+            ));
+        }
+        let differing = expected
+            .pixels()
+            .zip(actual.pixels())
+            .filter(|(e, a)| e.0.iter().zip(a.0.iter()).any(|(e, a)| e.abs_diff(*a) > CHANNEL_TOLERANCE))
+            .count();
+        if differing > 0 {
+            let _ = actual.save(&failed);
+            return Err(format!("{}: {} pixels differ (actual image: {})", name, differing, failed.display()));
+        }
+        Ok(())
+    }
+
+    /// $100 monthly budget, $40 used this month, $12 this week, $3 today
+    fn balance(pace_delta: Option<f64>) -> BalanceData {
+        BalanceData {
+            limit: Some(100.0),
+            usage: Some(40.0),
+            usage_monthly: Some(40.0),
+            usage_weekly: Some(12.0),
+            usage_daily: Some(3.0),
+            remaining: Some(60.0),
+            remaining_monthly: Some(60.0),
+            pace_month_delta_percent: pace_delta,
+            pace_week_delta_percent: pace_delta,
+            pace_day_delta_percent: pace_delta,
+            pace_month_target: Some(50.0),
+            pace_week_target: Some(10.0),
+            pace_day_target: Some(2.0),
+            ..Default::default()
+        }
+    }
+
+    struct Case {
+        name: &'static str,
+        value: f64,
+        is_percentage: bool,
+        has_data: bool,
+        dark: bool,
+        lang: Lang,
+        settings: AppSettings,
+        balance: BalanceData,
+        daily_spend: Vec<f64>,
+        format: NumberFormat,
+    }
+
+    fn case(name: &'static str, value: f64, configure: impl FnOnce(&mut Case)) -> Case {
+        let mut case = Case {
+            name,
+            value,
+            is_percentage: true,
+            has_data: true,
+            dark: false,
+            lang: Lang::En,
+            settings: AppSettings::default(),
+            balance: balance(Some(-10.0)),
+            daily_spend: vec![1.5, 4.0, 0.0, 2.5, 6.0, 3.0, 3.0],
+            format: NumberFormat::default(),
+        };
+        configure(&mut case);
+        case
+    }
+
+    fn colored(case: &mut Case, pace_delta: Option<f64>) {
+        case.settings.menubar_monochrome = false;
+        case.balance = balance(pace_delta);
+    }
+
+    #[test]
+    fn menubar_icon_matches_snapshots() {
+        // One renderer for all cases, so cached text runs are replayed into later icons
+        let renderer = IconRenderer::new(fallback_font());
+        let cases = [
+            case("no_data", 0.0, |c| {
+                c.has_data = false;
+                c.balance = BalanceData::default();
+            }),
+            case("monthly_percent_remaining_light", 60.0, |_| {}),
+            case("monthly_percent_remaining_dark", 60.0, |c| c.dark = true),
+            case("pace_on_track", 60.0, |c| colored(c, Some(-10.0))),
+            case("pace_behind", 60.0, |c| colored(c, Some(10.0))),
+            case("pace_ahead", 60.0, |c| colored(c, Some(45.0))),
+            case("pace_unknown", 60.0, |c| colored(c, None)),
+            case("pace_ahead_dark", 60.0, |c| {
+                colored(c, Some(45.0));
+                c.dark = true;
+            }),
+            case("weekly_percent_remaining", 45.0, |c| c.settings.menubar_timeframe = "weekly".to_string()),
+            case("daily_percent_remaining", 7.0, |c| c.settings.menubar_timeframe = "daily".to_string()),
+            case("daily_german", 7.0, |c| {
+                c.settings.menubar_timeframe = "daily".to_string();
+                c.lang = Lang::De;
+            }),
+            case("dollars_remaining", 60.0, |c| c.is_percentage = false),
+            case("dollars_used_fills_from_top", 40.0, |c| {
+                c.is_percentage = false;
+                c.settings.show_remaining = false;
+            }),
+            case("decimals_two", 59.87, |c| c.settings.decimal_places = 2),
+            case("value_only", 60.0, |c| {
+                c.settings.show_unit = false;
+                c.settings.show_timeframe_indicator = false;
+            }),
+            case("pace_marker_remaining", 60.0, |c| c.settings.show_pace_marker = true),
+            case("pace_marker_used_ahead_dark", 40.0, |c| {
+                colored(c, Some(45.0));
+                c.settings.show_pace_marker = true;
+                c.settings.show_remaining = false;
+                c.dark = true;
+            }),
+            case("template", 60.0, |c| c.settings.menubar_template = "{remaining_pct}% · {pace_delta:+}%".to_string()),
+            case("template_truncated_dark", 60.0, |c| {
+                c.settings.menubar_template = "${left:.2} left of ${budget:.2} this month".to_string();
+                c.dark = true;
+            }),
+            case("euro_compact_decimal_comma", 1234.0, |c| {
+                c.is_percentage = false;
+                c.format = NumberFormat {
+                    decimal_comma: true,
+                    compact: true,
+                    currency: "EUR".to_string(),
+                    symbol: "€".to_string(),
+                    rate: 0.9,
+                };
+            }),
+            case("euro_decimals_template", 60.0, |c| {
+                c.settings.decimal_places = 2;
+                c.settings.menubar_template = "{left}{unit} · {remaining_pct:.0}%".to_string();
+                c.settings.show_percentage = false;
+                c.format = NumberFormat {
+                    decimal_comma: true,
+                    currency: "EUR".to_string(),
+                    symbol: "€".to_string(),
+                    rate: 0.9,
+                    ..NumberFormat::default()
+                };
+            }),
+            case("ring", 60.0, |c| c.settings.icon_style = "ring".to_string()),
+            case("ring_used_ahead_dark", 40.0, |c| {
+                colored(c, Some(45.0));
+                c.settings.icon_style = "ring".to_string();
+                c.settings.show_remaining = false;
+                c.dark = true;
+            }),
+            case("bar", 60.0, |c| c.settings.icon_style = "bar".to_string()),
+            case("bar_used_behind_weekly", 40.0, |c| {
+                colored(c, Some(10.0));
+                c.settings.icon_style = "bar".to_string();
+                c.settings.show_remaining = false;
+                c.settings.menubar_timeframe = "weekly".to_string();
+            }),
+            case("sparkline", 60.0, |c| c.settings.icon_style = "sparkline".to_string()),
+            case("sparkline_no_history_dark", 60.0, |c| {
+                c.settings.icon_style = "sparkline".to_string();
+                c.daily_spend.clear();
+                c.dark = true;
+            }),
+        ];
+
+        // 2x images keep the plain case name; a subset is also pinned at 1x and 3x
+        let scaled_names = ["no_data", "monthly_percent_remaining_light", "pace_marker_remaining", "template", "ring", "bar", "sparkline"];
+        let runs = cases.iter().flat_map(|c| {
+            let extra: &[f32] = if scaled_names.contains(&c.name) { &[1.0, 3.0] } else { &[] };
+            std::iter::once((c, MENUBAR_RENDER_SCALE, c.name.to_string()))
+                .chain(extra.iter().map(move |&scale| (c, scale, format!("{}@{}x", c.name, scale))))
+        });
+
+        let failures: Vec<String> = runs
+            .filter_map(|(c, scale, name)| {
+                let env = IconEnv {
+                    scale,
+                    is_dark_mode: c.dark,
+                    lang: c.lang,
+                    days_in_month: 30,
+                    daily_spend: &c.daily_spend,
+                    format: &c.format,
+                };
+                let image = renderer
+                    .render(c.value, c.is_percentage, c.has_data, c.settings.show_unit, &c.settings, &c.balance, &env)
+                    .unwrap();
+                let expected_height = (22.0 * scale) as u32;
+                if image.height() != expected_height {
+                    return Some(format!("{}: {} px high instead of {}", name, image.height(), expected_height));
+                }
+                check_snapshot(&name, &image).err()
+            })
+            .collect();
+        assert!(failures.is_empty(), "snapshot mismatches:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn icon_scales_follow_the_monitors() {
+        assert_eq!(icon_scales([]), vec![MENUBAR_RENDER_SCALE]);
+        assert_eq!(icon_scales([2.0, 1.0, 2.0]), vec![1.0, 2.0]);
+        // Fractional (Windows/Linux) factors round up; anything above 3x gets the 3x icon
+        assert_eq!(icon_scales([1.25, 1.5, 2.000001]), vec![2.0]);
+        assert_eq!(icon_scales([4.0, 3.0, f64::NAN, 0.0]), vec![3.0]);
+    }
+
+    #[test]
+    fn pace_marker_sits_at_the_target_share() {
+        let renderer = IconRenderer::new(fallback_font());
+        let format = NumberFormat::default();
+        let env = IconEnv {
+            scale: MENUBAR_RENDER_SCALE,
+            is_dark_mode: false,
+            lang: Lang::En,
+            days_in_month: 30,
+            daily_spend: &[],
+            format: &format,
+        };
+        let scale = MENUBAR_RENDER_SCALE;
+        let hex_height = HEX_SIZE_PTS * scale / 0.866;
+        let hex_top = (22.0 * scale - hex_height) / 2.0;
+        let column = (HEX_SIZE_PTS * scale / 2.0) as u32;
+        // Skip the pointed ends, where the border runs through the centre column
+        let interior = (hex_top + 4.0 * scale) as u32..(hex_top + hex_height - 4.0 * scale) as u32;
+        let marker_rows = |image: &RgbaImage| -> Vec<u32> {
+            interior.clone().filter(|&y| image.get_pixel(column, y).0 == [0, 0, 0, 255]).collect()
+        };
+
+        for show_remaining in [true, false] {
+            for ratio in [0.2, 0.35, 0.5, 0.65, 0.8] {
+                let mut balance = balance(None);
+                balance.pace_month_target = Some(ratio * 100.0);
+                let mut settings = AppSettings { show_pace_marker: true, show_remaining, ..Default::default() };
+                let image = renderer.render(50.0, true, true, true, &settings, &balance, &env).unwrap();
+
+                let rows = marker_rows(&image);
+                assert!(!rows.is_empty(), "no marker at {} (remaining: {})", ratio, show_remaining);
+                let center = rows.iter().sum::<u32>() as f32 / rows.len() as f32 + 0.5;
+                let expected = hex_top + ratio as f32 * hex_height;
+                assert!(
+                    (center - expected).abs() <= 1.0,
+                    "marker at y={} instead of {} for {} (remaining: {})",
+                    center,
+                    expected,
+                    ratio,
+                    show_remaining
+                );
+
+                settings.show_pace_marker = false;
+                let image = renderer.render(50.0, true, true, true, &settings, &balance, &env).unwrap();
+                assert!(marker_rows(&image).is_empty(), "marker drawn although disabled");
+            }
+        }
+    }
+
+    #[test]
+    fn reuses_rendered_icons_and_text_runs() {
+        let renderer = IconRenderer::new(fallback_font());
+        let settings = AppSettings::default();
+        let balance = balance(Some(-10.0));
+        let format = NumberFormat::default();
+        let env = IconEnv {
+            scale: MENUBAR_RENDER_SCALE,
+            is_dark_mode: false,
+            lang: Lang::En,
+            days_in_month: 30,
+            daily_spend: &[],
+            format: &format,
+        };
+        let run_count = || renderer.runs.lock().unwrap().entries.len();
+        let image_count = || renderer.images.lock().unwrap().entries.len();
+
+        let first = renderer.render(60.0, true, true, true, &settings, &balance, &env).unwrap();
+        assert_eq!((run_count(), image_count()), (3, 1)); // "60", "%", "M"
+        let again = renderer.render(60.0, true, true, true, &settings, &balance, &env).unwrap();
+        assert_eq!(first, again);
+        assert_eq!((run_count(), image_count()), (3, 1));
+
+        // A new value rasterises only its digits
+        renderer.render(59.0, true, true, true, &settings, &balance, &env).unwrap();
+        assert_eq!((run_count(), image_count()), (4, 2));
+        let dark = IconEnv { is_dark_mode: true, ..env };
+        assert_ne!(renderer.render(59.0, true, true, true, &settings, &balance, &dark).unwrap(), first);
+        assert_eq!((run_count(), image_count()), (4, 3));
+
+        for _ in 0..IMAGE_CACHE_SIZE * 2 {
+            renderer.render_mood_text(false, MENUBAR_RENDER_SCALE).unwrap();
+        }
+        assert_eq!(image_count(), 4);
+
+        // The sparkline series is read again only for a new day or a new sample
+        let loads = std::cell::Cell::new(0);
+        let today = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let series = |day: NaiveDate, generation: u64| {
+            renderer.daily_spend("fp", day, generation, || {
+                loads.set(loads.get() + 1);
+                vec![1.0; SPARKLINE_DAYS]
+            })
+        };
+        assert_eq!(series(today, 1), vec![1.0; SPARKLINE_DAYS]);
+        series(today, 1);
+        assert_eq!(loads.get(), 1);
+        series(today, 2);
+        series(today.succ_opt().unwrap(), 2);
+        assert_eq!(loads.get(), 3);
+    }
+
+    /// Time per redraw with and without the renderer context:
+    /// `cargo test --release menubar_icon_render_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn menubar_icon_render_benchmark() {
+        use std::time::{Duration, Instant};
+
+        const FRAMES: u32 = 200;
+        fn time_frames(label: &str, mut frame: impl FnMut(u32)) -> Duration {
+            let start = Instant::now();
+            for i in 0..FRAMES {
+                frame(i);
+            }
+            let per_frame = start.elapsed() / FRAMES;
+            println!("{:<44} {:>9.1} µs/frame", label, per_frame.as_secs_f64() * 1e6);
+            per_frame
+        }
+
+        let settings = AppSettings::default();
+        let balance = balance(Some(-10.0));
+        let format = NumberFormat::default();
+        let env = IconEnv {
+            scale: MENUBAR_RENDER_SCALE,
+            is_dark_mode: false,
+            lang: Lang::En,
+            days_in_month: 30,
+            daily_spend: &[],
+            format: &format,
+        };
+
+        // What every redraw did before: font looked up on disk, logo decoded, nothing kept
+        let uncached = time_frames("font lookup + full render", |_| {
+            let renderer = IconRenderer::new(menubar_font());
+            renderer.render(60.0, true, true, true, &settings, &balance, &env).unwrap();
+        });
+        let renderer = IconRenderer::new(menubar_font());
+        time_frames("new value every frame (text runs reused)", |i| {
+            renderer.render(60.0 + i as f64, true, true, true, &settings, &balance, &env).unwrap();
+        });
+        let blink = time_frames("mood blink (two alternating icons)", |i| {
+            if i % 2 == 0 {
+                renderer.render_mood_text(false, MENUBAR_RENDER_SCALE).unwrap();
+            } else {
+                renderer.render(60.0, true, true, true, &settings, &balance, &env).unwrap();
+            }
+        });
+        assert!(blink < uncached, "cached redraw ({:?}) not faster than uncached ({:?})", blink, uncached);
+    }
+
+    #[test]
+    fn days_in_month_handles_leap_years() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(days_in_month(day(2026, 2, 14)), 28);
+        assert_eq!(days_in_month(day(2028, 2, 29)), 29);
+        assert_eq!(days_in_month(day(2026, 12, 31)), 31);
+        assert_eq!(days_in_month(day(2026, 4, 1)), 30);
+    }
+}
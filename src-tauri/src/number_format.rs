@@ -0,0 +1,205 @@
+// Number and money formatting
+// How amounts look in the menubar, the window (via `get_number_format`), the CLI and
+// notifications: decimal separator by locale, optional compact forms (1.2k, 12.5k) and a
+// display currency converted from USD, OpenRouter's billing currency, at a user-set rate.
+// Reports and --json output stay in USD.
+
+use serde::Serialize;
+
+use crate::i18n::{self, tr, Lang};
+use crate::{read_settings, AppSettings};
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NumberFormat {
+    /// "1.234,50" instead of "1,234.50"
+    pub decimal_comma: bool,
+    /// 1.2k / 12.5k / 3.4M for values from 1000 on
+    pub compact: bool,
+    /// ISO code of the display currency
+    pub currency: String,
+    pub symbol: String,
+    /// Display currency per USD
+    pub rate: f64,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_comma: false,
+            compact: false,
+            currency: "USD".to_string(),
+            symbol: "$".to_string(),
+            rate: 1.0,
+        }
+    }
+}
+
+fn currency_symbol(code: &str) -> String {
+    match code {
+        "USD" => "$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Reject currencies and rates the formatter could not use
+pub(crate) fn validate(settings: &AppSettings) -> Result<(), String> {
+    if !matches!(settings.number_locale.as_str(), "auto" | "en" | "de") {
+        return Err(tr!("format.unknown_locale", settings.number_locale));
+    }
+    let code = settings.display_currency.trim();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(tr!("format.currency_code", code));
+    }
+    let rate_set = settings.exchange_rate.is_finite() && settings.exchange_rate > 0.0;
+    if !code.eq_ignore_ascii_case("USD") && !rate_set {
+        return Err(tr!("format.exchange_rate", code.to_uppercase()));
+    }
+    Ok(())
+}
+
+impl NumberFormat {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        let decimal_comma = match settings.number_locale.as_str() {
+            "de" => true,
+            "en" => false,
+            _ => i18n::resolve(&settings.language) == Lang::De,
+        };
+        let currency = settings.display_currency.trim().to_uppercase();
+        let usable = validate(settings).is_ok() && currency != "USD";
+        let (currency, rate) = if usable { (currency, settings.exchange_rate) } else { ("USD".to_string(), 1.0) };
+        Self {
+            decimal_comma,
+            compact: settings.compact_numbers,
+            symbol: currency_symbol(&currency),
+            currency,
+            rate,
+        }
+    }
+
+    /// USD amount in the display currency
+    pub(crate) fn convert(&self, usd: f64) -> f64 {
+        usd * self.rate
+    }
+
+    /// A plain number with the locale's separators, compacted from 1000 on when enabled
+    pub(crate) fn number(&self, value: f64, decimals: usize) -> String {
+        if self.compact && value.abs() >= 1000.0 {
+            let (scaled, suffix) = if value.abs() >= 999_500.0 { (value / 1e6, "M") } else { (value / 1e3, "k") };
+            let decimals = if scaled.abs() >= 100.0 { 0 } else { 1 };
+            let text = format!("{:.*}", decimals, scaled);
+            return format!("{}{}", self.localize(text.strip_suffix(".0").unwrap_or(&text)), suffix);
+        }
+        self.localize(&format!("{:.*}", decimals, value))
+    }
+
+    /// A USD amount converted and printed with the currency symbol
+    /// ("$12.50", "€12.50", "CHF 12.50"; "12,50 €" with decimal commas)
+    pub(crate) fn money(&self, usd: f64, decimals: usize) -> String {
+        let amount = self.number(self.convert(usd), decimals);
+        let (sign, digits) = match amount.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", amount.as_str()),
+        };
+        if self.decimal_comma {
+            format!("{}{} {}", sign, digits, self.symbol)
+        } else if self.symbol.chars().count() == 1 {
+            format!("{}{}{}", sign, self.symbol, digits)
+        } else {
+            format!("{}{} {}", sign, self.symbol, digits)
+        }
+    }
+
+    /// Swap in the locale's separators and group thousands of "1234.5"-style text
+    fn localize(&self, text: &str) -> String {
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text),
+        };
+        let (int, frac) = match digits.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (digits, None),
+        };
+        let (group, point) = if self.decimal_comma { ('.', ',') } else { (',', '.') };
+        let mut out = sign.to_string();
+        for (i, c) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                out.push(group);
+            }
+            out.push(c);
+        }
+        if let Some(frac) = frac {
+            out.push(point);
+            out.push_str(frac);
+        }
+        out
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// The resolved format (locale "auto" decided) for the window to mirror
+#[tauri::command]
+pub fn get_number_format() -> NumberFormat {
+    NumberFormat::from_settings(&read_settings().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(locale: &str, currency: &str, rate: f64, compact: bool) -> AppSettings {
+        AppSettings {
+            number_locale: locale.to_string(),
+            display_currency: currency.to_string(),
+            exchange_rate: rate,
+            compact_numbers: compact,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn formats_by_locale_and_currency() {
+        let en = NumberFormat::from_settings(&settings("en", "USD", 1.0, false));
+        assert_eq!(en.money(1234.5, 2), "$1,234.50");
+        assert_eq!(en.money(-5.0, 2), "-$5.00");
+        assert_eq!(en.number(59.87, 1), "59.9");
+
+        let de = NumberFormat::from_settings(&settings("de", "eur", 0.9, false));
+        assert_eq!(de.money(1234.5, 2), "1.111,05 €");
+        assert_eq!(de.number(59.87, 2), "59,87");
+
+        let chf = NumberFormat::from_settings(&settings("en", "CHF", 0.8, false));
+        assert_eq!(chf.money(10.0, 2), "CHF 8.00");
+    }
+
+    #[test]
+    fn compacts_large_values() {
+        let en = NumberFormat::from_settings(&settings("en", "USD", 1.0, true));
+        assert_eq!(en.number(999.0, 0), "999");
+        assert_eq!(en.number(1234.0, 2), "1.2k");
+        assert_eq!(en.number(12_500.0, 0), "12.5k");
+        assert_eq!(en.number(12_000.0, 0), "12k");
+        assert_eq!(en.number(250_000.0, 0), "250k");
+        assert_eq!(en.number(999_800.0, 0), "1M");
+        assert_eq!(en.money(-3_400_000.0, 2), "-$3.4M");
+
+        let de = NumberFormat::from_settings(&settings("de", "EUR", 1.0, true));
+        assert_eq!(de.money(1234.0, 2), "1,2k €");
+    }
+
+    #[test]
+    fn rejects_unusable_currencies() {
+        assert!(validate(&settings("en", "EUR", 0.0, false)).is_err());
+        assert!(validate(&settings("en", "EURO", 1.0, false)).is_err());
+        assert!(validate(&settings("fr", "USD", 1.0, false)).is_err());
+        assert!(validate(&settings("auto", "USD", 0.0, false)).is_ok());
+        // A hand-edited invalid rate falls back to USD instead of mislabelling amounts
+        assert_eq!(NumberFormat::from_settings(&settings("en", "EUR", -1.0, false)).currency, "USD");
+    }
+}
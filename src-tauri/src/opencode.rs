@@ -0,0 +1,542 @@
+// OpenCode integration
+// Reads and writes the OpenRouter entry in ~/.local/share/opencode/auth.json and
+// keeps it in step with the active BP-ESC key when the user opts in.
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::i18n::tr;
+use crate::{
+    get_config_dir, key_fingerprint, log_message, mask_key, read_settings,
+    save_settings_internal, verify_key_live, write_file_atomic, AppSettings, ApiKeyConfig,
+};
+
+/// Written next to auth.json before every change
+const BACKUP_FILE_NAME: &str = "auth.json.bpesc-backup";
+
+// ============================================================================
+// AUTH FILE PARSING
+// ============================================================================
+
+fn extract_key_from_object(obj: &serde_json::Map<String, Value>) -> Option<String> {
+    let key_fields = ["api_key", "apiKey", "key", "token"];
+    for field in key_fields {
+        if let Some(Value::String(value)) = obj.get(field) {
+            let trimmed = value.trim();
+            if !trimmed.is_empty() {
+                return Some(trimmed.to_string());
+            }
+        }
+    }
+
+    if let Some(Value::Object(creds)) = obj.get("credentials") {
+        if let Some(key) = extract_key_from_object(creds) {
+            return Some(key);
+        }
+    }
+
+    None
+}
+
+pub(crate) fn is_openrouter_entry(obj: &serde_json::Map<String, Value>) -> bool {
+    let keys = ["provider", "name", "id"];
+    keys.iter().any(|field| {
+        obj.get(*field)
+            .and_then(|value| value.as_str())
+            .map(|value| value.eq_ignore_ascii_case("openrouter"))
+            .unwrap_or(false)
+    })
+}
+
+fn extract_key_from_provider(value: &Value) -> Option<String> {
+    if let Value::Object(obj) = value {
+        if let Some(key) = extract_key_from_object(obj) {
+            return Some(key);
+        }
+        if let Some(Value::Object(auth)) = obj.get("auth") {
+            if let Some(key) = extract_key_from_object(auth) {
+                return Some(key);
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn extract_openrouter_key_from_value(value: &Value) -> Option<String> {
+    if let Value::Object(obj) = value {
+        if let Some(Value::Object(providers)) = obj.get("providers") {
+            if let Some(provider) = providers.get("openrouter") {
+                if let Some(key) = extract_key_from_provider(provider) {
+                    return Some(key);
+                }
+            }
+        }
+
+        if let Some(provider) = obj.get("openrouter") {
+            if let Some(key) = extract_key_from_provider(provider) {
+                return Some(key);
+            }
+        }
+
+        if let Some(Value::Array(providers)) = obj.get("providers") {
+            for provider in providers {
+                if let Value::Object(entry) = provider {
+                    if is_openrouter_entry(entry) {
+                        if let Some(key) = extract_key_from_object(entry) {
+                            return Some(key);
+                        }
+                        if let Some(key) = extract_key_from_provider(provider) {
+                            return Some(key);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        for item in items {
+            if let Value::Object(obj) = item {
+                if is_openrouter_entry(obj) {
+                    if let Some(key) = extract_key_from_object(obj) {
+                        return Some(key);
+                    }
+                    if let Some(key) = extract_key_from_provider(item) {
+                        return Some(key);
+                    }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[tauri::command]
+pub fn read_opencode_openrouter_key() -> Result<String, String> {
+    let auth_path = get_opencode_auth_path()?;
+
+    let contents = fs::read_to_string(&auth_path)
+        .map_err(|e| tr!("opencode.read", e))?;
+    let value: Value = serde_json::from_str(&contents)
+        .map_err(|e| tr!("opencode.parse", e))?;
+
+    extract_openrouter_key_from_value(&value)
+        .ok_or_else(|| tr!("opencode.no_key"))
+}
+
+// ============================================================================
+// AUTH FILE WRITING
+// ============================================================================
+
+/// Get the OpenCode auth file path: ~/.local/share/opencode/auth.json
+pub(crate) fn get_opencode_auth_path() -> Result<PathBuf, String> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| tr!("file.no_home"))?;
+    Ok(opencode_auth_path_in(&home))
+}
+
+pub(crate) fn opencode_auth_path_in(home: &Path) -> PathBuf {
+    home.join(".local")
+        .join("share")
+        .join("opencode")
+        .join("auth.json")
+}
+
+fn replace_key_in_object(obj: &mut serde_json::Map<String, Value>, new_key: &str) -> bool {
+    let key_fields = ["api_key", "apiKey", "key", "token"];
+    for field in key_fields {
+        if let Some(Value::String(value)) = obj.get_mut(field) {
+            if !value.trim().is_empty() {
+                *value = new_key.to_string();
+                return true;
+            }
+        }
+    }
+
+    if let Some(Value::Object(creds)) = obj.get_mut("credentials") {
+        return replace_key_in_object(creds, new_key);
+    }
+
+    false
+}
+
+fn replace_key_in_provider(value: &mut Value, new_key: &str) -> bool {
+    if let Value::Object(obj) = value {
+        if replace_key_in_object(obj, new_key) {
+            return true;
+        }
+        if let Some(Value::Object(auth)) = obj.get_mut("auth") {
+            return replace_key_in_object(auth, new_key);
+        }
+    }
+    false
+}
+
+/// Mirror of extract_openrouter_key_from_value: replaces the key in the first
+/// location the extractor would read it from. Returns false if there is none.
+fn replace_openrouter_key_in_value(value: &mut Value, new_key: &str) -> bool {
+    if let Value::Object(obj) = value {
+        if let Some(Value::Object(providers)) = obj.get_mut("providers") {
+            if let Some(provider) = providers.get_mut("openrouter") {
+                if replace_key_in_provider(provider, new_key) {
+                    return true;
+                }
+            }
+        }
+
+        if let Some(provider) = obj.get_mut("openrouter") {
+            if replace_key_in_provider(provider, new_key) {
+                return true;
+            }
+        }
+
+        if let Some(Value::Array(providers)) = obj.get_mut("providers") {
+            for provider in providers.iter_mut() {
+                let is_openrouter = matches!(&*provider, Value::Object(entry) if is_openrouter_entry(entry));
+                if is_openrouter && replace_key_in_provider(provider, new_key) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        for item in items.iter_mut() {
+            let is_openrouter = matches!(&*item, Value::Object(entry) if is_openrouter_entry(entry));
+            if is_openrouter && replace_key_in_provider(item, new_key) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+/// Replace the OpenRouter key in a parsed auth file, or add an `openrouter` API entry
+/// next to the other providers if there is none yet.
+fn set_openrouter_key_in_value(value: &mut Value, new_key: &str) -> Result<(), String> {
+    if replace_openrouter_key_in_value(value, new_key) {
+        return Ok(());
+    }
+    match value {
+        Value::Object(obj) => {
+            obj.insert(
+                "openrouter".to_string(),
+                serde_json::json!({ "type": "api", "key": new_key }),
+            );
+            Ok(())
+        }
+        _ => Err(tr!("opencode.layout")),
+    }
+}
+
+/// Write the OpenRouter key into OpenCode's auth file, leaving other providers untouched.
+/// Creates an `openrouter` API entry if the file has none yet. The previous file is
+/// kept as `auth.json.bpesc-backup` so a bad write can always be undone by hand.
+fn write_opencode_openrouter_key(new_key: &str) -> Result<(), String> {
+    let auth_path = get_opencode_auth_path()?;
+
+    let mut value: Value = if auth_path.exists() {
+        let contents = fs::read_to_string(&auth_path)
+            .map_err(|e| tr!("opencode.read", e))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| tr!("opencode.parse", e))?
+    } else {
+        Value::Object(serde_json::Map::new())
+    };
+
+    set_openrouter_key_in_value(&mut value, new_key)?;
+
+    if auth_path.exists() {
+        let backup_path = auth_path.with_file_name(BACKUP_FILE_NAME);
+        fs::copy(&auth_path, &backup_path)
+            .map_err(|e| tr!("opencode.backup", e))?;
+        fs::set_permissions(&backup_path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| tr!("opencode.backup_permissions", e))?;
+    } else if let Some(parent) = auth_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| tr!("opencode.create_dir", e))?;
+    }
+    let contents = serde_json::to_string_pretty(&value)
+        .map_err(|e| tr!("opencode.serialize", e))?;
+    // Atomic, and 0600 from the start: OpenCode never sees a half-written
+    // file and the keys are never readable by other users in between
+    write_file_atomic(&auth_path, &contents)
+        .map_err(|e| tr!("opencode.write", e))
+}
+
+// ============================================================================
+// SYNC STATE
+// ============================================================================
+
+/// Sync bookkeeping, stored apart from settings.json so a stale settings save
+/// from the frontend can never roll it back.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+struct OpenCodeSyncState {
+    /// Fingerprint of the OpenCode key we last wrote or acknowledged
+    #[serde(default)]
+    last_seen_fingerprint: Option<String>,
+    /// Fingerprint we already emitted a change event for (avoids repeating it every tick)
+    #[serde(default)]
+    last_notified_fingerprint: Option<String>,
+}
+
+fn get_sync_state_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("opencode-sync.json"))
+}
+
+fn read_sync_state() -> OpenCodeSyncState {
+    get_sync_state_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_sync_state(state: &OpenCodeSyncState) -> Result<(), String> {
+    let config_dir = get_config_dir()?;
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| tr!("file.create_dir", config_dir.display(), e))?;
+    let path = get_sync_state_path()?;
+    let contents = serde_json::to_string_pretty(state)
+        .map_err(|e| tr!("file.write", path.display(), e))?;
+    write_file_atomic(&path, &contents)
+        .map_err(|e| tr!("file.write", path.display(), e))
+}
+
+fn mark_key_seen(key: &str) -> Result<(), String> {
+    let mut state = read_sync_state();
+    let fingerprint = key_fingerprint(key);
+    state.last_seen_fingerprint = Some(fingerprint.clone());
+    state.last_notified_fingerprint = Some(fingerprint);
+    write_sync_state(&state)
+}
+
+fn active_key(settings: &AppSettings) -> Option<&ApiKeyConfig> {
+    settings.api_keys.get(settings.active_api_key_index)
+}
+
+// ============================================================================
+// SYNC
+// ============================================================================
+
+/// Write a key into OpenCode unless it is already there. Returns true if the file changed.
+pub(crate) fn push_key(key: &str) -> Result<bool, String> {
+    if read_opencode_openrouter_key().ok().as_deref() == Some(key) {
+        mark_key_seen(key)?;
+        return Ok(false);
+    }
+    write_opencode_openrouter_key(key)?;
+    mark_key_seen(key)?;
+    Ok(true)
+}
+
+fn push_active_key(settings: &AppSettings) -> Result<bool, String> {
+    let active = active_key(settings).ok_or_else(|| tr!("opencode.no_active_key"))?;
+    push_key(&active.key)
+}
+
+/// Called from save_settings: pushes the key when the active key changed or sync was just enabled
+pub(crate) fn sync_after_settings_change(app: &AppHandle, previous: Option<&AppSettings>, settings: &AppSettings) {
+    if !settings.opencode_sync_enabled {
+        return;
+    }
+    let previous_key = previous
+        .filter(|p| p.opencode_sync_enabled)
+        .and_then(active_key)
+        .map(|k| k.key.clone());
+    let current_key = active_key(settings).map(|k| k.key.clone());
+    if current_key.is_none() || previous_key == current_key {
+        return;
+    }
+
+    match push_active_key(settings) {
+        Ok(true) => {
+            let _ = log_message(app.clone(), "[INFO] Synced active key to OpenCode auth file".to_string());
+        }
+        Ok(false) => {}
+        Err(e) => {
+            let _ = log_message(app.clone(), format!("[ERROR] OpenCode sync failed: {}", e));
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OpenCodeKeyChange {
+    pub masked_key: String,
+    /// Index in api_keys if BP-ESC already knows this key
+    pub known_index: Option<usize>,
+    pub known_label: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OpenCodeSyncStatus {
+    pub enabled: bool,
+    pub auth_file_exists: bool,
+    pub opencode_key_masked: Option<String>,
+    pub in_sync: bool,
+    /// Set when OpenCode's key changed outside BP-ESC and differs from the active key
+    pub external_change: Option<OpenCodeKeyChange>,
+}
+
+/// Detect a key that was changed in OpenCode since we last wrote or acknowledged it
+fn detect_external_change(settings: &AppSettings, opencode_key: &str) -> Option<OpenCodeKeyChange> {
+    let state = read_sync_state();
+    let fingerprint = key_fingerprint(opencode_key);
+    let is_active = active_key(settings).map(|k| k.key == opencode_key).unwrap_or(false);
+
+    if is_active || state.last_seen_fingerprint.as_deref() == Some(fingerprint.as_str()) {
+        return None;
+    }
+
+    let known_index = settings.api_keys.iter().position(|k| k.key == opencode_key);
+    Some(OpenCodeKeyChange {
+        masked_key: mask_key(opencode_key),
+        known_index,
+        known_label: known_index.map(|i| settings.api_keys[i].label.clone()),
+    })
+}
+
+/// Auto-refresh hook: emits `opencode-key-changed` once per externally changed key
+pub(crate) fn check_external_change(app: &AppHandle) {
+    let Ok(settings) = read_settings() else { return };
+    if !settings.opencode_sync_enabled {
+        return;
+    }
+    let Ok(opencode_key) = read_opencode_openrouter_key() else { return };
+
+    let mut state = read_sync_state();
+    let fingerprint = key_fingerprint(&opencode_key);
+
+    // First run with sync enabled: take the current file as the baseline
+    if state.last_seen_fingerprint.is_none() {
+        state.last_seen_fingerprint = Some(fingerprint.clone());
+        let _ = write_sync_state(&state);
+        return;
+    }
+
+    let Some(change) = detect_external_change(&settings, &opencode_key) else { return };
+    if state.last_notified_fingerprint.as_deref() == Some(fingerprint.as_str()) {
+        return;
+    }
+    state.last_notified_fingerprint = Some(fingerprint);
+    let _ = write_sync_state(&state);
+    let _ = app.emit("opencode-key-changed", &change);
+}
+
+#[tauri::command]
+pub fn get_opencode_sync_status() -> Result<OpenCodeSyncStatus, String> {
+    let settings = read_settings()?;
+    let auth_file_exists = get_opencode_auth_path()?.exists();
+    let opencode_key = read_opencode_openrouter_key().ok();
+    let in_sync = match (&opencode_key, active_key(&settings)) {
+        (Some(opencode_key), Some(active)) => *opencode_key == active.key,
+        _ => false,
+    };
+
+    Ok(OpenCodeSyncStatus {
+        enabled: settings.opencode_sync_enabled,
+        auth_file_exists,
+        opencode_key_masked: opencode_key.as_deref().map(mask_key),
+        in_sync,
+        external_change: opencode_key.as_deref().and_then(|key| detect_external_change(&settings, key)),
+    })
+}
+
+/// Write the active key into OpenCode now (also works with automatic sync turned off)
+#[tauri::command]
+pub fn sync_opencode_key(app: AppHandle) -> Result<bool, String> {
+    let settings = read_settings()?;
+    let written = push_active_key(&settings)?;
+    if written {
+        let _ = log_message(app, "[INFO] Wrote active key to OpenCode auth file".to_string());
+    }
+    Ok(written)
+}
+
+/// Adopt the key OpenCode is using: switch to it if known, otherwise verify and add it
+#[tauri::command]
+pub async fn adopt_opencode_key(app: AppHandle) -> Result<AppSettings, String> {
+    let opencode_key = read_opencode_openrouter_key()?;
+    let mut settings = read_settings()?;
+
+    if let Some(index) = settings.api_keys.iter().position(|k| k.key == opencode_key) {
+        settings.active_api_key_index = index;
+    } else {
+        let verification = verify_key_live(&app, &opencode_key)
+            .await
+            .map_err(|e| tr!("opencode.verification", e))?;
+        let label = verification.server_label
+            .clone()
+            .unwrap_or_else(|| "OpenCode".to_string());
+        let mut cfg = ApiKeyConfig::new(opencode_key.clone(), label);
+        cfg.verified = Some(verification);
+        settings.api_keys.push(cfg);
+        settings.active_api_key_index = settings.api_keys.len() - 1;
+    }
+
+    save_settings_internal(&settings)?;
+    mark_key_seen(&opencode_key)?;
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+/// Keep BP-ESC's key and stop offering the external OpenCode key
+#[tauri::command]
+pub fn dismiss_opencode_key_change() -> Result<(), String> {
+    let opencode_key = read_opencode_openrouter_key()?;
+    mark_key_seen(&opencode_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replaces_the_key_where_the_extractor_reads_it() {
+        let mut value = json!({
+            "anthropic": { "type": "api", "key": "sk-ant-keep" },
+            "openrouter": { "type": "api", "key": "sk-or-v1-old" },
+        });
+        assert!(replace_openrouter_key_in_value(&mut value, "sk-or-v1-new"));
+        assert_eq!(extract_openrouter_key_from_value(&value).as_deref(), Some("sk-or-v1-new"));
+        assert_eq!(value["anthropic"]["key"], "sk-ant-keep");
+
+        let mut nested = json!({ "providers": [
+            { "name": "anthropic", "apiKey": "sk-ant-keep" },
+            { "name": "OpenRouter", "auth": { "token": "sk-or-v1-old" } },
+        ]});
+        assert!(replace_openrouter_key_in_value(&mut nested, "sk-or-v1-new"));
+        assert_eq!(nested["providers"][0]["apiKey"], "sk-ant-keep");
+        assert_eq!(nested["providers"][1]["auth"]["token"], "sk-or-v1-new");
+
+        let mut none = json!({ "anthropic": { "type": "api", "key": "sk-ant-keep" } });
+        assert!(!replace_openrouter_key_in_value(&mut none, "sk-or-v1-new"));
+        assert_eq!(none, json!({ "anthropic": { "type": "api", "key": "sk-ant-keep" } }));
+    }
+
+    #[test]
+    fn inserts_an_entry_and_keeps_other_providers() {
+        let mut value = json!({
+            "anthropic": { "type": "oauth", "refresh": "r", "access": "a" },
+            "openai": { "type": "api", "key": "sk-openai-keep" },
+        });
+        set_openrouter_key_in_value(&mut value, "sk-or-v1-new").unwrap();
+        assert_eq!(value, json!({
+            "anthropic": { "type": "oauth", "refresh": "r", "access": "a" },
+            "openai": { "type": "api", "key": "sk-openai-keep" },
+            "openrouter": { "type": "api", "key": "sk-or-v1-new" },
+        }));
+
+        let mut list = json!([{ "name": "anthropic", "key": "sk-ant-keep" }]);
+        assert!(set_openrouter_key_in_value(&mut list, "sk-or-v1-new").is_err());
+        assert_eq!(list, json!([{ "name": "anthropic", "key": "sk-ant-keep" }]));
+    }
+}
@@ -0,0 +1,333 @@
+// Pinned keys
+// Any key can be pinned to a menubar icon of its own next to the main one, with its own
+// timeframe and %/$ view (e.g. a personal and a CI budget side by side). The window only
+// fetches the active key; pinned keys are fetched here on each auto-refresh tick.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItemBuilder, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::i18n::tr;
+use crate::number_format::NumberFormat;
+use crate::{
+    cached_icon_scales, fetch_balance, menubar_icon, opencode, read_settings, render_tray_icon, save_settings_internal, set_tray_icon,
+    toggle_window, tray_status, write_log, ApiKeyConfig, AppSettings, BalanceData, MenubarState,
+};
+
+const TRAY_PREFIX: &str = "pinned:";
+const MENU_PREFIX: &str = "pin:";
+
+/// How a pinned icon shows its key; everything else follows the main menubar settings
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TrayPin {
+    #[serde(default = "crate::default_menubar_timeframe")]
+    pub timeframe: String,          // "monthly" | "weekly" | "daily"
+    #[serde(default = "crate::default_true")]
+    pub show_percentage: bool,
+    #[serde(default = "crate::default_true")]
+    pub show_remaining: bool,
+}
+
+/// Last balance of each pinned key (by key id) and the pinned icons currently shown
+#[derive(Default)]
+pub(crate) struct PinnedTrayState {
+    balances: Mutex<HashMap<String, (BalanceData, DateTime<Local>)>>,
+    shown: Mutex<BTreeSet<String>>,
+    /// Hash of the image each icon shows; every main icon update redraws the pinned ones
+    icons: Mutex<HashMap<String, u64>>,
+}
+
+impl PinnedTrayState {
+    /// False when the icon already shows this image (setting it again wakes the main thread)
+    fn icon_changed(&self, key_id: &str, icons: &[Image]) -> bool {
+        let mut hasher = DefaultHasher::new();
+        for icon in icons {
+            (icon.width(), icon.height(), icon.rgba()).hash(&mut hasher);
+        }
+        let hash = hasher.finish();
+        match self.icons.lock() {
+            Ok(mut icons) => icons.insert(key_id.to_string(), hash) != Some(hash),
+            Err(_) => true,
+        }
+    }
+}
+
+fn tray_id(key_id: &str) -> String {
+    format!("{}{}", TRAY_PREFIX, key_id)
+}
+
+fn pinned_keys(settings: &AppSettings) -> impl Iterator<Item = (usize, &ApiKeyConfig)> {
+    settings
+        .api_keys
+        .iter()
+        .enumerate()
+        .filter(|(_, key)| key.pin.is_some() && !key.id.is_empty())
+}
+
+pub(crate) fn validate(pin: &TrayPin) -> Result<(), String> {
+    match pin.timeframe.as_str() {
+        "daily" | "weekly" | "monthly" => Ok(()),
+        other => Err(tr!("key.unknown_timeframe", other)),
+    }
+}
+
+/// The settings the main icon would use with this key active and shown the pin's way
+pub(crate) fn view_settings(settings: &AppSettings, index: usize) -> Option<AppSettings> {
+    let pin = settings.api_keys.get(index)?.pin.as_ref()?;
+    Some(AppSettings {
+        active_api_key_index: index,
+        menubar_timeframe: pin.timeframe.clone(),
+        show_percentage: pin.show_percentage,
+        show_remaining: pin.show_remaining,
+        ..settings.clone()
+    })
+}
+
+fn tray_menu(app: &AppHandle, key: &ApiKeyConfig) -> tauri::Result<Menu<Wry>> {
+    let timeframe = key.pin.as_ref().map(|p| p.timeframe.as_str()).unwrap_or("monthly");
+    let item_id = |action: &str| format!("{}{}:{}", MENU_PREFIX, action, key.id);
+
+    let title = MenuItemBuilder::with_id(item_id("title"), &key.label).enabled(false).build(app)?;
+    let sep1 = PredefinedMenuItem::separator(app)?;
+    let daily = CheckMenuItem::with_id(app, item_id("daily"), tr!("tray.daily"), true, timeframe == "daily", None::<&str>)?;
+    let weekly = CheckMenuItem::with_id(app, item_id("weekly"), tr!("tray.weekly"), true, timeframe == "weekly", None::<&str>)?;
+    let monthly = CheckMenuItem::with_id(app, item_id("monthly"), tr!("tray.monthly"), true, timeframe == "monthly", None::<&str>)?;
+    let sep2 = PredefinedMenuItem::separator(app)?;
+    let refresh = MenuItemBuilder::with_id(item_id("refresh"), tr!("tray.pin_refresh")).build(app)?;
+    let activate = MenuItemBuilder::with_id(item_id("activate"), tr!("tray.pin_activate")).build(app)?;
+    let unpin = MenuItemBuilder::with_id(item_id("unpin"), tr!("tray.unpin")).build(app)?;
+
+    Menu::with_items(app, &[&title, &sep1, &daily, &weekly, &monthly, &sep2, &refresh, &activate, &unpin])
+}
+
+/// Create, update and remove pinned icons to match the settings (main thread)
+pub(crate) fn sync(app: &AppHandle) {
+    let Some(state) = app.try_state::<PinnedTrayState>() else { return };
+    let Ok(settings) = read_settings() else { return };
+    let wanted: BTreeSet<String> = pinned_keys(&settings).map(|(_, key)| key.id.clone()).collect();
+
+    let Ok(mut shown) = state.shown.lock() else { return };
+    for key_id in shown.difference(&wanted) {
+        app.remove_tray_by_id(tray_id(key_id).as_str());
+        tray_status::forget(&tray_id(key_id));
+    }
+    if let Ok(mut balances) = state.balances.lock() {
+        balances.retain(|key_id, _| wanted.contains(key_id));
+    }
+    if let Ok(mut icons) = state.icons.lock() {
+        icons.retain(|key_id, _| wanted.contains(key_id));
+    }
+
+    for (_, key) in pinned_keys(&settings) {
+        let id = tray_id(&key.id);
+        let menu = match tray_menu(app, key) {
+            Ok(menu) => menu,
+            Err(e) => {
+                let _ = write_log(&format!("[Pinned] {}: {}", key.label, e));
+                continue;
+            }
+        };
+        if let Some(tray) = app.tray_by_id(id.as_str()) {
+            let _ = tray.set_menu(Some(menu));
+            continue;
+        }
+        let built = TrayIconBuilder::with_id(id)
+            .icon(Image::from_bytes(include_bytes!("../icons/32x32.png")).expect("bundled icon"))
+            .menu(&menu)
+            .show_menu_on_left_click(false)
+            .on_tray_icon_event(|tray, event| {
+                if let TrayIconEvent::Click {
+                    button: MouseButton::Left,
+                    button_state: MouseButtonState::Up,
+                    ..
+                } = event
+                {
+                    toggle_window(tray.app_handle());
+                }
+            })
+            .build(app);
+        if let Err(e) = built {
+            let _ = write_log(&format!("[Pinned] {}: {}", key.label, e));
+        }
+    }
+    *shown = wanted;
+    drop(shown);
+    redraw_all(app);
+}
+
+/// Sync pinned icons from any thread (tray icons and menus live on the main thread)
+pub(crate) fn sync_later(app: &AppHandle) {
+    let handle = app.clone();
+    let _ = app.run_on_main_thread(move || sync(&handle));
+}
+
+/// Redraw every pinned icon and tooltip from its last balance
+pub(crate) fn redraw_all(app: &AppHandle) {
+    let Some(state) = app.try_state::<PinnedTrayState>() else { return };
+    let Ok(settings) = read_settings() else { return };
+    let is_dark = app
+        .try_state::<MenubarState>()
+        .and_then(|s| s.is_dark.lock().ok().map(|d| *d))
+        .unwrap_or(false);
+    let balances = state.balances.lock().map(|b| b.clone()).unwrap_or_default();
+    let days_in_month = menubar_icon::days_in_month(Local::now().date_naive());
+    let scales = cached_icon_scales(app);
+
+    for (index, key) in pinned_keys(&settings) {
+        let (Some(view), Some(tray)) = (view_settings(&settings, index), app.tray_by_id(tray_id(&key.id).as_str())) else {
+            continue;
+        };
+        let (balance, refreshed_at) = match balances.get(&key.id) {
+            Some((balance, at)) => (balance.clone(), Some(*at)),
+            None => (BalanceData::default(), None),
+        };
+        let shown = render_tray_icon(&balance, &view, is_dark, &scales).and_then(|icons| {
+            if state.icon_changed(&key.id, &icons) {
+                set_tray_icon(&tray, icons)
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = shown {
+            let _ = write_log(&format!("[Pinned] {}: {}", key.label, e));
+        }
+        let format = NumberFormat::from_settings(&view);
+        let text = tray_status::describe(&balance, &view, &format, days_in_month, refreshed_at, false);
+        tray_status::apply(&tray, text);
+    }
+}
+
+async fn fetch_pinned(app: &AppHandle, only_missing: bool) {
+    let Some(state) = app.try_state::<PinnedTrayState>() else { return };
+    let Ok(settings) = read_settings() else { return };
+
+    for (_, key) in pinned_keys(&settings) {
+        let known = state.balances.lock().map(|b| b.contains_key(&key.id)).unwrap_or(false);
+        if only_missing && known {
+            continue;
+        }
+        match fetch_balance(app.clone(), key.key.clone()).await {
+            Ok(balance) => {
+                if let Ok(mut balances) = state.balances.lock() {
+                    balances.insert(key.id.clone(), (balance, Local::now()));
+                }
+            }
+            Err(e) => {
+                let _ = write_log(&format!("[Pinned] {}: {}", key.label, e));
+            }
+        }
+    }
+    redraw_all(app);
+}
+
+/// Fetch every pinned key (auto-refresh tick)
+pub(crate) async fn refresh_all(app: &AppHandle) {
+    fetch_pinned(app, false).await;
+}
+
+/// Fetch pinned keys that have no balance yet (app start, newly pinned)
+pub(crate) async fn refresh_new(app: &AppHandle) {
+    fetch_pinned(app, true).await;
+}
+
+/// Pin a key to its own menubar icon, or unpin it with None
+#[tauri::command]
+pub async fn set_key_pin(app: AppHandle, index: usize, pin: Option<TrayPin>) -> Result<AppSettings, String> {
+    if let Some(pin) = &pin {
+        validate(pin)?;
+    }
+    let mut settings = read_settings()?;
+    let cfg = settings.api_keys.get_mut(index)
+        .ok_or_else(|| tr!("key.not_found", index + 1))?;
+    cfg.pin = pin;
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    sync_later(&app);
+    refresh_new(&app).await;
+    Ok(settings)
+}
+
+fn apply_menu_action(app: &AppHandle, action: &str, key_id: &str) -> Result<(), String> {
+    let mut settings = read_settings()?;
+    let Some(index) = settings.api_keys.iter().position(|k| k.id == key_id) else { return Ok(()) };
+
+    match action {
+        "daily" | "weekly" | "monthly" => {
+            if let Some(pin) = settings.api_keys[index].pin.as_mut() {
+                pin.timeframe = action.to_string();
+            }
+        }
+        "unpin" => settings.api_keys[index].pin = None,
+        "activate" => {
+            let previous = settings.clone();
+            settings.active_api_key_index = index;
+            save_settings_internal(&settings)?;
+            opencode::sync_after_settings_change(app, Some(&previous), &settings);
+            // The window adopts the settings and fetches the new active key
+            let _ = app.emit("settings-updated", &settings);
+            return Ok(());
+        }
+        "refresh" => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move { refresh_all(&handle).await });
+            return Ok(());
+        }
+        _ => return Ok(()),
+    }
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    sync(app);
+    Ok(())
+}
+
+/// Handle a click in a pinned icon's menu (menu events reach every tray's handler,
+/// so they are dispatched from the main tray's)
+pub(crate) fn handle_menu_event(app: &AppHandle, id: &str) {
+    let Some((action, key_id)) = id.strip_prefix(MENU_PREFIX).and_then(|rest| rest.split_once(':')) else { return };
+    if let Err(e) = apply_menu_action(app, action, key_id) {
+        let _ = write_log(&format!("[Pinned] {}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> AppSettings {
+        let mut work = ApiKeyConfig::new("sk-or-v1-work".to_string(), "Work".to_string());
+        let mut ci = ApiKeyConfig::new("sk-or-v1-ci".to_string(), "CI".to_string());
+        work.id = "key-work".to_string();
+        ci.id = "key-ci".to_string();
+        ci.pin = Some(TrayPin {
+            timeframe: "daily".to_string(),
+            show_percentage: false,
+            show_remaining: false,
+        });
+        AppSettings {
+            api_keys: vec![work, ci],
+            icon_style: "ring".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn view_uses_the_pin_and_keeps_the_rest() {
+        let settings = settings();
+        assert!(view_settings(&settings, 0).is_none());
+
+        let view = view_settings(&settings, 1).unwrap();
+        assert_eq!(view.active_api_key_index, 1);
+        assert_eq!(view.menubar_timeframe, "daily");
+        assert!(!view.show_percentage && !view.show_remaining);
+        assert_eq!(view.icon_style, "ring");
+        assert!(validate(&TrayPin { timeframe: "yearly".to_string(), ..settings.api_keys[1].pin.clone().unwrap() }).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+// Model pricing and job cost estimates
+// Prices come from OpenRouter's public /api/v1/models list, cached locally, with a
+// bundled snapshot for offline use. Estimates are compared against the active key's
+// remaining budget and month pace.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::i18n::tr;
+use crate::number_format::NumberFormat;
+use crate::{fetch_balance, get_config_dir, pace_status_from_delta, read_settings, write_file_atomic, BalanceData};
+
+const PRICING_CACHE_FILE: &str = "pricing-cache.json";
+/// Prices change rarely; refresh once a day
+const PRICING_CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+/// Snapshot of /api/v1/models used when neither the network nor a cache is available
+const BUNDLED_PRICING: &str = include_str!("../data/model-pricing.json");
+
+/// Prices in USD per token (prompt/completion) and per request
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelPrice {
+    pub id: String,
+    pub name: String,
+    pub prompt: f64,
+    pub completion: f64,
+    pub request: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    #[serde(default)]
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    pricing: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct PricingCache {
+    fetched_at: i64, // unix seconds
+    models: Vec<ModelPrice>,
+}
+
+/// Where the prices of an estimate came from
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    Live,
+    Cache,
+    StaleCache,
+    Bundled,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CostEstimate {
+    pub model: String,
+    pub model_name: String,
+    pub price_source: PriceSource,
+    pub prices_fetched_at: Option<i64>,
+    pub requests: u64,
+    pub prompt_tokens: u64,     // per request
+    pub completion_tokens: u64, // per request
+    pub cost_per_request: f64,
+    pub total_cost: f64,
+    pub remaining_monthly: Option<f64>,
+    pub remaining_after: Option<f64>,
+    /// false when the job costs more than the remaining budget
+    pub covered: Option<bool>,
+    pub usage_monthly: Option<f64>,
+    pub pace_month_target: Option<f64>,
+    pub pace_delta_before: Option<f64>,
+    pub pace_delta_after: Option<f64>,
+    pub pace_status_after: Option<String>,
+    pub summary: String,
+}
+
+// ============================================================================
+// PRICE LIST
+// ============================================================================
+
+fn cache_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(PRICING_CACHE_FILE))
+}
+
+fn read_cache() -> Option<PricingCache> {
+    let contents = fs::read_to_string(cache_path().ok()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_cache(cache: &PricingCache) -> Result<(), String> {
+    let path = cache_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| tr!("file.create_dir", dir.display(), e))?;
+    }
+    let contents = serde_json::to_string(cache)
+        .map_err(|e| tr!("file.write", path.display(), e))?;
+    write_file_atomic(&path, &contents)
+        .map_err(|e| tr!("file.write", path.display(), e))
+}
+
+/// Prices are decimal strings; "-1" marks variable-price routers, which can't be estimated
+fn price_field(pricing: &Value, field: &str) -> Option<f64> {
+    let price = match pricing.get(field)? {
+        Value::String(s) => s.parse::<f64>().ok()?,
+        Value::Number(n) => n.as_f64()?,
+        _ => return None,
+    };
+    (price >= 0.0).then_some(price)
+}
+
+fn parse_models(body: &str) -> Result<Vec<ModelPrice>, String> {
+    let response: ModelsResponse = serde_json::from_str(body)
+        .map_err(|e| tr!("pricing.models_parse", e))?;
+    Ok(response
+        .data
+        .into_iter()
+        .filter_map(|entry| {
+            let pricing = entry.pricing?;
+            Some(ModelPrice {
+                prompt: price_field(&pricing, "prompt")?,
+                completion: price_field(&pricing, "completion")?,
+                request: price_field(&pricing, "request").unwrap_or(0.0),
+                name: if entry.name.is_empty() { entry.id.clone() } else { entry.name },
+                id: entry.id,
+            })
+        })
+        .collect())
+}
+
+async fn fetch_prices_remote() -> Result<Vec<ModelPrice>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|_| tr!("net.client"))?;
+
+    let response = client
+        .get("https://openrouter.ai/api/v1/models")
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                tr!("net.timeout")
+            } else {
+                tr!("net.error", e)
+            }
+        })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(tr!("pricing.models_status", status));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| tr!("net.body", e))?;
+    parse_models(&body)
+}
+
+/// Price list from the cache, the network, a stale cache or the bundled snapshot (in that order)
+pub(crate) async fn load_prices(force_refresh: bool) -> (Vec<ModelPrice>, PriceSource, Option<i64>) {
+    let now = Utc::now().timestamp();
+    let cache = read_cache().filter(|c| !c.models.is_empty());
+
+    if let Some(cache) = &cache {
+        if !force_refresh && now - cache.fetched_at < PRICING_CACHE_TTL_SECS {
+            return (cache.models.clone(), PriceSource::Cache, Some(cache.fetched_at));
+        }
+    }
+
+    match fetch_prices_remote().await {
+        Ok(models) if !models.is_empty() => {
+            let fresh = PricingCache { fetched_at: now, models };
+            if let Err(e) = write_cache(&fresh) {
+                eprintln!("{}", e);
+            }
+            (fresh.models, PriceSource::Live, Some(now))
+        }
+        result => {
+            if let Err(e) = result {
+                eprintln!("Model prices unavailable, using fallback: {}", e);
+            }
+            match cache {
+                Some(cache) => (cache.models, PriceSource::StaleCache, Some(cache.fetched_at)),
+                None => (
+                    parse_models(BUNDLED_PRICING).unwrap_or_default(),
+                    PriceSource::Bundled,
+                    None,
+                ),
+            }
+        }
+    }
+}
+
+/// Exact id, case-insensitive id, or a unique id without the vendor prefix ("gpt-4o")
+pub(crate) fn find_model<'a>(models: &'a [ModelPrice], query: &str) -> Result<&'a ModelPrice, String> {
+    let query = query.trim();
+    if let Some(model) = models
+        .iter()
+        .find(|m| m.id == query)
+        .or_else(|| models.iter().find(|m| m.id.eq_ignore_ascii_case(query)))
+    {
+        return Ok(model);
+    }
+
+    let short: Vec<&ModelPrice> = models
+        .iter()
+        .filter(|m| m.id.rsplit('/').next().is_some_and(|s| s.eq_ignore_ascii_case(query)))
+        .collect();
+    if let [model] = short.as_slice() {
+        return Ok(model);
+    }
+
+    let needle = query.to_lowercase();
+    let similar: Vec<&str> = models
+        .iter()
+        .filter(|m| m.id.to_lowercase().contains(&needle))
+        .map(|m| m.id.as_str())
+        .take(5)
+        .collect();
+    if similar.is_empty() {
+        Err(tr!("pricing.unknown_model", query))
+    } else {
+        Err(tr!("pricing.unknown_model_similar", query, similar.join(", ")))
+    }
+}
+
+// ============================================================================
+// ESTIMATE
+// ============================================================================
+
+/// Cost of `requests` calls to `price` without any budget comparison
+fn price_job(price: &ModelPrice, prompt_tokens: u64, completion_tokens: u64, requests: u64) -> CostEstimate {
+    let cost_per_request = price.prompt * prompt_tokens as f64
+        + price.completion * completion_tokens as f64
+        + price.request;
+
+    CostEstimate {
+        model: price.id.clone(),
+        model_name: price.name.clone(),
+        price_source: PriceSource::Bundled,
+        prices_fetched_at: None,
+        requests,
+        prompt_tokens,
+        completion_tokens,
+        cost_per_request,
+        total_cost: cost_per_request * requests as f64,
+        remaining_monthly: None,
+        remaining_after: None,
+        covered: None,
+        usage_monthly: None,
+        pace_month_target: None,
+        pace_delta_before: None,
+        pace_delta_after: None,
+        pace_status_after: None,
+        summary: String::new(),
+    }
+}
+
+/// Where the job leaves the budget of the reset period and its pace (same delta as
+/// `pace_month_delta_percent`)
+fn compare_budget(estimate: &mut CostEstimate, balance: &BalanceData, pace_warn_threshold: f64) {
+    let total_cost = estimate.total_cost;
+    let target = balance.pace_month_target.filter(|t| *t > 0.0);
+    let delta = |usage: f64| target.map(|t| ((usage - t) / t) * 100.0);
+
+    estimate.remaining_monthly = balance.budget_remaining();
+    estimate.remaining_after = estimate.remaining_monthly.map(|r| r - total_cost);
+    estimate.covered = estimate.remaining_monthly.map(|r| total_cost <= r);
+    estimate.usage_monthly = balance.budget_usage();
+    estimate.pace_month_target = balance.pace_month_target;
+    estimate.pace_delta_before = estimate.usage_monthly.and_then(delta);
+    estimate.pace_delta_after = estimate.usage_monthly.and_then(|usage| delta(usage + total_cost));
+    estimate.pace_status_after = estimate
+        .pace_delta_after
+        .map(|d| pace_status_from_delta(d, pace_warn_threshold.max(0.0)).to_string());
+}
+
+/// One-line answer for the UI and CLI
+fn summarize(estimate: &CostEstimate, format: &NumberFormat) -> String {
+    let money = |usd: f64| format.money(usd, 2);
+    let mut summary = tr!("pricing.costs", money(estimate.total_cost));
+    match (estimate.remaining_monthly, estimate.covered) {
+        (Some(remaining), Some(false)) => summary.push_str(&tr!(
+            "pricing.exceeds",
+            money(estimate.total_cost - remaining),
+            money(remaining)
+        )),
+        (Some(remaining), _) => summary.push_str(&tr!(
+            "pricing.remains",
+            money(remaining - estimate.total_cost),
+            money(remaining)
+        )),
+        _ => {}
+    }
+    if let Some(delta) = estimate.pace_delta_after {
+        let pace = if delta.abs() < 0.5 {
+            tr!("pace.on")
+        } else if delta > 0.0 {
+            tr!("pace.ahead", format!("{:.0}", delta))
+        } else {
+            tr!("pace.behind", format!("{:.0}", -delta))
+        };
+        summary.push_str(&tr!("pricing.after", pace));
+    }
+    summary
+}
+
+/// Estimate a job, compared against `balance` when one is given
+pub(crate) async fn estimate_job(
+    model: &str,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    requests: u64,
+    balance: Option<&BalanceData>,
+    force_refresh: bool,
+) -> Result<CostEstimate, String> {
+    if requests == 0 {
+        return Err(tr!("pricing.requests"));
+    }
+    let (models, price_source, prices_fetched_at) = load_prices(force_refresh).await;
+    let price = find_model(&models, model)?;
+
+    let mut estimate = price_job(price, prompt_tokens, completion_tokens, requests);
+    estimate.price_source = price_source;
+    estimate.prices_fetched_at = prices_fetched_at;
+    let settings = read_settings().unwrap_or_default();
+    if let Some(balance) = balance {
+        compare_budget(&mut estimate, balance, settings.pace_warn_threshold);
+    }
+    estimate.summary = summarize(&estimate, &NumberFormat::from_settings(&settings));
+    Ok(estimate)
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Estimate a planned job against the active key's remaining budget and pace
+#[tauri::command]
+pub async fn estimate_job_cost(
+    app: AppHandle,
+    model: String,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    requests: Option<u64>,
+    force_refresh: Option<bool>,
+) -> Result<CostEstimate, String> {
+    let settings = read_settings()?;
+    let balance = match settings.api_keys.get(settings.active_api_key_index) {
+        Some(api) => fetch_balance(app, api.key.clone()).await.ok(),
+        None => None,
+    };
+    estimate_job(
+        &model,
+        prompt_tokens,
+        completion_tokens,
+        requests.unwrap_or(1),
+        balance.as_ref(),
+        force_refresh.unwrap_or(false),
+    )
+    .await
+}
+
+/// The cached price list (for model pickers)
+#[tauri::command]
+pub async fn get_model_prices(force_refresh: Option<bool>) -> Result<Vec<ModelPrice>, String> {
+    Ok(load_prices(force_refresh.unwrap_or(false)).await.0)
+}
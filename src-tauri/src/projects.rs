@@ -0,0 +1,171 @@
+// Project tags
+// The "current project" that balance samples are tagged with, so usage between two
+// refreshes can be attributed (see history.rs). Chosen from the tray submenu or the
+// CLI, or set from the git repo of the shell's working directory by a CLI hook.
+
+use std::path::Path;
+
+use chrono::Local;
+use tauri::menu::{CheckMenuItem, IsMenuItem, MenuItemBuilder, PredefinedMenuItem, Submenu};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+
+use crate::history::{self, ProjectSpend};
+use crate::i18n::tr;
+use crate::number_format::NumberFormat;
+use crate::{key_fingerprint, menubar_icon, read_settings, refresh_tray_menu, save_settings_internal, write_log, AppSettings, BalanceData};
+
+/// Projects offered in the tray submenu (most recent first)
+const MAX_RECENT_PROJECTS: usize = 8;
+const MAX_PROJECT_NAME: usize = 64;
+
+/// Menu ids: "project:<name>" selects a tag, "project_none" clears it
+const MENU_PREFIX: &str = "project:";
+const MENU_NONE: &str = "project_none";
+
+fn normalize(name: &str) -> Result<Option<String>, String> {
+    let name = name.trim();
+    if name.chars().count() > MAX_PROJECT_NAME {
+        return Err(tr!("project.name_too_long", MAX_PROJECT_NAME));
+    }
+    Ok((!name.is_empty()).then(|| name.to_string()))
+}
+
+/// Set the current project and move it to the front of the recent list.
+/// Returns false when nothing changed.
+pub(crate) fn select_project(settings: &mut AppSettings, project: Option<&str>) -> Result<bool, String> {
+    let project = match project {
+        Some(name) => normalize(name)?,
+        None => None,
+    };
+    let changed = settings.current_project != project;
+    if let Some(name) = &project {
+        settings.recent_projects.retain(|p| p != name);
+        settings.recent_projects.insert(0, name.clone());
+        settings.recent_projects.truncate(MAX_RECENT_PROJECTS);
+    }
+    settings.current_project = project;
+    Ok(changed)
+}
+
+/// Name of the git repository containing `dir` (the repo root's folder name)
+pub(crate) fn detect_git_project(dir: &Path) -> Option<String> {
+    // "." or "sub" have no folder name of their own; `..` must walk the real parents
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .find(|ancestor| ancestor.join(".git").exists())
+        .and_then(|root| root.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+/// Shell snippet that re-detects the project whenever the directory changes
+pub(crate) fn hook_snippet(shell: &str, exe: &str) -> Result<String, String> {
+    let detect = format!("'{}' cli project detect --quiet", exe.replace('\'', "'\\''"));
+    match shell {
+        "zsh" => Ok(format!(
+            "_bpesc_project() {{ {} &! }}\nautoload -Uz add-zsh-hook\nadd-zsh-hook chpwd _bpesc_project\n_bpesc_project\n",
+            detect
+        )),
+        "bash" => Ok(format!(
+            "_bpesc_project() {{ [ \"$PWD\" = \"$_BPESC_LAST_PWD\" ] && return; _BPESC_LAST_PWD=\"$PWD\"; ({} &) }}\nPROMPT_COMMAND=\"_bpesc_project${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}\"\n",
+            detect
+        )),
+        "fish" => Ok(format!(
+            "function _bpesc_project --on-variable PWD\n    {} &\n    disown\nend\n_bpesc_project\n",
+            detect
+        )),
+        other => Err(tr!("project.unsupported_shell", other)),
+    }
+}
+
+/// Append a balance sample after a fresh fetch; only configured keys are sampled
+/// (key checks of pasted, unsaved keys are not)
+pub(crate) fn record_balance_sample(api_key: &str, balance: &BalanceData) {
+    let Some(usage) = balance.usage else { return };
+    let Ok(settings) = read_settings() else { return };
+    if !settings.api_keys.iter().any(|k| k.key.trim() == api_key.trim()) {
+        return;
+    }
+    let days_in_month = menubar_icon::days_in_month(Local::now().date_naive()) as f64;
+    if let Err(e) = history::record_sample(key_fingerprint(api_key), usage, balance.monthly_budget(days_in_month), settings.current_project) {
+        let _ = write_log(&format!("[History] {}", e));
+    }
+}
+
+/// Select a project, save and let the app know. A balance refresh is requested so a
+/// fresh sample closes the interval of the previous tag.
+fn apply_project(app: &AppHandle, project: Option<&str>) -> Result<AppSettings, String> {
+    let mut settings = read_settings()?;
+    if !select_project(&mut settings, project)? {
+        return Ok(settings);
+    }
+    let _ = write_log(&format!(
+        "[Projects] Current project: {}",
+        settings.current_project.as_deref().unwrap_or("none")
+    ));
+    save_settings_internal(&settings)?;
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.emit("settings-updated", &settings);
+        let _ = window.emit("refresh-balance", ());
+    }
+    refresh_tray_menu(app);
+    Ok(settings)
+}
+
+/// Handle a click in the tray's project submenu
+pub(crate) fn handle_menu_event(app: &AppHandle, id: &str) {
+    let project = match id.strip_prefix(MENU_PREFIX) {
+        Some(name) => Some(name),
+        None if id == MENU_NONE => None,
+        None => return,
+    };
+    if let Err(e) = apply_project(app, project) {
+        let _ = write_log(&format!("[Projects] {}", e));
+    }
+}
+
+fn menu_label(name: &str, spend: &ProjectSpend, format: &NumberFormat) -> String {
+    match spend.by_project.iter().find(|t| t.name == name).filter(|t| t.cost >= 0.005) {
+        Some(total) => format!("{}  {}", name, format.money(total.cost, 2)),
+        None => name.to_string(),
+    }
+}
+
+/// "Project: <name>" submenu with the recent tags and this month's totals
+pub(crate) fn tray_submenu(app: &AppHandle, settings: Option<&AppSettings>) -> tauri::Result<Submenu<Wry>> {
+    let current = settings.and_then(|s| s.current_project.clone());
+    let recent = settings.map(|s| s.recent_projects.clone()).unwrap_or_default();
+    let spend = history::project_totals(&history::current_month());
+    let format = settings.map(NumberFormat::from_settings).unwrap_or_default();
+
+    let none = CheckMenuItem::with_id(app, MENU_NONE, tr!("tray.no_project"), true, current.is_none(), None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let mut projects = Vec::new();
+    for name in &recent {
+        let id = format!("{}{}", MENU_PREFIX, name);
+        let checked = current.as_deref() == Some(name.as_str());
+        projects.push(CheckMenuItem::with_id(app, id, menu_label(name, &spend, &format), true, checked, None::<&str>)?);
+    }
+    let hint = MenuItemBuilder::with_id("project_hint", tr!("tray.project_hint"))
+        .enabled(false)
+        .build(app)?;
+
+    let mut items: Vec<&dyn IsMenuItem<Wry>> = vec![&none, &separator];
+    if projects.is_empty() {
+        items.push(&hint);
+    }
+    for item in &projects {
+        items.push(item);
+    }
+    let title = tr!("tray.project", current.clone().unwrap_or_else(|| tr!("tray.project_none")));
+    Submenu::with_items(app, title, true, &items)
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Set (or with `None` clear) the project that new usage is attributed to
+#[tauri::command]
+pub fn set_current_project(app: AppHandle, project: Option<String>) -> Result<AppSettings, String> {
+    apply_project(&app, project.as_deref())
+}
@@ -0,0 +1,1218 @@
+// Metering proxy
+// Optional OpenAI-compatible endpoint on 127.0.0.1 that forwards requests to OpenRouter
+// with the active key, reads usage/cost from each response and records it per client
+// and project in the history store, so the menubar moves without polling.
+// A budget guard rejects new requests once a cap is hit, before anything is forwarded.
+// Callers authenticate with a per-install token; browser requests (any Origin) and
+// foreign Host headers (DNS rebinding) are turned away.
+
+use std::convert::Infallible;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use http_body_util::channel::Channel;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full, Limited};
+use hyper::body::Incoming;
+use hyper::header::{self, HeaderMap, HeaderName};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::net::TcpListener;
+
+use crate::history::{self, HistoryEntry, RequestRecord};
+use crate::i18n::tr;
+use crate::number_format::NumberFormat;
+use crate::{
+    get_config_dir, key_fingerprint, pricing, read_settings, save_settings_internal, update_menubar_display, write_log,
+    AppSettings, BalanceData, MenubarState,
+};
+
+const OPENROUTER_API_BASE: &str = "https://openrouter.ai/api/v1";
+/// Largest request body forwarded (prompts with images can be big)
+const MAX_REQUEST_BODY: usize = 32 * 1024 * 1024;
+/// Non-streaming responses are parsed for usage up to this size
+const MAX_USAGE_SCAN: usize = 16 * 1024 * 1024;
+
+type ProxyBody = BoxBody<Bytes, Infallible>;
+type ProxyError = (StatusCode, String);
+type GuardFn = Box<dyn Fn(&GuardRequest) -> Result<(), String> + Send + Sync>;
+
+/// What the proxy needs from its host; tests swap in a mock upstream and recorder
+pub(crate) struct ProxyEnv {
+    /// OpenRouter API base, e.g. https://openrouter.ai/api/v1
+    pub upstream: String,
+    /// Key to forward with, looked up per request so key switches apply immediately
+    pub api_key: Box<dyn Fn() -> Result<String, String> + Send + Sync>,
+    pub on_record: Box<dyn Fn(RequestRecord) + Send + Sync>,
+    /// Budget guard: Err(reason) rejects the request before it reaches OpenRouter
+    pub guard: GuardFn,
+    /// Bearer token callers must send instead of an OpenRouter key
+    pub token: String,
+}
+
+/// What the budget guard gets to see of a generation request
+#[derive(Debug, Serialize, Clone)]
+pub(crate) struct GuardRequest {
+    pub client: String,
+    pub project: Option<String>,
+    /// `model` plus OpenRouter's `models` fallback list
+    pub models: Vec<String>,
+}
+
+struct RunningProxy {
+    port: u16,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Listener task of the running proxy (managed state)
+#[derive(Default)]
+pub(crate) struct ProxyState {
+    running: Mutex<Option<RunningProxy>>,
+    last_error: Mutex<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProxyStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub base_url: String,
+    /// The API key clients use for the proxy
+    pub token: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Sent to the window after every metered request
+#[derive(Debug, Serialize, Clone)]
+struct ProxyUsageEvent {
+    record: RequestRecord,
+    balance: Option<BalanceData>,
+}
+
+/// Sent to the window when the budget guard rejects a request
+#[derive(Debug, Serialize, Clone)]
+struct ProxyBlockedEvent {
+    request: GuardRequest,
+    reason: String,
+}
+
+/// Who made the request and where it goes
+struct RequestMeta {
+    client: String,
+    project: Option<String>,
+    path: String,
+    key_fingerprint: String,
+}
+
+// ============================================================================
+// REQUEST HANDLING
+// ============================================================================
+
+/// OpenAI-style error body, so SDKs surface the message instead of a parse failure
+fn error_response(status: StatusCode, message: &str) -> Response<ProxyBody> {
+    let body = json!({
+        "error": { "message": message, "type": "bpesc_proxy_error", "code": status.as_u16() }
+    });
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from(body.to_string())).boxed())
+        .expect("static response parts")
+}
+
+/// Split "/p/<project>/v1/chat/completions" into (project, "chat/completions").
+/// Both the OpenAI-style `/v1/` and OpenRouter's `/api/v1/` prefixes are accepted.
+fn route(path: &str) -> Option<(Option<String>, String)> {
+    let (project, rest) = match path.strip_prefix("/p/") {
+        Some(tail) => {
+            let (project, rest) = tail.split_once('/')?;
+            (Some(urlencoding::decode(project).ok()?.into_owned()), format!("/{}", rest))
+        }
+        None => (None, path.to_string()),
+    };
+    let api_path = rest.strip_prefix("/api/v1/").or_else(|| rest.strip_prefix("/v1/"))?;
+    Some((project.filter(|p| !p.trim().is_empty()), api_path.to_string()))
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)?
+        .to_str()
+        .ok()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// X-BPESC-Client, OpenRouter's X-Title app header, or the User-Agent product name
+fn client_name(headers: &HeaderMap) -> String {
+    header_str(headers, "x-bpesc-client")
+        .or_else(|| header_str(headers, "x-title"))
+        .or_else(|| header_str(headers, "user-agent").and_then(|ua| ua.split(['/', ' ']).next()))
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn forwardable_request_header(name: &HeaderName) -> bool {
+    !matches!(
+        name.as_str(),
+        "host" | "authorization" | "content-length" | "connection" | "keep-alive" | "transfer-encoding"
+            | "upgrade" | "te" | "trailer" | "accept-encoding" | "proxy-authorization" | "proxy-connection"
+            | "x-bpesc-client" | "x-bpesc-project"
+    )
+}
+
+/// CORS headers are dropped too: no web page gets to read proxy responses
+fn forwardable_response_header(name: &HeaderName) -> bool {
+    !matches!(
+        name.as_str(),
+        "content-length" | "connection" | "keep-alive" | "transfer-encoding" | "upgrade" | "te" | "trailer"
+    ) && !name.as_str().starts_with("access-control-")
+}
+
+/// Compare without stopping at the first differing byte
+fn token_matches(sent: &str, token: &str) -> bool {
+    sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Only local tools may call: no browser (they always send Origin on cross-site requests),
+/// no Host other than our own address (DNS rebinding), and the proxy token as bearer key.
+/// `/health` needs no token.
+fn check_caller(headers: &HeaderMap, path: &str, port: u16, token: &str) -> Result<(), ProxyError> {
+    if headers.contains_key(header::ORIGIN) {
+        return Err((StatusCode::FORBIDDEN, "Requests from web pages are not accepted.".to_string()));
+    }
+    let host = header_str(headers, "host").unwrap_or_default();
+    if host != format!("127.0.0.1:{}", port) && host != format!("localhost:{}", port) {
+        return Err((StatusCode::FORBIDDEN, format!("Unexpected Host {:?}. Use http://127.0.0.1:{}.", host, port)));
+    }
+    if path == "/health" {
+        return Ok(());
+    }
+    let sent = header_str(headers, "authorization")
+        .and_then(|auth| auth.strip_prefix("Bearer ").or_else(|| auth.strip_prefix("bearer ")))
+        .map(str::trim)
+        .unwrap_or_default();
+    if !token_matches(sent, token) {
+        return Err((StatusCode::UNAUTHORIZED, "Use the BP-ESC proxy token as API key.".to_string()));
+    }
+    Ok(())
+}
+
+/// Ask OpenRouter to include the cost in the response unless the client chose itself
+fn with_usage_accounting(body: &[u8]) -> Option<Bytes> {
+    let mut value: Value = serde_json::from_slice(body).ok()?;
+    let object = value.as_object_mut()?;
+    if object.contains_key("usage") {
+        return None;
+    }
+    object.insert("usage".to_string(), json!({ "include": true }));
+    serde_json::to_vec(&value).ok().map(Bytes::from)
+}
+
+async fn handle(req: Request<Incoming>, env: Arc<ProxyEnv>, client: reqwest::Client, port: u16) -> Result<Response<ProxyBody>, Infallible> {
+    if let Err((status, message)) = check_caller(req.headers(), req.uri().path(), port, &env.token) {
+        return Ok(error_response(status, &message));
+    }
+    Ok(match forward(req, env, &client).await {
+        Ok(response) => response,
+        Err((status, message)) => error_response(status, &message),
+    })
+}
+
+async fn forward(req: Request<Incoming>, env: Arc<ProxyEnv>, client: &reqwest::Client) -> Result<Response<ProxyBody>, ProxyError> {
+    if req.uri().path() == "/health" {
+        return Ok(Response::new(Full::new(Bytes::from_static(b"{\"status\":\"ok\"}")).boxed()));
+    }
+
+    let (path_project, api_path) = route(req.uri().path()).ok_or_else(|| {
+        (StatusCode::NOT_FOUND, format!("Unknown path {}. Use the base URL .../v1 or .../p/<project>/v1.", req.uri().path()))
+    })?;
+    let api_key = (env.api_key)().map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e))?;
+
+    let (parts, body) = req.into_parts();
+    let meta = RequestMeta {
+        client: client_name(&parts.headers),
+        project: path_project.or_else(|| header_str(&parts.headers, "x-bpesc-project").map(str::to_string)),
+        path: api_path.clone(),
+        key_fingerprint: key_fingerprint(&api_key),
+    };
+
+    let mut body = Limited::new(body, MAX_REQUEST_BODY)
+        .collect()
+        .await
+        .map_err(|e| (StatusCode::PAYLOAD_TOO_LARGE, format!("Could not read request body: {}", e)))?
+        .to_bytes();
+    if parts.method == Method::POST {
+        let guard_request = GuardRequest {
+            client: meta.client.clone(),
+            project: meta.project.clone(),
+            models: requested_models(&body),
+        };
+        (env.guard)(&guard_request)
+            .map_err(|reason| (StatusCode::PAYMENT_REQUIRED, format!("BP-ESC budget guard: {}", reason)))?;
+        if let Some(with_usage) = with_usage_accounting(&body) {
+            body = with_usage;
+        }
+    }
+
+    let mut url = format!("{}/{}", env.upstream.trim_end_matches('/'), api_path);
+    if let Some(query) = parts.uri.query() {
+        url.push('?');
+        url.push_str(query);
+    }
+
+    let mut upstream_req = client
+        .request(parts.method.clone(), url)
+        .header(header::AUTHORIZATION, format!("Bearer {}", api_key))
+        .header(header::ACCEPT_ENCODING, "identity");
+    for (name, value) in parts.headers.iter().filter(|(name, _)| forwardable_request_header(name)) {
+        upstream_req = upstream_req.header(name, value);
+    }
+
+    let upstream = upstream_req
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("OpenRouter could not be reached: {}", e)))?;
+
+    let status = upstream.status();
+    let mut response = Response::builder().status(status);
+    for (name, value) in upstream.headers().iter().filter(|(name, _)| forwardable_response_header(name)) {
+        response = response.header(name, value);
+    }
+    let event_stream = header_str(upstream.headers(), "content-type")
+        .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+    // Stream the body through while watching it for the usage block
+    let (mut sender, channel) = Channel::<Bytes, Infallible>::new(16);
+    tokio::spawn(async move {
+        let mut upstream = upstream;
+        let mut scanner = UsageScanner::new(event_stream);
+        let mut cut_short = false;
+        loop {
+            match upstream.chunk().await {
+                Ok(Some(chunk)) => {
+                    scanner.feed(&chunk);
+                    if sender.send_data(chunk).await.is_err() {
+                        // Client went away; the generation is cancelled upstream but may be billed
+                        cut_short = true;
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    let _ = write_log(&format!("[Proxy] Upstream stream failed: {}", e));
+                    cut_short = true;
+                    break;
+                }
+            }
+        }
+        if status.is_success() {
+            if let Some(usage) = scanner.finish(cut_short) {
+                record_usage(&env, meta, status.as_u16(), usage).await;
+            }
+        }
+    });
+
+    response
+        .body(channel.boxed())
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Invalid upstream response: {}", e)))
+}
+
+// ============================================================================
+// BUDGET GUARD
+// ============================================================================
+
+fn requested_models(body: &[u8]) -> Vec<String> {
+    let Ok(value) = serde_json::from_slice::<Value>(body) else { return Vec::new() };
+    let mut models: Vec<String> = value.get("model").and_then(Value::as_str).map(str::to_string).into_iter().collect();
+    if let Some(fallbacks) = value.get("models").and_then(Value::as_array) {
+        models.extend(fallbacks.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    models
+}
+
+/// Exact id or "vendor/*"; variants like ":free" or ":online" count as their base model
+fn model_allowed(allowed: &[String], model: &str) -> bool {
+    let base = model.split(':').next().unwrap_or(model);
+    allowed.iter().map(|a| a.trim()).any(|a| match a.strip_suffix("/*") {
+        Some(vendor) => base.split('/').next().is_some_and(|v| v.eq_ignore_ascii_case(vendor)),
+        None => a.eq_ignore_ascii_case(base),
+    })
+}
+
+/// Override is active while `proxy_guard_override_until` lies in the future
+pub(crate) fn guard_overridden(settings: &AppSettings, now: DateTime<Utc>) -> bool {
+    settings
+        .proxy_guard_override_until
+        .as_deref()
+        .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+        .is_some_and(|until| until > now)
+}
+
+/// Why a request must not go through, or None. `project_spend` is this month's local
+/// spend of the request's project (only looked at when that project has a cap).
+pub(crate) fn guard_rejection(
+    settings: &AppSettings,
+    balance: Option<&BalanceData>,
+    request: &GuardRequest,
+    project_spend: f64,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    if !settings.proxy_guard_enabled || guard_overridden(settings, now) {
+        return None;
+    }
+
+    if !settings.proxy_allowed_models.is_empty() {
+        if request.models.is_empty() {
+            return Some(tr!("guard.name_model"));
+        }
+        if let Some(model) = request.models.iter().find(|m| !model_allowed(&settings.proxy_allowed_models, m)) {
+            return Some(tr!("guard.model_not_allowed", model));
+        }
+    }
+
+    if let Some(balance) = balance {
+        if let Some(remaining) = balance.budget_remaining().filter(|r| *r <= 0.0) {
+            let remaining = NumberFormat::from_settings(settings).money(remaining.max(0.0), 2);
+            return Some(tr!("guard.budget_exhausted", remaining));
+        }
+        let max_pace = settings.proxy_guard_max_pace_percent;
+        if let Some(delta) = balance.pace_month_delta_percent.filter(|d| max_pace > 0.0 && *d > max_pace) {
+            return Some(tr!("guard.ahead_of_pace", format!("{:.0}", delta), format!("{:.0}", max_pace)));
+        }
+    }
+
+    if let Some(project) = request.project.as_deref() {
+        if let Some(cap) = settings.proxy_project_caps.get(project).filter(|cap| project_spend >= **cap) {
+            let format = NumberFormat::from_settings(settings);
+            return Some(tr!(
+                "guard.project_cap",
+                project,
+                format.money(project_spend, 2),
+                format.money(*cap, 2)
+            ));
+        }
+    }
+    None
+}
+
+// ============================================================================
+// USAGE
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct Usage {
+    model: Option<String>,
+    generation_id: Option<String>,
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    cost: Option<f64>,
+    /// The stream ended before its usage block; tokens and cost are unknown
+    cut_short: bool,
+}
+
+/// Finds the usage block of a JSON or SSE response while it streams through
+struct UsageScanner {
+    event_stream: bool,
+    buffer: Vec<u8>,
+    overflow: bool,
+    model: Option<String>,
+    generation_id: Option<String>,
+    usage: Option<Value>,
+}
+
+impl UsageScanner {
+    fn new(event_stream: bool) -> Self {
+        Self { event_stream, buffer: Vec::new(), overflow: false, model: None, generation_id: None, usage: None }
+    }
+
+    fn feed(&mut self, chunk: &[u8]) {
+        if self.event_stream {
+            self.buffer.extend_from_slice(chunk);
+            while let Some(end) = self.buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                self.scan_event_line(&line);
+            }
+        } else if !self.overflow && self.buffer.len() + chunk.len() <= MAX_USAGE_SCAN {
+            self.buffer.extend_from_slice(chunk);
+        } else {
+            self.overflow = true;
+            self.buffer.clear();
+        }
+    }
+
+    /// `data: {...}` lines carry the chunks; usage arrives in the last one
+    fn scan_event_line(&mut self, line: &[u8]) {
+        let Ok(text) = std::str::from_utf8(line) else { return };
+        let Some(data) = text.trim().strip_prefix("data:") else { return };
+        if let Ok(value) = serde_json::from_str::<Value>(data.trim()) {
+            self.observe(&value);
+        }
+    }
+
+    fn observe(&mut self, value: &Value) {
+        if let Some(model) = value.get("model").and_then(Value::as_str) {
+            self.model = Some(model.to_string());
+        }
+        if let Some(id) = value.get("id").and_then(Value::as_str) {
+            self.generation_id = Some(id.to_string());
+        }
+        if let Some(usage) = value.get("usage").filter(|u| u.is_object()) {
+            self.usage = Some(usage.clone());
+        }
+    }
+
+    /// Usage of the response; a stream cut short is still recorded, without usage if need be
+    fn finish(mut self, cut_short: bool) -> Option<Usage> {
+        let rest = std::mem::take(&mut self.buffer);
+        if self.event_stream {
+            self.scan_event_line(&rest);
+        } else if !self.overflow {
+            if let Ok(value) = serde_json::from_slice::<Value>(&rest) {
+                self.observe(&value);
+            }
+        }
+
+        let Some(usage) = self.usage else {
+            return cut_short.then_some(Usage {
+                model: self.model,
+                generation_id: self.generation_id,
+                cut_short: true,
+                ..Default::default()
+            });
+        };
+        let tokens = |field: &str| usage.get(field).and_then(Value::as_u64).unwrap_or(0);
+        Some(Usage {
+            prompt_tokens: tokens("prompt_tokens"),
+            completion_tokens: tokens("completion_tokens"),
+            cost: usage.get("cost").and_then(Value::as_f64),
+            model: self.model,
+            generation_id: self.generation_id,
+            cut_short: false,
+        })
+    }
+}
+
+/// Cost from the cached price list when the response carried none
+async fn estimate_cost(usage: &Usage) -> Option<f64> {
+    let (models, _, _) = pricing::load_prices(false).await;
+    let price = pricing::find_model(&models, usage.model.as_deref()?).ok()?;
+    Some(price.prompt * usage.prompt_tokens as f64 + price.completion * usage.completion_tokens as f64 + price.request)
+}
+
+async fn record_usage(env: &ProxyEnv, meta: RequestMeta, status: u16, usage: Usage) {
+    let (cost, cost_estimated) = match usage.cost {
+        Some(cost) => (cost, false),
+        None if usage.cut_short => (0.0, true),
+        None => (estimate_cost(&usage).await.unwrap_or(0.0), true),
+    };
+    (env.on_record)(RequestRecord {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        key_fingerprint: meta.key_fingerprint,
+        client: meta.client,
+        project: meta.project,
+        model: usage.model,
+        generation_id: usage.generation_id,
+        path: meta.path,
+        status,
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        cost,
+        cost_estimated,
+    });
+}
+
+// ============================================================================
+// SERVER
+// ============================================================================
+
+/// Accept connections until the task is aborted
+pub(crate) async fn serve(listener: TcpListener, env: Arc<ProxyEnv>) {
+    let port = listener.local_addr().map(|addr| addr.port()).unwrap_or_default();
+    // No overall timeout: streamed completions can run for minutes
+    let client = match reqwest::Client::builder().connect_timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = write_log(&format!("[Proxy] Could not create HTTP client: {}", e));
+            return;
+        }
+    };
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                let _ = write_log(&format!("[Proxy] Accept failed: {}", e));
+                continue;
+            }
+        };
+        let env = env.clone();
+        let client = client.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| handle(req, env.clone(), client.clone(), port));
+            if let Err(e) = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await {
+                let _ = write_log(&format!("[Proxy] Connection error: {}", e));
+            }
+        });
+    }
+}
+
+/// Per-install proxy token in ~/.config/bpesc-balance/proxy-token (0600), created on first use.
+/// Kept out of settings.json so no settings save can drop or rotate it.
+pub(crate) fn proxy_token() -> Result<String, String> {
+    let path = get_config_dir()?.join("proxy-token");
+    if let Ok(token) = fs::read_to_string(&path) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+
+    let mut random = [0u8; 24];
+    fs::File::open("/dev/urandom")
+        .and_then(|mut source| source.read_exact(&mut random))
+        .map_err(|e| tr!("proxy.token", e))?;
+    let token = format!("bpesc-{}", random.iter().map(|b| format!("{:02x}", b)).collect::<String>());
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| tr!("proxy.token", e))?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(&path)
+        .and_then(|mut file| file.write_all(token.as_bytes()))
+        .map_err(|e| tr!("proxy.token", e))?;
+    Ok(token)
+}
+
+fn active_key() -> Result<String, String> {
+    let settings = read_settings()?;
+    settings
+        .api_keys
+        .get(settings.active_api_key_index)
+        .map(|k| k.key.trim().to_string())
+        .ok_or_else(|| tr!("key.none_configured"))
+}
+
+/// Count the spend on the cached balance and redraw the menubar right away;
+/// the next /api/v1/key refresh replaces the estimate with server numbers
+fn apply_spend(app: &AppHandle, record: &RequestRecord) {
+    let active_fingerprint = active_key().map(|k| key_fingerprint(&k)).ok();
+    let state = app.try_state::<MenubarState>();
+    let balance = state.as_ref().and_then(|s| s.balance.lock().ok().and_then(|b| b.clone()));
+    let settings = state.as_ref().and_then(|s| s.settings.lock().ok().and_then(|s| s.clone()));
+
+    let balance = match (balance, settings) {
+        (Some(mut balance), Some(settings)) if active_fingerprint.as_deref() == Some(record.key_fingerprint.as_str()) => {
+            balance.add_spend(record.cost);
+            let handle = app.clone();
+            let redraw = balance.clone();
+            let _ = app.run_on_main_thread(move || {
+                let _ = update_menubar_display(handle, redraw, settings);
+            });
+            Some(balance)
+        }
+        _ => None,
+    };
+    let _ = app.emit("proxy-usage", ProxyUsageEvent { record: record.clone(), balance });
+}
+
+/// Guard against the current settings and the cached balance of the active key
+fn check_guard(app: &AppHandle, request: &GuardRequest) -> Result<(), String> {
+    let settings = read_settings()?;
+    let balance = app
+        .try_state::<MenubarState>()
+        .and_then(|s| s.balance.lock().ok().and_then(|b| b.clone()));
+    let project_spend = match request.project.as_deref() {
+        Some(project) if settings.proxy_project_caps.contains_key(project) => history::project_spend(project),
+        _ => 0.0,
+    };
+
+    match guard_rejection(&settings, balance.as_ref(), request, project_spend, Utc::now()) {
+        None => Ok(()),
+        Some(reason) => {
+            let _ = write_log(&format!("[Proxy] Blocked request from {}: {}", request.client, reason));
+            let _ = app.emit("proxy-blocked", ProxyBlockedEvent { request: request.clone(), reason: reason.clone() });
+            Err(tr!("guard.override_hint", reason))
+        }
+    }
+}
+
+fn app_env(app: AppHandle, token: String) -> ProxyEnv {
+    let guard_app = app.clone();
+    ProxyEnv {
+        token,
+        upstream: OPENROUTER_API_BASE.to_string(),
+        api_key: Box::new(active_key),
+        guard: Box::new(move |request| check_guard(&guard_app, request)),
+        on_record: Box::new(move |record| {
+            if let Err(e) = history::append(&HistoryEntry::Request(record.clone())) {
+                let _ = write_log(&format!("[Proxy] {}", e));
+            }
+            apply_spend(&app, &record);
+        }),
+    }
+}
+
+/// Start, stop or move the proxy to match the settings
+pub(crate) async fn apply_settings(app: &AppHandle, settings: &AppSettings) -> Result<(), String> {
+    let Some(state) = app.try_state::<ProxyState>() else { return Ok(()) };
+    let wanted = settings.proxy_enabled.then_some(settings.proxy_port);
+
+    if let Ok(mut running) = state.running.lock() {
+        // Only a listener on the wanted port is left alone; "off" falls through
+        // so a failed start's error is cleared once the proxy is disabled
+        if wanted.is_some() && running.as_ref().map(|r| r.port) == wanted {
+            return Ok(());
+        }
+        if let Some(old) = running.take() {
+            old.task.abort();
+            let _ = write_log(&format!("[Proxy] Stopped listening on 127.0.0.1:{}", old.port));
+        }
+    }
+    let Some(port) = wanted else {
+        if let Ok(mut error) = state.last_error.lock() {
+            *error = None;
+        }
+        return Ok(());
+    };
+
+    let result = match (proxy_token(), TcpListener::bind(("127.0.0.1", port)).await) {
+        (Err(e), _) => Err(e),
+        (Ok(token), Ok(listener)) => {
+            let task = tauri::async_runtime::spawn(serve(listener, Arc::new(app_env(app.clone(), token))));
+            if let Ok(mut running) = state.running.lock() {
+                *running = Some(RunningProxy { port, task });
+            }
+            let _ = write_log(&format!("[Proxy] Listening on 127.0.0.1:{}", port));
+            Ok(())
+        }
+        (Ok(_), Err(e)) => Err(tr!("proxy.listen", port, e)),
+    };
+    if let Ok(mut error) = state.last_error.lock() {
+        *error = result.clone().err();
+    }
+    result
+}
+
+fn status_for(app: &AppHandle, settings: &AppSettings) -> ProxyStatus {
+    let state = app.try_state::<ProxyState>();
+    ProxyStatus {
+        enabled: settings.proxy_enabled,
+        running: state
+            .as_ref()
+            .and_then(|s| s.running.lock().ok().map(|r| r.is_some()))
+            .unwrap_or(false),
+        port: settings.proxy_port,
+        base_url: format!("http://127.0.0.1:{}/v1", settings.proxy_port),
+        token: settings.proxy_enabled.then(|| proxy_token().ok()).flatten(),
+        error: state.as_ref().and_then(|s| s.last_error.lock().ok().and_then(|e| e.clone())),
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn get_proxy_status(app: AppHandle) -> Result<ProxyStatus, String> {
+    Ok(status_for(&app, &read_settings()?))
+}
+
+/// Turn the proxy on/off (and optionally move it); settings are only saved if it could start
+#[tauri::command]
+pub async fn set_proxy_enabled(app: AppHandle, enabled: bool, port: Option<u16>) -> Result<AppSettings, String> {
+    let mut settings = read_settings()?;
+    settings.proxy_enabled = enabled;
+    if let Some(port) = port {
+        if port < 1024 {
+            return Err(tr!("proxy.port_range"));
+        }
+        settings.proxy_port = port;
+    }
+
+    apply_settings(&app, &settings).await?;
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+/// Budget guard limits; caps are validated here rather than in save_settings
+#[tauri::command]
+pub fn set_proxy_guard(
+    app: AppHandle,
+    enabled: bool,
+    max_pace_percent: f64,
+    allowed_models: Vec<String>,
+    project_caps: std::collections::BTreeMap<String, f64>,
+) -> Result<AppSettings, String> {
+    if !max_pace_percent.is_finite() || max_pace_percent < 0.0 {
+        return Err(tr!("proxy.negative_pace_cap"));
+    }
+    if let Some((project, _)) = project_caps.iter().find(|(_, cap)| !cap.is_finite() || **cap < 0.0) {
+        return Err(tr!("proxy.negative_project_cap", project));
+    }
+
+    let mut settings = read_settings()?;
+    settings.proxy_guard_enabled = enabled;
+    settings.proxy_guard_max_pace_percent = max_pace_percent;
+    settings.proxy_allowed_models = allowed_models
+        .iter()
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+    settings.proxy_project_caps = project_caps
+        .into_iter()
+        .map(|(project, cap)| (project.trim().to_string(), cap))
+        .filter(|(project, _)| !project.is_empty())
+        .collect();
+    save_settings_internal(&settings)?;
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+/// Emergency override: let everything through for `minutes` (0 ends the override)
+#[tauri::command]
+pub fn override_proxy_guard(app: AppHandle, minutes: u32) -> Result<AppSettings, String> {
+    if minutes > 24 * 60 {
+        return Err(tr!("proxy.override_limit"));
+    }
+    let mut settings = read_settings()?;
+    settings.proxy_guard_override_until = (minutes > 0).then(|| {
+        (Utc::now() + chrono::Duration::minutes(minutes as i64)).to_rfc3339_opts(SecondsFormat::Secs, true)
+    });
+    save_settings_internal(&settings)?;
+    let _ = write_log(&match &settings.proxy_guard_override_until {
+        Some(until) => format!("[Proxy] Budget guard overridden until {}", until),
+        None => "[Proxy] Budget guard override ended".to_string(),
+    });
+    let _ = app.emit("settings-updated", &settings);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Seen = Arc<Mutex<Vec<(String, String, Value)>>>;
+
+    const TOKEN: &str = "bpesc-test-token";
+
+    const CHAT_RESPONSE: &str = r#"{"id":"gen-1","model":"openai/gpt-4o-mini","choices":[{"message":{"role":"assistant","content":"hi"}}],"usage":{"prompt_tokens":12,"completion_tokens":3,"total_tokens":15,"cost":0.0042}}"#;
+    const STREAM_CHUNKS: [&str; 4] = [
+        "data: {\"id\":\"gen-2\",\"model\":\"openai/gpt-4o-mini\",\"choices\":[{\"delta\":{\"content\":\"h\"}}]}\n\n",
+        ": OPENROUTER PROCESSING\n\ndata: {\"id\":\"gen-2\",\"model\":\"openai/gpt-4o-mini\",\"choi",
+        "ces\":[{\"delta\":{\"content\":\"i\"}}]}\n\ndata: {\"id\":\"gen-2\",\"model\":\"openai/gpt-4o-mini\",\"choices\":[],\"usage\":{\"prompt_tokens\":20,\"completion_tokens\":7,\"cost\":0.0105}}\n\n",
+        "data: [DONE]\n\n",
+    ];
+
+    /// Fake OpenRouter: records what it received and answers with canned bodies
+    async fn mock_upstream(seen: Seen) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let seen = seen.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |req: Request<Incoming>| {
+                        let seen = seen.clone();
+                        async move {
+                            let auth = header_str(req.headers(), "authorization").unwrap_or_default().to_string();
+                            let path = req.uri().path().to_string();
+                            let body = req.into_body().collect().await.unwrap().to_bytes();
+                            let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+                            let stream = body.get("stream") == Some(&Value::Bool(true));
+                            let slow = body.get("model") == Some(&json!("test/slow"));
+                            seen.lock().unwrap().push((auth, path.clone(), body));
+
+                            let response = if path.ends_with("/models") {
+                                Response::new(Full::new(Bytes::from_static(b"{\"data\":[]}")).boxed())
+                            } else if slow {
+                                // One chunk, then keep-alives until the client hangs up
+                                let (mut sender, channel) = Channel::<Bytes, Infallible>::new(4);
+                                tokio::spawn(async move {
+                                    let first = "data: {\"id\":\"gen-3\",\"model\":\"test/slow\",\"choices\":[{\"delta\":{\"content\":\"h\"}}]}\n\n";
+                                    let _ = sender.send_data(Bytes::from_static(first.as_bytes())).await;
+                                    for _ in 0..500 {
+                                        tokio::time::sleep(Duration::from_millis(10)).await;
+                                        if sender.send_data(Bytes::from_static(b": OPENROUTER PROCESSING\n\n")).await.is_err() {
+                                            break;
+                                        }
+                                    }
+                                });
+                                Response::builder()
+                                    .header(header::CONTENT_TYPE, "text/event-stream")
+                                    .body(channel.boxed())
+                                    .unwrap()
+                            } else if stream {
+                                let (mut sender, channel) = Channel::<Bytes, Infallible>::new(4);
+                                tokio::spawn(async move {
+                                    for chunk in STREAM_CHUNKS {
+                                        let _ = sender.send_data(Bytes::from_static(chunk.as_bytes())).await;
+                                        tokio::time::sleep(Duration::from_millis(5)).await;
+                                    }
+                                });
+                                Response::builder()
+                                    .header(header::CONTENT_TYPE, "text/event-stream")
+                                    .body(channel.boxed())
+                                    .unwrap()
+                            } else {
+                                Response::builder()
+                                    .header(header::CONTENT_TYPE, "application/json")
+                                    .header("access-control-allow-origin", "*")
+                                    .body(Full::new(Bytes::from_static(CHAT_RESPONSE.as_bytes())).boxed())
+                                    .unwrap()
+                            };
+                            Ok::<_, Infallible>(response)
+                        }
+                    });
+                    let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+                });
+            }
+        });
+        format!("http://{}/api/v1", addr)
+    }
+
+    /// Proxy pointed at the mock; returns its base address and the recorded requests
+    async fn start_proxy(upstream: String) -> (String, Arc<Mutex<Vec<RequestRecord>>>) {
+        start_guarded_proxy(upstream, Box::new(|_| Ok(()))).await
+    }
+
+    async fn start_guarded_proxy(upstream: String, guard: GuardFn) -> (String, Arc<Mutex<Vec<RequestRecord>>>) {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let sink = records.clone();
+        let env = ProxyEnv {
+            upstream,
+            api_key: Box::new(|| Ok("sk-or-v1-proxytest".to_string())),
+            on_record: Box::new(move |record| sink.lock().unwrap().push(record)),
+            guard,
+            token: TOKEN.to_string(),
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, Arc::new(env)));
+        (format!("http://{}", addr), records)
+    }
+
+    /// HTTP client that authenticates like a configured tool
+    fn client() -> reqwest::Client {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, format!("Bearer {}", TOKEN).parse().unwrap());
+        reqwest::Client::builder().default_headers(headers).build().unwrap()
+    }
+
+    /// Usage is recorded after the body finished streaming, so give it a moment
+    async fn wait_for_records(records: &Arc<Mutex<Vec<RequestRecord>>>, count: usize) -> Vec<RequestRecord> {
+        for _ in 0..100 {
+            if records.lock().unwrap().len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        records.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn forwards_json_completion_and_records_cost() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, records) = start_proxy(mock_upstream(seen.clone()).await).await;
+
+            let response = client()
+                .post(format!("{}/p/billing/v1/chat/completions", proxy))
+                .header("X-Title", "aider")
+                .json(&json!({ "model": "openai/gpt-4o-mini", "messages": [] }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            assert!(response.headers().get("access-control-allow-origin").is_none());
+            assert_eq!(response.text().await.unwrap(), CHAT_RESPONSE);
+
+            let (auth, path, body) = seen.lock().unwrap()[0].clone();
+            assert_eq!(auth, "Bearer sk-or-v1-proxytest");
+            assert_eq!(path, "/api/v1/chat/completions");
+            assert_eq!(body["usage"], json!({ "include": true }));
+
+            let records = wait_for_records(&records, 1).await;
+            assert_eq!(records.len(), 1);
+            let record = &records[0];
+            assert_eq!(record.client, "aider");
+            assert_eq!(record.project.as_deref(), Some("billing"));
+            assert_eq!(record.model.as_deref(), Some("openai/gpt-4o-mini"));
+            assert_eq!(record.generation_id.as_deref(), Some("gen-1"));
+            assert_eq!((record.prompt_tokens, record.completion_tokens), (12, 3));
+            assert!((record.cost - 0.0042).abs() < 1e-12);
+            assert!(!record.cost_estimated);
+            assert_eq!(record.key_fingerprint, key_fingerprint("sk-or-v1-proxytest"));
+        });
+    }
+
+    #[test]
+    fn streams_sse_through_and_records_final_usage() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, records) = start_proxy(mock_upstream(seen.clone()).await).await;
+
+            let response = client()
+                .post(format!("{}/v1/chat/completions", proxy))
+                .header("X-BPESC-Project", "website")
+                .header("User-Agent", "OpenAI/Python 1.51.0")
+                .json(&json!({ "model": "openai/gpt-4o-mini", "stream": true, "usage": { "include": false } }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.text().await.unwrap(), STREAM_CHUNKS.concat());
+
+            // The client's own usage setting is left alone
+            assert_eq!(seen.lock().unwrap()[0].2["usage"], json!({ "include": false }));
+
+            let records = wait_for_records(&records, 1).await;
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].client, "OpenAI");
+            assert_eq!(records[0].project.as_deref(), Some("website"));
+            assert_eq!(records[0].generation_id.as_deref(), Some("gen-2"));
+            assert_eq!((records[0].prompt_tokens, records[0].completion_tokens), (20, 7));
+            assert!((records[0].cost - 0.0105).abs() < 1e-12);
+        });
+    }
+
+    #[test]
+    fn streams_cut_short_by_the_client_are_still_recorded() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, records) = start_proxy(mock_upstream(seen.clone()).await).await;
+
+            let mut response = client()
+                .post(format!("{}/p/web/v1/chat/completions", proxy))
+                .json(&json!({ "model": "test/slow", "stream": true }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            assert!(response.chunk().await.unwrap().is_some());
+            drop(response);
+
+            let records = wait_for_records(&records, 1).await;
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].project.as_deref(), Some("web"));
+            assert_eq!(records[0].model.as_deref(), Some("test/slow"));
+            assert_eq!(records[0].generation_id.as_deref(), Some("gen-3"));
+            assert_eq!((records[0].prompt_tokens, records[0].completion_tokens), (0, 0));
+            assert!(records[0].cost_estimated);
+        });
+    }
+
+    #[test]
+    fn requests_without_usage_are_not_recorded() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, records) = start_proxy(mock_upstream(seen.clone()).await).await;
+
+            let response = client().get(format!("{}/api/v1/models?category=programming", proxy)).send().await.unwrap();
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.text().await.unwrap(), "{\"data\":[]}");
+            assert_eq!(seen.lock().unwrap()[0].1, "/api/v1/models");
+
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(records.lock().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn unknown_paths_get_an_openai_style_error() {
+        tauri::async_runtime::block_on(async {
+            let (proxy, _) = start_proxy("http://127.0.0.1:9/api/v1".to_string()).await;
+
+            let response = client().get(format!("{}/chat/completions", proxy)).send().await.unwrap();
+            assert_eq!(response.status(), 404);
+            let body: Value = response.json().await.unwrap();
+            assert_eq!(body["error"]["type"], "bpesc_proxy_error");
+            assert!(body["error"]["message"].as_str().unwrap().contains("/v1"));
+        });
+    }
+
+    #[test]
+    fn rejects_callers_without_the_token() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, _) = start_proxy(mock_upstream(seen.clone()).await).await;
+            let url = format!("{}/v1/chat/completions", proxy);
+            let body = json!({ "model": "openai/gpt-4o-mini", "messages": [] });
+
+            let anonymous = reqwest::Client::new().post(&url).json(&body).send().await.unwrap();
+            assert_eq!(anonymous.status(), 401);
+            let wrong = reqwest::Client::new()
+                .post(&url)
+                .bearer_auth("sk-or-v1-someone-elses-key")
+                .json(&body)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(wrong.status(), 401);
+            // Health checks need no token
+            assert_eq!(reqwest::get(format!("{}/health", proxy)).await.unwrap().status(), 200);
+            assert!(seen.lock().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn rejects_browsers_and_foreign_hosts() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let (proxy, _) = start_proxy(mock_upstream(seen.clone()).await).await;
+            let url = format!("{}/v1/chat/completions", proxy);
+            let body = json!({ "model": "openai/gpt-4o-mini", "messages": [] });
+
+            // A web page, even one that somehow learned the token
+            let page = client().post(&url).header("Origin", "https://example.com").json(&body).send().await.unwrap();
+            assert_eq!(page.status(), 403);
+            let null_origin = client().post(&url).header("Origin", "null").json(&body).send().await.unwrap();
+            assert_eq!(null_origin.status(), 403);
+
+            // DNS rebinding: the browser connects to 127.0.0.1 but sends the attacker's host
+            let port = proxy.rsplit(':').next().unwrap();
+            let rebound = client()
+                .post(&url)
+                .header("Host", format!("attacker.example:{}", port))
+                .json(&body)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(rebound.status(), 403);
+            let localhost = client()
+                .get(format!("{}/v1/models", proxy))
+                .header("Host", format!("localhost:{}", port))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(localhost.status(), 200);
+
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 1);
+            assert_eq!(seen[0].1, "/api/v1/models");
+        });
+    }
+
+    #[test]
+    fn route_accepts_both_prefixes_and_project_paths() {
+        assert_eq!(route("/v1/chat/completions"), Some((None, "chat/completions".to_string())));
+        assert_eq!(route("/api/v1/models"), Some((None, "models".to_string())));
+        assert_eq!(
+            route("/p/my%20app/api/v1/completions"),
+            Some((Some("my app".to_string()), "completions".to_string()))
+        );
+        assert_eq!(route("/p//v1/models"), Some((None, "models".to_string())));
+        assert_eq!(route("/chat/completions"), None);
+    }
+
+    #[test]
+    fn guard_rejections_are_returned_without_forwarding() {
+        tauri::async_runtime::block_on(async {
+            let seen: Seen = Arc::default();
+            let guard = Box::new(|request: &GuardRequest| {
+                assert_eq!(request.models, vec!["openai/gpt-4o".to_string(), "openai/gpt-4o-mini".to_string()]);
+                Err("the key's budget is exhausted ($0.00 remaining).".to_string())
+            });
+            let (proxy, records) = start_guarded_proxy(mock_upstream(seen.clone()).await, guard).await;
+
+            let response = client()
+                .post(format!("{}/v1/chat/completions", proxy))
+                .json(&json!({ "model": "openai/gpt-4o", "models": ["openai/gpt-4o-mini"], "messages": [] }))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 402);
+            let body: Value = response.json().await.unwrap();
+            assert_eq!(body["error"]["type"], "bpesc_proxy_error");
+            assert!(body["error"]["message"].as_str().unwrap().starts_with("BP-ESC budget guard: the key's budget"));
+
+            // GET requests (model lists etc.) are never guarded
+            let response = client().get(format!("{}/v1/models", proxy)).send().await.unwrap();
+            assert_eq!(response.status(), 200);
+
+            let seen = seen.lock().unwrap();
+            assert_eq!(seen.len(), 1);
+            assert_eq!(seen[0].1, "/api/v1/models");
+            assert!(records.lock().unwrap().is_empty());
+        });
+    }
+
+    fn guard_request(project: Option<&str>, model: &str) -> GuardRequest {
+        GuardRequest { client: "test".to_string(), project: project.map(str::to_string), models: vec![model.to_string()] }
+    }
+
+    fn balance(remaining: f64, pace_delta: f64) -> BalanceData {
+        BalanceData {
+            effective_limit: Some(100.0),
+            usage_monthly: Some(100.0 - remaining),
+            pace_month_delta_percent: Some(pace_delta),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn guard_checks_budget_pace_and_project_caps() {
+        let now = Utc::now();
+        let mut settings = AppSettings::default();
+        settings.proxy_project_caps.insert("billing".to_string(), 5.0);
+        let request = guard_request(Some("billing"), "openai/gpt-4o");
+
+        assert_eq!(guard_rejection(&settings, Some(&balance(40.0, 10.0)), &request, 1.0, now), None);
+        // No cached balance yet: only the local caps apply
+        assert_eq!(guard_rejection(&settings, None, &request, 1.0, now), None);
+
+        let exhausted = guard_rejection(&settings, Some(&balance(-0.5, 10.0)), &request, 1.0, now).unwrap();
+        assert!(exhausted.contains("budget is exhausted"), "{}", exhausted);
+
+        let ahead = guard_rejection(&settings, Some(&balance(40.0, 150.0)), &request, 1.0, now).unwrap();
+        assert!(ahead.contains("150% ahead of pace"), "{}", ahead);
+        settings.proxy_guard_max_pace_percent = 0.0;
+        assert_eq!(guard_rejection(&settings, Some(&balance(40.0, 150.0)), &request, 1.0, now), None);
+
+        let capped = guard_rejection(&settings, Some(&balance(40.0, 10.0)), &request, 5.0, now).unwrap();
+        assert!(capped.contains("project billing"), "{}", capped);
+        assert_eq!(guard_rejection(&settings, None, &guard_request(Some("web"), "openai/gpt-4o"), 50.0, now), None);
+
+        settings.proxy_guard_enabled = false;
+        assert_eq!(guard_rejection(&settings, Some(&balance(-1.0, 500.0)), &request, 9.0, now), None);
+    }
+
+    #[test]
+    fn guard_allowlist_and_override() {
+        let now = Utc::now();
+        let mut settings = AppSettings {
+            proxy_allowed_models: vec!["anthropic/*".to_string(), "openai/gpt-4o-mini".to_string()],
+            ..Default::default()
+        };
+        let allowed = |settings: &AppSettings, model: &str| guard_rejection(settings, None, &guard_request(None, model), 0.0, now).is_none();
+
+        assert!(allowed(&settings, "anthropic/claude-sonnet-4"));
+        assert!(allowed(&settings, "openai/gpt-4o-mini"));
+        assert!(allowed(&settings, "OpenAI/GPT-4o-mini:online"));
+        assert!(!allowed(&settings, "openai/gpt-4o"));
+        assert!(!allowed(&settings, "anthropic-fake/model"));
+        let unnamed = GuardRequest { client: "test".to_string(), project: None, models: Vec::new() };
+        assert!(guard_rejection(&settings, None, &unnamed, 0.0, now).is_some());
+
+        settings.proxy_guard_override_until = Some((now + chrono::Duration::minutes(30)).to_rfc3339());
+        assert!(allowed(&settings, "openai/gpt-4o"));
+        settings.proxy_guard_override_until = Some((now - chrono::Duration::minutes(1)).to_rfc3339());
+        assert!(!allowed(&settings, "openai/gpt-4o"));
+    }
+}
@@ -0,0 +1,770 @@
+// Monthly spend reports
+// Markdown, CSV and standalone HTML for every key and all keys together, built from the
+// balance samples in the history store: total, daily series, peak days, pace adherence
+// and forecast vs. actual. Run from the tray, `generate_report` or `bp-esc cli report`.
+// Markdown and HTML are written in the app language; CSV keeps fixed column names.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, SecondsFormat, Utc};
+use serde::Serialize;
+use tauri::menu::{MenuItemBuilder, Submenu};
+use tauri::{AppHandle, Wry};
+
+use crate::history::{self, HistoryEntry, ProjectSpend};
+use crate::i18n::{self, tr, Lang};
+use crate::{get_config_dir, key_fingerprint, pace_status_from_delta, read_settings, write_log, AppSettings};
+
+pub(crate) const FORMATS: [&str; 3] = ["md", "csv", "html"];
+/// Complete months are forecast from the run rate up to this day
+const FORECAST_DAY: usize = 15;
+const PEAK_DAYS: usize = 3;
+
+/// Menu ids of the tray's report submenu
+const MENU_THIS_MONTH: &str = "report:this";
+const MENU_LAST_MONTH: &str = "report:last";
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DaySpend {
+    pub date: String, // YYYY-MM-DD, UTC like OpenRouter's usage periods
+    pub spend: f64,
+    pub cumulative: f64,
+    /// Linear share of the budget by the end of this day
+    pub pace_target: Option<f64>,
+}
+
+/// Days per pace status, counted from the first sampled day
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PaceAdherence {
+    pub days_on_track: u32,
+    pub days_behind: u32,
+    pub days_ahead: u32,
+    pub final_delta_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Forecast {
+    pub basis: String,
+    /// Month total extrapolated from the run rate up to the basis day
+    pub forecast: f64,
+    pub actual: f64,
+    /// Only for complete months
+    pub delta_percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct KeyReport {
+    pub name: String,
+    pub key_fingerprint: Option<String>,
+    pub total_spend: f64,
+    pub budget: Option<f64>,
+    pub samples: usize,
+    pub daily: Vec<DaySpend>,
+    pub peak_days: Vec<DaySpend>,
+    pub pace: Option<PaceAdherence>,
+    pub forecast: Option<Forecast>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MonthlyReport {
+    pub month: String,
+    pub generated_at: String,
+    /// false while the month is still running
+    pub complete: bool,
+    pub days_in_month: u32,
+    pub days_covered: u32,
+    pub aggregate: KeyReport,
+    pub keys: Vec<KeyReport>,
+    pub projects: ProjectSpend,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GeneratedReport {
+    pub month: String,
+    pub files: Vec<String>,
+}
+
+// ============================================================================
+// AGGREGATION
+// ============================================================================
+
+fn month_start(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| tr!("report.invalid_month", month))
+}
+
+fn days_in_month(start: NaiveDate) -> u32 {
+    let next = start.checked_add_months(chrono::Months::new(1)).unwrap_or(start);
+    (next - start).num_days() as u32
+}
+
+/// Day index inside the month of an RFC 3339 timestamp
+fn day_index(timestamp: &str, start: NaiveDate, days: u32) -> Option<usize> {
+    let date = DateTime::parse_from_rfc3339(timestamp).ok()?.with_timezone(&Utc).date_naive();
+    let index = (date - start).num_days();
+    (0..days as i64).contains(&index).then_some(index as usize)
+}
+
+/// Per-day spend of one key (or the sum of all keys)
+struct Series {
+    name: String,
+    key_fingerprint: Option<String>,
+    spend: Vec<f64>,
+    budget: Option<f64>,
+    samples: usize,
+    /// First day the series has data for (pace adherence starts here)
+    first_day: Option<usize>,
+}
+
+fn key_report(series: Series, start: NaiveDate, days: u32, days_covered: usize, complete: bool, threshold: f64) -> KeyReport {
+    let mut cumulative = 0.0;
+    let daily: Vec<DaySpend> = series
+        .spend
+        .iter()
+        .take(days_covered)
+        .enumerate()
+        .map(|(i, spend)| {
+            cumulative += spend;
+            DaySpend {
+                date: (start + Days::new(i as u64)).format("%Y-%m-%d").to_string(),
+                spend: *spend,
+                cumulative,
+                pace_target: series.budget.map(|budget| budget * (i + 1) as f64 / days as f64),
+            }
+        })
+        .collect();
+
+    let mut peak_days: Vec<DaySpend> = daily.iter().filter(|d| d.spend > 0.0).cloned().collect();
+    peak_days.sort_by(|a, b| b.spend.partial_cmp(&a.spend).unwrap_or(std::cmp::Ordering::Equal));
+    peak_days.truncate(PEAK_DAYS);
+
+    let pace = series.first_day.filter(|_| series.budget.is_some_and(|b| b > 0.0)).map(|first| {
+        let mut adherence = PaceAdherence::default();
+        for day in daily.iter().skip(first) {
+            let Some(target) = day.pace_target.filter(|t| *t > 0.0) else { continue };
+            let delta = (day.cumulative - target) / target * 100.0;
+            match pace_status_from_delta(delta, threshold) {
+                "ahead" => adherence.days_ahead += 1,
+                "behind" => adherence.days_behind += 1,
+                _ => adherence.days_on_track += 1,
+            }
+            adherence.final_delta_percent = Some(delta);
+        }
+        adherence
+    });
+
+    let basis_day = if complete { FORECAST_DAY.min(days_covered) } else { days_covered };
+    let forecast = series
+        .first_day
+        .filter(|first| *first < basis_day)
+        .and_then(|_| daily.get(basis_day - 1))
+        .map(|basis| {
+            let forecast = basis.cumulative / basis_day as f64 * days as f64;
+            Forecast {
+                basis: if complete {
+                    tr!("report.basis_day", basis_day)
+                } else {
+                    tr!("report.basis_to_date", basis_day, days)
+                },
+                forecast,
+                actual: cumulative,
+                delta_percent: (complete && forecast > 0.0).then(|| (cumulative - forecast) / forecast * 100.0),
+            }
+        });
+
+    KeyReport {
+        name: series.name,
+        key_fingerprint: series.key_fingerprint,
+        total_spend: cumulative,
+        budget: series.budget,
+        samples: series.samples,
+        daily,
+        peak_days,
+        pace,
+        forecast,
+    }
+}
+
+/// Report of `month` from its history entries; `seed` is the month before
+pub(crate) fn build_report(
+    settings: &AppSettings,
+    seed: &[HistoryEntry],
+    entries: &[HistoryEntry],
+    month: &str,
+    now: DateTime<Utc>,
+) -> Result<MonthlyReport, String> {
+    let start = month_start(month)?;
+    let days = days_in_month(start);
+    let current = now.format("%Y-%m").to_string();
+    if month > current.as_str() {
+        return Err(tr!("report.not_started", month));
+    }
+    let complete = month < current.as_str();
+    let days_covered = if complete { days as usize } else { now.day() as usize };
+
+    let mut series: Vec<Series> = Vec::new();
+    let series_for = |series: &mut Vec<Series>, fingerprint: &str| -> usize {
+        if let Some(index) = series.iter().position(|s| s.key_fingerprint.as_deref() == Some(fingerprint)) {
+            return index;
+        }
+        let name = settings
+            .api_keys
+            .iter()
+            .find(|k| key_fingerprint(&k.key) == fingerprint)
+            .map(|k| k.label.clone())
+            .unwrap_or_else(|| tr!("report.removed_key", fingerprint.get(..8).unwrap_or(fingerprint)));
+        series.push(Series {
+            name,
+            key_fingerprint: Some(fingerprint.to_string()),
+            spend: vec![0.0; days as usize],
+            budget: None,
+            samples: 0,
+            first_day: None,
+        });
+        series.len() - 1
+    };
+
+    for sample in history::samples(entries) {
+        let index = series_for(&mut series, &sample.key_fingerprint);
+        series[index].samples += 1;
+        if sample.budget.is_some() {
+            series[index].budget = sample.budget; // latest budget of the month
+        }
+    }
+    for delta in history::usage_deltas(seed, entries) {
+        let Some(day) = day_index(&delta.sample.timestamp, start, days) else { continue };
+        let index = series_for(&mut series, &delta.sample.key_fingerprint);
+        let from = day_index(&delta.previous.timestamp, start, days).unwrap_or(0);
+        let key = &mut series[index];
+        key.spend[day] += delta.delta;
+        key.first_day = Some(key.first_day.map_or(from, |first| first.min(from)));
+    }
+
+    // Keys in settings order, removed keys last
+    let position = |s: &Series| {
+        settings
+            .api_keys
+            .iter()
+            .position(|k| Some(key_fingerprint(&k.key)) == s.key_fingerprint)
+            .unwrap_or(usize::MAX)
+    };
+    series.sort_by_key(position);
+
+    // Keys can share account credits, so their budgets are not summed for the aggregate
+    let aggregate = Series {
+        name: tr!("report.all_keys"),
+        key_fingerprint: None,
+        spend: (0..days as usize).map(|day| series.iter().map(|s| s.spend[day]).sum()).collect(),
+        budget: None,
+        samples: series.iter().map(|s| s.samples).sum(),
+        first_day: series.iter().filter_map(|s| s.first_day).min(),
+    };
+
+    let threshold = settings.pace_warn_threshold.max(0.0);
+    Ok(MonthlyReport {
+        month: month.to_string(),
+        generated_at: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        complete,
+        days_in_month: days,
+        days_covered: days_covered as u32,
+        aggregate: key_report(aggregate, start, days, days_covered, complete, threshold),
+        keys: series
+            .into_iter()
+            .map(|s| key_report(s, start, days, days_covered, complete, threshold))
+            .collect(),
+        projects: history::summarize_samples(seed, entries, month),
+    })
+}
+
+/// Report of a month from the local history store
+pub(crate) fn generate(month: &str) -> Result<MonthlyReport, String> {
+    let settings = read_settings()?;
+    let seed = history::previous_month(month).map(|m| history::read_month(&m)).unwrap_or_default();
+    build_report(&settings, &seed, &history::read_month(month), month, Utc::now())
+}
+
+// ============================================================================
+// RENDERING
+// ============================================================================
+
+fn money(value: f64) -> String {
+    format!("${:.2}", value)
+}
+
+/// chrono has no localised month names without its locale tables
+const MONTH_KEYS: [&str; 12] = [
+    "month.january", "month.february", "month.march", "month.april", "month.may", "month.june",
+    "month.july", "month.august", "month.september", "month.october", "month.november", "month.december",
+];
+
+fn month_title(month: &str) -> String {
+    month_start(month)
+        .map(|start| format!("{} {}", i18n::text(i18n::language(), MONTH_KEYS[start.month0() as usize]), start.year()))
+        .unwrap_or_else(|_| month.to_string())
+}
+
+fn coverage_line(report: &MonthlyReport) -> String {
+    if report.complete {
+        tr!("report.complete", report.days_in_month)
+    } else {
+        tr!("report.in_progress", report.days_covered, report.days_in_month)
+    }
+}
+
+fn pace_line(pace: &PaceAdherence) -> String {
+    let end = pace
+        .final_delta_percent
+        .map(|d| tr!("report.pace_last_day", format!("{:+.0}%", d)))
+        .unwrap_or_default();
+    tr!("report.pace", pace.days_on_track, pace.days_behind, pace.days_ahead, end)
+}
+
+fn forecast_line(forecast: &Forecast, complete: bool) -> String {
+    let delta = forecast
+        .delta_percent
+        .map(|d| format!(" ({:+.0}%)", d))
+        .unwrap_or_default();
+    let (predicted, actual) = (money(forecast.forecast), money(forecast.actual));
+    if complete {
+        tr!("report.forecast_actual", predicted, forecast.basis, actual, delta)
+    } else {
+        tr!("report.forecast_so_far", predicted, forecast.basis, actual, delta)
+    }
+}
+
+fn budget_suffix(key: &KeyReport) -> String {
+    key.budget.map(|b| tr!("report.of_budget", money(b))).unwrap_or_default()
+}
+
+fn peak_days_line(key: &KeyReport) -> String {
+    let peaks: Vec<String> = key.peak_days.iter().map(|d| format!("{} ({})", d.date, money(d.spend))).collect();
+    peaks.join(", ")
+}
+
+fn markdown_section(out: &mut String, key: &KeyReport, complete: bool) {
+    out.push_str(&format!("\n## {}\n\n", key.name.replace('|', "/")));
+    if key.samples == 0 {
+        out.push_str(&format!("{}\n", tr!("report.no_samples")));
+        return;
+    }
+    out.push_str(&format!("- **{}:** {}{}\n", tr!("report.total_spend"), money(key.total_spend), budget_suffix(key)));
+    if let Some(pace) = &key.pace {
+        out.push_str(&format!("- **{}:** {}\n", tr!("report.pace_label"), pace_line(pace)));
+    }
+    if let Some(forecast) = &key.forecast {
+        out.push_str(&format!("- **{}:** {}\n", tr!("report.forecast_label"), forecast_line(forecast, complete)));
+    }
+    if !key.peak_days.is_empty() {
+        out.push_str(&format!("- **{}:** {}\n", tr!("report.peak_days"), peak_days_line(key)));
+    }
+
+    out.push_str(&format!(
+        "\n| {} | {} | {} | {} |\n|---|---:|---:|---:|\n",
+        tr!("report.date"),
+        tr!("report.spend"),
+        tr!("report.cumulative"),
+        tr!("report.pace_target")
+    ));
+    for day in &key.daily {
+        let target = day.pace_target.map(money).unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!("| {} | {} | {} | {} |\n", day.date, money(day.spend), money(day.cumulative), target));
+    }
+}
+
+fn render_markdown(report: &MonthlyReport) -> String {
+    let mut out = format!(
+        "# {}\n\n{}\n",
+        tr!("report.title", month_title(&report.month)),
+        tr!("report.generated", report.generated_at, coverage_line(report))
+    );
+    markdown_section(&mut out, &report.aggregate, report.complete);
+    for key in &report.keys {
+        markdown_section(&mut out, key, report.complete);
+    }
+
+    if report.projects.total_cost > 0.0 {
+        out.push_str(&format!(
+            "\n## {}\n\n| {} | {} |\n|---|---:|\n",
+            tr!("report.projects"),
+            tr!("report.project"),
+            tr!("report.spend")
+        ));
+        for project in &report.projects.by_project {
+            out.push_str(&format!("| {} | {} |\n", project.name.replace('|', "/"), money(project.cost)));
+        }
+        out.push_str(&format!("| {} | {} |\n", tr!("report.no_project"), money(report.projects.untagged_cost)));
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One row per key and day plus a "total" row per key; amounts in USD without symbol
+fn render_csv(report: &MonthlyReport) -> String {
+    let mut out = String::from("scope,date,spend_usd,cumulative_usd,pace_target_usd\n");
+    for key in std::iter::once(&report.aggregate).chain(&report.keys) {
+        let scope = csv_field(&key.name);
+        for day in &key.daily {
+            let target = day.pace_target.map(|t| format!("{:.4}", t)).unwrap_or_default();
+            out.push_str(&format!("{},{},{:.4},{:.4},{}\n", scope, day.date, day.spend, day.cumulative, target));
+        }
+        let budget = key.budget.map(|b| format!("{:.4}", b)).unwrap_or_default();
+        out.push_str(&format!("{},total,{:.4},{:.4},{}\n", scope, key.total_spend, key.total_spend, budget));
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_section(out: &mut String, key: &KeyReport, complete: bool) {
+    out.push_str(&format!("<section>\n<h2>{}</h2>\n", escape_html(&key.name)));
+    if key.samples == 0 {
+        out.push_str(&format!("<p class=\"muted\">{}</p>\n</section>\n", escape_html(&tr!("report.no_samples"))));
+        return;
+    }
+    out.push_str("<dl>\n");
+    out.push_str(&format!(
+        "<dt>{}</dt><dd><strong>{}</strong>{}</dd>\n",
+        escape_html(&tr!("report.total_spend")),
+        money(key.total_spend),
+        escape_html(&budget_suffix(key))
+    ));
+    if let Some(pace) = &key.pace {
+        out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(&tr!("report.pace_label")), escape_html(&pace_line(pace))));
+    }
+    if let Some(forecast) = &key.forecast {
+        out.push_str(&format!(
+            "<dt>{}</dt><dd>{}</dd>\n",
+            escape_html(&tr!("report.forecast_label")),
+            escape_html(&forecast_line(forecast, complete))
+        ));
+    }
+    if !key.peak_days.is_empty() {
+        out.push_str(&format!("<dt>{}</dt><dd>{}</dd>\n", escape_html(&tr!("report.peak_days")), peak_days_line(key)));
+    }
+    out.push_str("</dl>\n");
+
+    // Daily bars, scaled to the peak day
+    let peak = key.daily.iter().map(|d| d.spend).fold(0.0, f64::max);
+    out.push_str("<div class=\"chart\">\n");
+    for day in &key.daily {
+        let height = if peak > 0.0 { day.spend / peak * 100.0 } else { 0.0 };
+        out.push_str(&format!(
+            "<div class=\"bar\" style=\"height:{:.1}%\" title=\"{}: {}\"></div>\n",
+            height,
+            day.date,
+            money(day.spend)
+        ));
+    }
+    out.push_str(&format!(
+        "</div>\n<table>\n<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>\n",
+        escape_html(&tr!("report.date")),
+        escape_html(&tr!("report.spend")),
+        escape_html(&tr!("report.cumulative")),
+        escape_html(&tr!("report.pace_target"))
+    ));
+    for day in &key.daily {
+        let target = day.pace_target.map(money).unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            day.date,
+            money(day.spend),
+            money(day.cumulative),
+            target
+        ));
+    }
+    out.push_str("</table>\n</section>\n");
+}
+
+const HTML_STYLE: &str = "body{font:14px -apple-system,BlinkMacSystemFont,\"Segoe UI\",sans-serif;color:#1f2937;max-width:860px;margin:2rem auto;padding:0 1rem}\
+h1{font-size:1.5rem}h2{font-size:1.15rem;margin-top:2rem;border-bottom:1px solid #e5e7eb;padding-bottom:.25rem}\
+.muted{color:#6b7280}dl{display:grid;grid-template-columns:max-content 1fr;gap:.25rem 1rem}dt{color:#6b7280}dd{margin:0}\
+.chart{display:flex;align-items:flex-end;gap:2px;height:120px;margin:1rem 0;border-bottom:1px solid #d1d5db}\
+.bar{flex:1;background:#10b981;min-height:1px}table{border-collapse:collapse;width:100%;font-variant-numeric:tabular-nums}\
+th,td{padding:.2rem .5rem;border-bottom:1px solid #f3f4f6;text-align:right}th:first-child,td:first-child{text-align:left}\
+@media print{.chart{break-inside:avoid}}";
+
+fn render_html(report: &MonthlyReport) -> String {
+    let title = escape_html(&tr!("report.title", month_title(&report.month)));
+    let lang = match i18n::language() {
+        Lang::En => "en",
+        Lang::De => "de",
+    };
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<p class=\"muted\">{}</p>\n",
+        lang,
+        title,
+        HTML_STYLE,
+        title,
+        escape_html(&tr!("report.generated", report.generated_at, coverage_line(report)))
+    );
+    html_section(&mut out, &report.aggregate, report.complete);
+    for key in &report.keys {
+        html_section(&mut out, key, report.complete);
+    }
+
+    if report.projects.total_cost > 0.0 {
+        out.push_str(&format!(
+            "<section>\n<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>{}</th></tr>\n",
+            escape_html(&tr!("report.projects")),
+            escape_html(&tr!("report.project")),
+            escape_html(&tr!("report.spend"))
+        ));
+        for project in &report.projects.by_project {
+            out.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_html(&project.name), money(project.cost)));
+        }
+        out.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&tr!("report.no_project")),
+            money(report.projects.untagged_cost)
+        ));
+        out.push_str("</table>\n</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+pub(crate) fn render(report: &MonthlyReport, format: &str) -> Result<String, String> {
+    match format {
+        "md" | "markdown" => Ok(render_markdown(report)),
+        "csv" => Ok(render_csv(report)),
+        "html" => Ok(render_html(report)),
+        other => Err(tr!("report.unknown_format", other)),
+    }
+}
+
+// ============================================================================
+// FILES
+// ============================================================================
+
+/// ~/Documents/BP-ESC Reports, or the config directory without a Documents folder
+pub(crate) fn default_dir() -> Result<PathBuf, String> {
+    match dirs::document_dir() {
+        Some(documents) => Ok(documents.join("BP-ESC Reports")),
+        None => Ok(get_config_dir()?.join("reports")),
+    }
+}
+
+/// Write `bp-esc-report-<month>.<format>` for each format into `dir`
+pub(crate) fn write_files(report: &MonthlyReport, dir: &Path, formats: &[&str]) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| tr!("report.create_dir", e))?;
+    formats
+        .iter()
+        .map(|format| {
+            let extension = if *format == "markdown" { "md" } else { format };
+            let path = dir.join(format!("bp-esc-report-{}.{}", report.month, extension));
+            fs::write(&path, render(report, format)?)
+                .map_err(|e| tr!("file.write", path.display(), e))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+fn generate_files(month: &str, formats: &[&str]) -> Result<Vec<PathBuf>, String> {
+    let report = generate(month)?;
+    let files = write_files(&report, &default_dir()?, formats)?;
+    let _ = write_log(&format!("[Reports] {} written to {}", month, default_dir()?.display()));
+    Ok(files)
+}
+
+// ============================================================================
+// TRAY
+// ============================================================================
+
+pub(crate) fn tray_submenu(app: &AppHandle) -> tauri::Result<Submenu<Wry>> {
+    let this_month = MenuItemBuilder::with_id(MENU_THIS_MONTH, tr!("tray.report_this_month")).build(app)?;
+    let last_month = MenuItemBuilder::with_id(MENU_LAST_MONTH, tr!("tray.report_last_month")).build(app)?;
+    Submenu::with_items(app, tr!("tray.report"), true, &[&this_month, &last_month])
+}
+
+/// Generate all formats for the clicked month and open the HTML version
+pub(crate) fn handle_menu_event(id: &str) {
+    let current = history::current_month();
+    let month = match id {
+        MENU_THIS_MONTH => current,
+        MENU_LAST_MONTH => history::previous_month(&current).unwrap_or(current),
+        _ => return,
+    };
+    match generate_files(&month, &FORMATS) {
+        Ok(files) => {
+            if let Some(html) = files.iter().find(|f| f.extension().is_some_and(|e| e == "html")) {
+                let _ = open::that(html);
+            }
+        }
+        Err(e) => {
+            let _ = write_log(&format!("[Reports] {}", e));
+        }
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Write the report of `month` (default: current) in the given formats (default: all)
+#[tauri::command]
+pub fn generate_report(month: Option<String>, formats: Option<Vec<String>>, open: Option<bool>) -> Result<GeneratedReport, String> {
+    let month = month.unwrap_or_else(history::current_month);
+    let formats: Vec<&str> = match &formats {
+        Some(formats) if !formats.is_empty() => formats.iter().map(String::as_str).collect(),
+        _ => FORMATS.to_vec(),
+    };
+    let files = generate_files(&month, &formats)?;
+    if open.unwrap_or(false) {
+        if let Some(first) = files.first() {
+            open::that(first).map_err(|e| tr!("report.open", first.display(), e))?;
+        }
+    }
+    Ok(GeneratedReport {
+        month,
+        files: files.iter().map(|f| f.display().to_string()).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::BalanceSample;
+    use crate::ApiKeyConfig;
+
+    const KEY: &str = "sk-or-v1-reportreportreportreport";
+
+    fn sample(timestamp: &str, fingerprint: &str, usage: f64, budget: Option<f64>) -> HistoryEntry {
+        HistoryEntry::Sample(BalanceSample {
+            timestamp: timestamp.to_string(),
+            key_fingerprint: fingerprint.to_string(),
+            usage,
+            budget,
+            project: None,
+        })
+    }
+
+    fn settings() -> AppSettings {
+        let mut settings = AppSettings::default();
+        settings.api_keys.push(ApiKeyConfig::new(KEY.to_string(), "Work".to_string()));
+        settings.pace_warn_threshold = 20.0;
+        settings
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-10-19T12:00:00Z").unwrap().with_timezone(&Utc)
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    /// September 2026 (30 days) with a $300 budget, i.e. a $10/day pace target
+    fn september() -> MonthlyReport {
+        let fp = key_fingerprint(KEY);
+        let seed = [sample("2026-08-31T23:00:00Z", &fp, 100.0, Some(300.0))];
+        let entries = [
+            sample("2026-09-01T12:00:00Z", &fp, 110.0, None),
+            sample("2026-09-02T12:00:00Z", &fp, 140.0, None),
+            sample("2026-09-03T12:00:00Z", &fp, 145.0, None),
+            sample("2026-09-10T12:00:00Z", &fp, 185.0, None),
+            sample("2026-09-15T12:00:00Z", &fp, 215.0, None),
+            sample("2026-09-30T12:00:00Z", &fp, 235.0, Some(300.0)),
+            // A key that is no longer configured and starts mid-month
+            sample("2026-09-20T08:00:00Z", "removed", 50.0, None),
+            sample("2026-09-21T08:00:00Z", "removed", 56.0, None),
+        ];
+        build_report(&settings(), &seed, &entries, "2026-09", now()).unwrap()
+    }
+
+    #[test]
+    fn daily_series_and_peak_days() {
+        let report = september();
+        assert!(report.complete);
+        assert_eq!((report.days_in_month, report.days_covered), (30, 30));
+        assert_eq!(report.keys.len(), 2);
+
+        let work = &report.keys[0];
+        assert_eq!(work.name, "Work");
+        assert_eq!(work.samples, 6);
+        assert_eq!(work.budget, Some(300.0));
+        assert_eq!(work.daily.len(), 30);
+        let spend: Vec<(usize, f64)> = work.daily.iter().enumerate().filter(|(_, d)| d.spend > 0.0).map(|(i, d)| (i, d.spend)).collect();
+        // The first day is seeded from August's last sample
+        assert_eq!(spend, vec![(0, 10.0), (1, 30.0), (2, 5.0), (9, 40.0), (14, 30.0), (29, 20.0)]);
+        assert_eq!(work.daily[0].date, "2026-09-01");
+        assert_eq!(work.daily[14].cumulative, 115.0);
+        assert_close(work.daily[14].pace_target.unwrap(), 150.0);
+        assert_eq!(work.total_spend, 135.0);
+
+        let peaks: Vec<(&str, f64)> = work.peak_days.iter().map(|d| (d.date.as_str(), d.spend)).collect();
+        assert_eq!(peaks, vec![("2026-09-10", 40.0), ("2026-09-02", 30.0), ("2026-09-15", 30.0)]);
+
+        let removed = &report.keys[1];
+        assert_eq!(removed.key_fingerprint.as_deref(), Some("removed"));
+        assert_eq!(removed.total_spend, 6.0);
+        assert_eq!(report.aggregate.total_spend, 141.0);
+        assert_eq!(report.aggregate.daily[20].spend, 6.0);
+        assert_eq!(report.aggregate.budget, None);
+    }
+
+    #[test]
+    fn pace_adherence_counts_days_per_status() {
+        let report = september();
+        let pace = report.keys[0].pace.as_ref().unwrap();
+        // Day 1 on target, days 2-3 over the 20% threshold, day 4 slightly over, then under
+        assert_eq!((pace.days_on_track, pace.days_behind, pace.days_ahead), (27, 1, 2));
+        assert_close(pace.final_delta_percent.unwrap(), -55.0);
+        // Without a budget there is no pace to adhere to
+        assert!(report.keys[1].pace.is_none());
+        assert!(report.aggregate.pace.is_none());
+    }
+
+    #[test]
+    fn forecast_from_the_run_rate_to_the_forecast_day() {
+        let report = september();
+        let forecast = report.keys[0].forecast.as_ref().unwrap();
+        // $115 by day 15 extrapolates to $230 for 30 days
+        assert_close(forecast.forecast, 115.0 / FORECAST_DAY as f64 * 30.0);
+        assert_eq!(forecast.actual, 135.0);
+        assert_close(forecast.delta_percent.unwrap(), (135.0 - 230.0) / 230.0 * 100.0);
+        // No data before the forecast day, nothing to extrapolate
+        assert!(report.keys[1].forecast.is_none());
+        assert_eq!(report.aggregate.forecast.as_ref().unwrap().actual, 141.0);
+    }
+
+    #[test]
+    fn running_month_forecasts_from_the_days_so_far() {
+        let fp = key_fingerprint(KEY);
+        let entries = [
+            sample("2026-10-01T06:00:00Z", &fp, 0.0, Some(310.0)),
+            sample("2026-10-19T06:00:00Z", &fp, 38.0, Some(310.0)),
+        ];
+        let report = build_report(&settings(), &[], &entries, "2026-10", now()).unwrap();
+        assert!(!report.complete);
+        assert_eq!(report.days_covered, 19);
+        let work = &report.keys[0];
+        assert_eq!(work.daily.len(), 19);
+        let forecast = work.forecast.as_ref().unwrap();
+        assert_close(forecast.forecast, 38.0 / 19.0 * 31.0);
+        assert_eq!(forecast.delta_percent, None);
+
+        assert!(build_report(&settings(), &[], &entries, "2026-11", now()).is_err());
+    }
+
+    #[test]
+    fn csv_fields_with_separators_or_line_breaks_are_quoted() {
+        assert_eq!(csv_field("Work"), "Work");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+        assert_eq!(csv_field("a\rb"), "\"a\rb\"");
+    }
+}
@@ -0,0 +1,222 @@
+// Team keys
+// Provisioning-key mode: list, create, limit and disable the OpenRouter keys handed
+// out to team members. Every member gets the same pace calculation as the active key.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::activity::configured_provisioning_key;
+use crate::i18n::tr;
+use crate::{build_balance_data, BalanceData, OpenRouterData};
+
+const KEYS_URL: &str = "https://openrouter.ai/api/v1/keys";
+
+/// One entry of /api/v1/keys; usage fields match /api/v1/key
+#[derive(Debug, Deserialize)]
+struct KeyRecord {
+    hash: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(flatten)]
+    usage: OpenRouterData,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyListResponse {
+    #[serde(default)]
+    data: Vec<KeyRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyResponse {
+    data: KeyRecord,
+    /// Only present right after creation
+    #[serde(default)]
+    key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TeamMember {
+    pub hash: String,
+    pub name: String,
+    pub label: Option<String>,
+    pub disabled: bool,
+    pub created_at: Option<String>,
+    pub balance: BalanceData,
+}
+
+/// A new member key; the secret is returned by OpenRouter exactly once
+#[derive(Debug, Serialize, Clone)]
+pub struct CreatedTeamKey {
+    pub member: TeamMember,
+    pub key: String,
+}
+
+impl From<KeyRecord> for TeamMember {
+    fn from(record: KeyRecord) -> Self {
+        let label = record.usage.label.clone();
+        TeamMember {
+            hash: record.hash,
+            name: record.name,
+            label,
+            disabled: record.disabled,
+            created_at: record.created_at,
+            // Member keys have no view of the account, so pace is against their own limit
+            balance: build_balance_data(record.usage, None),
+        }
+    }
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .map_err(|_| tr!("net.client"))
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<String, String> {
+    let key = configured_provisioning_key()?;
+    let response = request
+        .header("Authorization", format!("Bearer {}", key))
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                tr!("net.timeout")
+            } else {
+                tr!("net.error", e)
+            }
+        })?;
+
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| tr!("net.body", e))?;
+
+    match status.as_u16() {
+        200..=299 => Ok(body),
+        401 | 403 => Err(tr!("team.needs_provisioning")),
+        404 => Err(tr!("team.not_found")),
+        _ => Err(tr!("team.status", status, body)),
+    }
+}
+
+fn parse<T: for<'de> Deserialize<'de>>(body: &str) -> Result<T, String> {
+    serde_json::from_str(body).map_err(|e| tr!("team.parse", e))
+}
+
+async fn patch_key(hash: &str, changes: Value) -> Result<TeamMember, String> {
+    let body = send(client()?.patch(format!("{}/{}", KEYS_URL, hash)).json(&changes)).await?;
+    Ok(parse::<KeyResponse>(&body)?.data.into())
+}
+
+// ============================================================================
+// OPERATIONS (shared by Tauri commands and the CLI)
+// ============================================================================
+
+pub(crate) async fn list_members(include_disabled: bool) -> Result<Vec<TeamMember>, String> {
+    let mut members: Vec<TeamMember> = Vec::new();
+    // The list endpoint pages by offset; stop once a page adds nothing new
+    loop {
+        let request = client()?.get(KEYS_URL).query(&[
+            ("include_disabled", include_disabled.to_string()),
+            ("offset", members.len().to_string()),
+        ]);
+        let page: Vec<TeamMember> = parse::<KeyListResponse>(&send(request).await?)?
+            .data
+            .into_iter()
+            .filter(|record| !members.iter().any(|m| m.hash == record.hash))
+            .map(TeamMember::from)
+            .collect();
+        if page.is_empty() {
+            break;
+        }
+        members.extend(page);
+    }
+    members.sort_by_key(|m| m.name.to_lowercase());
+    Ok(members)
+}
+
+/// NaN and infinity would reach OpenRouter as `null`, i.e. remove the limit
+pub(crate) fn check_limit(limit: Option<f64>) -> Result<(), String> {
+    match limit {
+        Some(l) if !l.is_finite() || l < 0.0 => Err(tr!("team.invalid_limit")),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) async fn create_member_key(name: &str, limit: Option<f64>) -> Result<CreatedTeamKey, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(tr!("team.name_required"));
+    }
+    check_limit(limit)?;
+
+    let body = send(client()?.post(KEYS_URL).json(&json!({ "name": name, "limit": limit }))).await?;
+    let response = parse::<KeyResponse>(&body)?;
+    let key = response
+        .key
+        .ok_or_else(|| tr!("team.no_key_returned"))?;
+    Ok(CreatedTeamKey { member: response.data.into(), key })
+}
+
+/// `None` removes the limit
+pub(crate) async fn set_member_limit(hash: &str, limit: Option<f64>) -> Result<TeamMember, String> {
+    check_limit(limit)?;
+    patch_key(hash, json!({ "limit": limit })).await
+}
+
+pub(crate) async fn set_member_disabled(hash: &str, disabled: bool) -> Result<TeamMember, String> {
+    patch_key(hash, json!({ "disabled": disabled })).await
+}
+
+/// Find a member by exact hash, unique hash prefix or case-insensitive name
+pub(crate) fn resolve_member<'a>(members: &'a [TeamMember], query: &str) -> Result<&'a TeamMember, String> {
+    if let Some(member) = members.iter().find(|m| m.hash == query) {
+        return Ok(member);
+    }
+    let matches: Vec<&TeamMember> = members
+        .iter()
+        .filter(|m| m.hash.starts_with(query) || m.name.eq_ignore_ascii_case(query))
+        .collect();
+    match matches.as_slice() {
+        [member] => Ok(member),
+        [] => Err(tr!("team.no_match", query)),
+        _ => Err(tr!("team.ambiguous", query, matches.len())),
+    }
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_team_keys(include_disabled: Option<bool>) -> Result<Vec<TeamMember>, String> {
+    list_members(include_disabled.unwrap_or(true)).await
+}
+
+#[tauri::command]
+pub async fn create_team_key(name: String, limit: Option<f64>) -> Result<CreatedTeamKey, String> {
+    create_member_key(&name, limit).await
+}
+
+#[tauri::command]
+pub async fn update_team_key_limit(hash: String, limit: Option<f64>) -> Result<TeamMember, String> {
+    set_member_limit(&hash, limit).await
+}
+
+#[tauri::command]
+pub async fn set_team_key_disabled(hash: String, disabled: bool) -> Result<TeamMember, String> {
+    set_member_disabled(&hash, disabled).await
+}
@@ -0,0 +1,233 @@
+// Tray tooltip
+// The menubar icon is a bitmap, so hovering it and VoiceOver get the same state as text:
+// "OpenRouter · 62% remaining this month · 8% ahead of pace · updated 14:05". Set on
+// every redraw of the icon, including the frames of the mood blink.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::{self, tr};
+use crate::menubar_format::{self, FormatContext};
+use crate::number_format::NumberFormat;
+use crate::{menubar_icon, read_settings, AppSettings, BalanceData, MenubarState, MoodBlinkState};
+
+/// One line describing the balance as the menubar shows it
+pub(crate) fn describe(
+    balance: &BalanceData,
+    settings: &AppSettings,
+    format: &NumberFormat,
+    days_in_month: u32,
+    refreshed_at: Option<DateTime<Local>>,
+    mood_due: bool,
+) -> String {
+    let label = settings
+        .api_keys
+        .get(settings.active_api_key_index)
+        .map(|key| key.label.clone())
+        .or_else(|| balance.label.clone())
+        .unwrap_or_else(|| "OpenRouter".to_string());
+    let mut parts = vec![label];
+
+    if balance.has_data() {
+        let ctx = FormatContext {
+            balance,
+            settings,
+            format,
+            days_in_month,
+            lang: i18n::resolve(&settings.language),
+        };
+        let (used, budget, left) = menubar_format::timeframe_amounts(&ctx);
+        let shown = if settings.show_remaining { left } else { Some(used) };
+        let amount = match (settings.show_percentage, shown, budget) {
+            (true, Some(shown), Some(budget)) => format!("{}%", format.number((shown / budget * 100.0).round() + 0.0, 0)),
+            // Without a budget the percentage has no base; name the amount used instead
+            (true, _, _) => format.money(used, 2),
+            (false, Some(shown), _) => format.money(shown, 2),
+            (false, None, _) => format.money(used, 2),
+        };
+        let remaining = settings.show_remaining && budget.is_some();
+        parts.push(match (settings.menubar_timeframe.as_str(), remaining) {
+            ("weekly", true) => tr!("status.remaining_week", amount),
+            ("weekly", false) => tr!("status.used_week", amount),
+            ("daily", true) => tr!("status.remaining_day", amount),
+            ("daily", false) => tr!("status.used_day", amount),
+            (_, true) => tr!("status.remaining_month", amount),
+            (_, false) => tr!("status.used_month", amount),
+        });
+
+        let delta = match settings.menubar_timeframe.as_str() {
+            "weekly" => balance.pace_week_delta_percent,
+            "daily" => balance.pace_day_delta_percent,
+            _ => balance.pace_month_delta_percent,
+        };
+        if let Some(delta) = delta.filter(|_| budget.is_some()) {
+            parts.push(pace_text(delta));
+        }
+    } else {
+        parts.push(tr!("status.no_data"));
+    }
+
+    if let Some(at) = refreshed_at {
+        parts.push(tr!("status.updated", at.format("%H:%M")));
+    }
+    if mood_due {
+        parts.push(tr!("status.mood_due"));
+    }
+    parts.join(" · ")
+}
+
+/// "8% ahead of pace" / "3% behind pace" / "on pace"
+pub(crate) fn pace_text(delta: f64) -> String {
+    if delta.abs() < 0.5 {
+        tr!("pace.on")
+    } else if delta > 0.0 {
+        tr!("pace.ahead", format!("{:.0}", delta))
+    } else {
+        tr!("pace.behind", format!("{:.0}", -delta))
+    }
+}
+
+/// Remember when the active key's balance was last fetched from OpenRouter
+pub(crate) fn mark_refreshed(app: &AppHandle, api_key: &str) {
+    let Ok(settings) = read_settings() else { return };
+    let is_active = settings
+        .api_keys
+        .get(settings.active_api_key_index)
+        .is_some_and(|key| key.key.trim() == api_key.trim());
+    if let (true, Some(state)) = (is_active, app.try_state::<MenubarState>()) {
+        if let Ok(mut refreshed_at) = state.refreshed_at.lock() {
+            *refreshed_at = Some(Local::now());
+        }
+    }
+}
+
+/// Describe the cached menubar state on the tray icon (safe from any thread)
+pub(crate) fn refresh(app: &AppHandle) {
+    let Some(state) = app.try_state::<MenubarState>() else { return };
+    let balance = state.balance.lock().ok().and_then(|b| b.clone());
+    let settings = state.settings.lock().ok().and_then(|s| s.clone());
+    let (Some(balance), Some(settings)) = (balance, settings) else { return };
+    let refreshed_at = state.refreshed_at.lock().ok().and_then(|at| *at);
+    let mood_due = app
+        .try_state::<MoodBlinkState>()
+        .and_then(|s| s.is_blinking.lock().ok().map(|b| *b))
+        .unwrap_or(false);
+
+    let format = NumberFormat::from_settings(&settings);
+    let days_in_month = menubar_icon::days_in_month(Local::now().date_naive());
+    let text = describe(&balance, &settings, &format, days_in_month, refreshed_at, mood_due);
+    if let Some(tray) = app.tray_by_id("main-tray") {
+        apply(&tray, text);
+    }
+}
+
+/// Text last set per tray id; the mood blink asks every second, mostly for the same text
+static APPLIED: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Set a tray icon's tooltip and, on macOS, its VoiceOver label (skipped when unchanged:
+/// each call is a round trip to the main thread)
+pub(crate) fn apply(tray: &tauri::tray::TrayIcon, text: String) {
+    if let Ok(mut applied) = APPLIED.lock() {
+        let id: &str = tray.id().as_ref();
+        if applied.get(id) == Some(&text) {
+            return;
+        }
+        applied.insert(id.to_string(), text.clone());
+    }
+    let _ = tray.set_tooltip(Some(text.as_str()));
+    #[cfg(target_os = "macos")]
+    set_accessibility_label(tray, text);
+}
+
+/// Drop the remembered text of a removed tray, so a new one with the same id gets its own
+pub(crate) fn forget(tray_id: &str) {
+    if let Ok(mut applied) = APPLIED.lock() {
+        applied.remove(tray_id);
+    }
+}
+
+/// VoiceOver reads the status item button's label, not its tooltip
+#[cfg(target_os = "macos")]
+fn set_accessibility_label(tray: &tauri::tray::TrayIcon, text: String) {
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+
+    let _ = tray.with_inner_tray_icon(move |inner| {
+        let Some(item) = inner.ns_status_item() else { return };
+        unsafe {
+            let item = &*item as *const _ as id;
+            let button: id = msg_send![item, button];
+            if button.is_null() {
+                return;
+            }
+            let label = NSString::alloc(nil).init_str(&text);
+            let _: () = msg_send![button, setAccessibilityLabel: label];
+            // The button copies the label; drop our +1 from alloc
+            let _: () = msg_send![label, release];
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            number_locale: "en".to_string(),
+            api_keys: vec![crate::ApiKeyConfig::new("sk-or-v1-test".to_string(), "Work".to_string())],
+            ..Default::default()
+        }
+    }
+
+    fn balance() -> BalanceData {
+        BalanceData {
+            limit: Some(100.0),
+            usage: Some(38.0),
+            usage_monthly: Some(38.0),
+            usage_weekly: Some(10.0),
+            remaining: Some(62.0),
+            pace_month_delta_percent: Some(8.2),
+            pace_week_delta_percent: Some(-3.0),
+            ..Default::default()
+        }
+    }
+
+    fn describe_with(settings: &AppSettings, balance: &BalanceData, mood_due: bool) -> String {
+        let format = NumberFormat::from_settings(settings);
+        let at = Local.with_ymd_and_hms(2026, 3, 9, 14, 5, 0).single();
+        describe(balance, settings, &format, 30, at, mood_due)
+    }
+
+    #[test]
+    fn describes_the_selected_timeframe() {
+        let monthly = describe_with(&settings(), &balance(), false);
+        assert_eq!(monthly, "Work · 62% remaining this month · 8% ahead of pace · updated 14:05");
+
+        let weekly = AppSettings {
+            menubar_timeframe: "weekly".to_string(),
+            show_percentage: false,
+            show_remaining: false,
+            ..settings()
+        };
+        assert_eq!(
+            describe_with(&weekly, &balance(), true),
+            "Work · $10.00 used this week · 3% behind pace · updated 14:05 · mood entry due"
+        );
+    }
+
+    #[test]
+    fn says_when_there_is_nothing_to_show() {
+        let empty = describe_with(&AppSettings::default(), &BalanceData::default(), false);
+        assert_eq!(empty, "OpenRouter · no balance yet · updated 14:05");
+
+        // No limit: nothing to take a percentage or pace of
+        let unlimited = BalanceData { limit: None, remaining: None, ..balance() };
+        assert_eq!(describe_with(&settings(), &unlimited, false), "Work · $38.00 used this month · updated 14:05");
+    }
+}
@@ -11,7 +11,9 @@ window.addEventListener('DOMContentLoaded', () => {
   // State
   let currentSettings = null;
   let currentBalance = null;
+  let numberFormat = null; // resolved by Rust (get_number_format): separators, compact, currency
   let sortableInstance = null; // SortableJS instance for API key reordering
+  let keyRotationReminders = []; // [{ index, label, due_date, days_left, has_successor }]
   let toastTimeoutId = null;
 
   // DOM elements - States
@@ -88,6 +90,13 @@ window.addEventListener('DOMContentLoaded', () => {
   const limitValue = document.getElementById('limitValue');
   const usageValue = document.getElementById('usageValue');
   const remainingValue = document.getElementById('remainingValue');
+  const accountCreditsItem = document.getElementById('accountCreditsItem');
+  const accountCreditsValue = document.getElementById('accountCreditsValue');
+  const keyInfoLine = document.getElementById('keyInfoLine');
+  const freeTierWarned = new Set(); // key labels already warned this session
+  const modelBreakdown = document.getElementById('modelBreakdown');
+  const modelBreakdownTitle = document.getElementById('modelBreakdownTitle');
+  const modelBreakdownRows = document.getElementById('modelBreakdownRows');
   const usageMonthValue = document.getElementById('usageMonthValue');
   const usageWeekValue = document.getElementById('usageWeekValue');
   const usageDayValue = document.getElementById('usageDayValue');
@@ -140,6 +149,15 @@ window.addEventListener('DOMContentLoaded', () => {
   const timeframeMonthly = document.getElementById('timeframeMonthly');
   const timeframeWeekly = document.getElementById('timeframeWeekly');
   const timeframeDaily = document.getElementById('timeframeDaily');
+  const iconStyleButtons = document.querySelectorAll('#iconStyleGroup [data-icon-style]');
+  const paceMarkerToggle = document.getElementById('paceMarkerToggle');
+  const menubarTemplateInput = document.getElementById('menubarTemplateInput');
+  const numberLocaleButtons = document.querySelectorAll('#numberLocaleGroup [data-number-locale]');
+  const languageButtons = document.querySelectorAll('#languageGroup [data-language]');
+  const compactNumbersToggle = document.getElementById('compactNumbersToggle');
+  const displayCurrencyInput = document.getElementById('displayCurrencyInput');
+  const exchangeRateInput = document.getElementById('exchangeRateInput');
+  const menubarTemplatePreview = document.getElementById('menubarTemplatePreview');
   const menubarMonochromeToggle = document.getElementById('menubarMonochromeToggle');
   const paceWarnValue = document.getElementById('paceWarnValue');
   const paceWarnMinus = document.getElementById('paceWarnMinus');
@@ -156,6 +174,23 @@ window.addEventListener('DOMContentLoaded', () => {
   const moreBtn = document.getElementById('moreBtn');
   const moreMenu = document.getElementById('moreMenu');
   const copyKeyLabel = document.getElementById('copyKeyLabel');
+  const pushKeyLabel = document.getElementById('pushKeyLabel');
+  const opencodeSyncToggle = document.getElementById('opencodeSyncToggle');
+  const envExportToggle = document.getElementById('envExportToggle');
+  const envExportSnippets = document.getElementById('envExportSnippets');
+  const proxyToggle = document.getElementById('proxyToggle');
+  const proxyPortInput = document.getElementById('proxyPortInput');
+  const proxyDetails = document.getElementById('proxyDetails');
+  const proxyGuardSettings = document.getElementById('proxyGuardSettings');
+  const proxyGuardToggle = document.getElementById('proxyGuardToggle');
+  const proxyGuardPaceInput = document.getElementById('proxyGuardPaceInput');
+  const proxyAllowedModelsInput = document.getElementById('proxyAllowedModelsInput');
+  const proxyProjectCapsInput = document.getElementById('proxyProjectCapsInput');
+  const saveProxyGuardBtn = document.getElementById('saveProxyGuardBtn');
+  const overrideProxyGuardBtn = document.getElementById('overrideProxyGuardBtn');
+  const proxyGuardOverrideInfo = document.getElementById('proxyGuardOverrideInfo');
+  const provisioningKeyInput = document.getElementById('provisioningKeyInput');
+  const saveProvisioningKeyBtn = document.getElementById('saveProvisioningKeyBtn');
   const quitBtn = document.getElementById('quitBtn');
   const hideBtn = document.getElementById('hideBtn');
   const prevKeyBtn = document.getElementById('prevKeyBtn');
@@ -168,10 +203,29 @@ window.addEventListener('DOMContentLoaded', () => {
   const confirmCancel = document.getElementById('confirmCancel');
   const confirmOk = document.getElementById('confirmOk');
   let confirmCallback = null;
+  let confirmCancelCallback = null;
 
   const apiKeyList = document.getElementById('apiKeyList');
   const newApiKeyInput = document.getElementById('newApiKeyInput');
   const addApiKeyBtn = document.getElementById('addApiKeyBtn');
+  const discoverKeysBtn = document.getElementById('discoverKeysBtn');
+  const discoveredKeyList = document.getElementById('discoveredKeyList');
+  const importDiscoveredBtn = document.getElementById('importDiscoveredBtn');
+
+  function createKeySelect(className, title, options, value) {
+    const select = document.createElement('select');
+    select.className = className;
+    select.title = title;
+    options.forEach(([optionValue, text]) => {
+      const option = document.createElement('option');
+      option.value = optionValue;
+      option.textContent = text;
+      select.appendChild(option);
+    });
+    select.value = value;
+    select.onclick = (e) => e.stopPropagation();
+    return select;
+  }
 
   function renderApiKeyList() {
     if (!currentSettings || !apiKeyList) return;
@@ -202,6 +256,7 @@ window.addEventListener('DOMContentLoaded', () => {
       const label = document.createElement('span');
       label.className = 'api-key-label';
       label.textContent = api.label || `Key ${index + 1}`;
+      label.title = describeKeyVerification(api.verified);
       
       // Edit pencil icon (hover only)
       const editIcon = document.createElement('span');
@@ -269,6 +324,116 @@ window.addEventListener('DOMContentLoaded', () => {
         }
       };
 
+      // Rotate icon: swap in a new key while keeping label and position
+      const reminder = keyRotationReminders.find(r => r.index === index);
+      const rotateIcon = document.createElement('span');
+      rotateIcon.className = `api-key-edit-icon api-key-rotate-icon ${reminder ? 'rotation-due' : ''}`;
+      rotateIcon.innerHTML = '<span class="material-symbols-rounded">autorenew</span>';
+      rotateIcon.title = reminder
+        ? `Rotation due ${reminder.due_date}${reminder.has_successor ? ' (successor staged)' : ''}`
+        : 'Rotate key';
+      rotateIcon.onclick = (e) => {
+        e.stopPropagation();
+        const input = document.createElement('input');
+        input.type = 'password';
+        input.className = 'api-key-edit-input';
+        input.placeholder = api.successor_key ? 'Enter to use staged successor' : 'New key (sk-or-...)';
+
+        const rotate = async () => {
+          const currentIndex = parseInt(row.getAttribute('data-index'));
+          const newKey = input.value.trim();
+          if (!newKey && !api.successor_key) {
+            renderApiKeyList();
+            return;
+          }
+          showToast('Verifying new key...', 'info');
+          try {
+            currentSettings = await invoke('rotate_api_key', { index: currentIndex, newKey: newKey || null });
+            addLog(`Rotated API key: ${api.label}`);
+            showToast(`Key "${api.label}" rotated.`, 'success');
+            await loadKeyRotationReminders();
+            if (currentIndex === currentSettings.active_api_key_index) loadBalance();
+          } catch (err) {
+            showError(err);
+            renderApiKeyList();
+          }
+        };
+
+        input.onkeydown = (ke) => {
+          if (ke.key === 'Enter') rotate();
+          if (ke.key === 'Escape') renderApiKeyList();
+        };
+        input.onblur = () => { if (!input.value.trim()) renderApiKeyList(); };
+
+        label.replaceWith(input);
+        input.focus();
+      };
+
+      // Reset period fallback for keys whose limit OpenRouter reports without one
+      const resetSelect = document.createElement('select');
+      resetSelect.className = 'api-key-reset-select';
+      resetSelect.title = 'Limit reset period (used when OpenRouter reports none)';
+      [['', 'auto'], ['daily', 'daily'], ['weekly', 'weekly'], ['monthly', 'monthly']].forEach(([value, text]) => {
+        const option = document.createElement('option');
+        option.value = value;
+        option.textContent = text;
+        resetSelect.appendChild(option);
+      });
+      resetSelect.value = api.limit_reset || '';
+      resetSelect.onclick = (e) => e.stopPropagation();
+      resetSelect.onchange = async () => {
+        const currentIndex = parseInt(row.getAttribute('data-index'));
+        try {
+          currentSettings = await invoke('set_key_limit_reset', { index: currentIndex, period: resetSelect.value || null });
+          if (currentIndex === currentSettings.active_api_key_index) loadBalance();
+        } catch (err) {
+          showError(err);
+        }
+        renderApiKeyList();
+      };
+
+      // Pin icon: show this key in a menubar icon of its own, next to the main one
+      const savePin = async (pin) => {
+        const currentIndex = parseInt(row.getAttribute('data-index'));
+        try {
+          currentSettings = await invoke('set_key_pin', { index: currentIndex, pin });
+          addLog(pin ? `Pinned ${api.label} to the menubar` : `Unpinned ${api.label}`);
+        } catch (err) {
+          showError(err);
+        }
+        renderApiKeyList();
+      };
+      const pinIcon = document.createElement('span');
+      pinIcon.className = `api-key-edit-icon api-key-pin-icon ${api.pin ? 'pinned' : ''}`;
+      pinIcon.innerHTML = '<span class="material-symbols-rounded">push_pin</span>';
+      pinIcon.title = api.pin ? 'Unpin from menubar' : 'Pin to its own menubar icon';
+      pinIcon.onclick = (e) => {
+        e.stopPropagation();
+        // A new pin starts out like the main menubar icon
+        savePin(api.pin ? null : {
+          timeframe: currentSettings.menubar_timeframe,
+          show_percentage: currentSettings.show_percentage,
+          show_remaining: currentSettings.show_remaining,
+        });
+      };
+
+      const pinSelects = [];
+      if (api.pin) {
+        const timeframeSelect = createKeySelect('api-key-pin-select', 'Pinned icon timeframe',
+          [['monthly', 'month'], ['weekly', 'week'], ['daily', 'day']], api.pin.timeframe);
+        const viewSelect = createKeySelect('api-key-pin-select', 'Pinned icon value',
+          [['pct-left', '% left'], ['pct-used', '% used'], ['amount-left', 'amount left'], ['amount-used', 'amount used']],
+          `${api.pin.show_percentage ? 'pct' : 'amount'}-${api.pin.show_remaining ? 'left' : 'used'}`);
+        const onPinChange = () => savePin({
+          timeframe: timeframeSelect.value,
+          show_percentage: viewSelect.value.startsWith('pct'),
+          show_remaining: viewSelect.value.endsWith('left'),
+        });
+        timeframeSelect.onchange = onPinChange;
+        viewSelect.onchange = onPinChange;
+        pinSelects.push(timeframeSelect, viewSelect);
+      }
+
       const mask = document.createElement('span');
       mask.className = 'api-key-mask';
       mask.textContent = '••••' + api.key.slice(-4);
@@ -295,6 +460,10 @@ window.addEventListener('DOMContentLoaded', () => {
 
       row.appendChild(label);
       row.appendChild(editIcon);
+      row.appendChild(rotateIcon);
+      row.appendChild(resetSelect);
+      row.appendChild(pinIcon);
+      pinSelects.forEach(select => row.appendChild(select));
       row.appendChild(mask);
       row.appendChild(deleteBtn);
       apiKeyList.appendChild(row);
@@ -304,6 +473,15 @@ window.addEventListener('DOMContentLoaded', () => {
     initSortable();
   }
 
+  async function loadKeyRotationReminders() {
+    try {
+      keyRotationReminders = await invoke('get_key_rotation_reminders');
+    } catch (e) {
+      keyRotationReminders = [];
+    }
+    renderApiKeyList();
+  }
+
   // Initialize SortableJS for API key reordering
   function initSortable() {
     console.log('[SortableJS] initSortable called');
@@ -399,18 +577,16 @@ window.addEventListener('DOMContentLoaded', () => {
     }
 
     try {
-      const test = await testApiKey(key);
-      if (!test.valid) {
-        showError('Key validation failed: ' + test.error);
-        return;
-      }
-
-      const label = test.balance.label || `Key ${currentSettings.api_keys.length + 1}`;
-      currentSettings.api_keys.push({ key, label });
-      currentSettings.active_api_key_index = currentSettings.api_keys.length - 1;
+      // Backend verifies live, rejects duplicates and stores the server metadata
+      addLog(`Verifying API key: ${key.slice(0, 8)}...`);
+      currentSettings = await invoke('add_api_key', { key, label: null });
       newApiKeyInput.value = '';
-      
-      await saveSettingsAction(true);
+
+      const added = currentSettings.api_keys[currentSettings.active_api_key_index];
+      if (added?.verified?.is_provisioning_key) {
+        showToast('This is a provisioning key: it manages keys but cannot be used for completions', 'info', 6000);
+      }
+      currentBalance = null;
       renderApiKeyList();
       loadBalance();
     } catch (e) {
@@ -418,6 +594,305 @@ window.addEventListener('DOMContentLoaded', () => {
     }
   };
 
+  function describeKeyVerification(verified) {
+    if (!verified) return 'Not verified yet';
+    const parts = [`Verified ${verified.verified_at}`];
+    if (verified.server_label) parts.push(`server label "${verified.server_label}"`);
+    parts.push(verified.limit != null ? `limit ${formatCurrency(verified.limit)}` : 'no limit');
+    if (verified.is_provisioning_key) parts.push('provisioning key');
+    return parts.join(' · ');
+  }
+
+  function renderDiscoveredKeys(keys) {
+    discoveredKeyList.innerHTML = '';
+    keys.forEach(found => {
+      const row = document.createElement('label');
+      row.className = `discovered-key-row ${found.already_configured ? 'configured' : ''}`;
+
+      const checkbox = document.createElement('input');
+      checkbox.type = 'checkbox';
+      checkbox.value = found.fingerprint;
+      checkbox.checked = !found.already_configured;
+      checkbox.disabled = found.already_configured;
+      row.appendChild(checkbox);
+
+      const info = document.createElement('div');
+      const title = document.createElement('div');
+      title.textContent = found.already_configured
+        ? `${found.masked_key} — already added as "${found.existing_label}"`
+        : `${found.masked_key} → ${found.suggested_label}`;
+      info.appendChild(title);
+      found.sources.forEach(source => {
+        const line = document.createElement('div');
+        line.className = 'discovered-key-source';
+        line.textContent = `${source.tool}: ${source.path}`;
+        info.appendChild(line);
+      });
+      row.appendChild(info);
+
+      discoveredKeyList.appendChild(row);
+    });
+
+    const importable = keys.some(k => !k.already_configured);
+    discoveredKeyList.classList.toggle('hidden', keys.length === 0);
+    importDiscoveredBtn.classList.toggle('hidden', !importable);
+  }
+
+  if (discoverKeysBtn) {
+    discoverKeysBtn.onclick = async () => {
+      addLog('Scanning local tool configs for OpenRouter keys');
+      try {
+        const keys = await invoke('discover_api_keys');
+        renderDiscoveredKeys(keys);
+        if (keys.length === 0) {
+          showToast('No OpenRouter keys found in local tool configs', 'info');
+        } else if (!keys.some(k => !k.already_configured)) {
+          showToast('All keys found are already configured', 'info');
+        }
+      } catch (e) {
+        showError('Key discovery failed: ' + e);
+      }
+    };
+  }
+
+  if (importDiscoveredBtn) {
+    importDiscoveredBtn.onclick = async () => {
+      const fingerprints = Array.from(discoveredKeyList.querySelectorAll('input:checked'))
+        .map(cb => cb.value);
+      if (fingerprints.length === 0) return;
+
+      try {
+        const result = await invoke('import_discovered_keys', { fingerprints });
+        currentSettings = result.settings;
+        renderApiKeyList();
+        renderDiscoveredKeys([]);
+        result.failed.forEach(f => addLog(`Import skipped ${f.masked_key}: ${f.error}`, 'error'));
+        if (result.failed.length > 0) {
+          showError(`Imported ${result.imported}, ${result.failed.length} failed verification (see log)`);
+        } else {
+          showToast(`Imported ${result.imported} key${result.imported === 1 ? '' : 's'}`, 'success');
+        }
+      } catch (e) {
+        showError('Import failed: ' + e);
+      }
+    };
+  }
+
+  function renderEnvExportStatus(status) {
+    if (!envExportSnippets) return;
+    envExportSnippets.innerHTML = '';
+    envExportSnippets.classList.toggle('hidden', !status.enabled);
+    if (!status.enabled) return;
+
+    status.snippets.forEach(snippet => {
+      const label = document.createElement('div');
+      label.textContent = snippet.shell;
+      envExportSnippets.appendChild(label);
+
+      const code = document.createElement('code');
+      code.textContent = snippet.line;
+      code.title = 'Click to copy';
+      code.onclick = async () => {
+        try {
+          await invoke('copy_to_clipboard', { text: snippet.line });
+          showToast('Copied to clipboard', 'success');
+        } catch (e) {
+          showError(e);
+        }
+      };
+      envExportSnippets.appendChild(code);
+    });
+  }
+
+  async function loadEnvExportStatus() {
+    try {
+      renderEnvExportStatus(await invoke('get_env_export_status'));
+    } catch (e) {
+      console.error('Failed to load env export status:', e);
+    }
+  }
+
+  function appendCopyableCode(container, text) {
+    const code = document.createElement('code');
+    code.textContent = text;
+    code.title = 'Click to copy';
+    code.onclick = async () => {
+      try {
+        await invoke('copy_to_clipboard', { text });
+        showToast('Copied to clipboard', 'success');
+      } catch (e) {
+        showError(e);
+      }
+    };
+    container.appendChild(code);
+  }
+
+  function renderProxyStatus(status, spend) {
+    if (!proxyDetails) return;
+    proxyDetails.innerHTML = '';
+    proxyDetails.classList.toggle('hidden', !status.enabled);
+    if (!status.enabled) return;
+
+    const addLine = (text, className) => {
+      const line = document.createElement('div');
+      line.textContent = text;
+      if (className) line.className = className;
+      proxyDetails.appendChild(line);
+    };
+
+    if (status.error) {
+      addLine(status.error, 'proxy-error');
+      return;
+    }
+    addLine('Base URL (forwards with the active key)');
+    appendCopyableCode(proxyDetails, status.base_url);
+    addLine('Per project');
+    appendCopyableCode(proxyDetails, status.base_url.replace(/\/v1$/, '/p/<project>/v1'));
+    if (status.token) {
+      addLine('API key for your tools');
+      appendCopyableCode(proxyDetails, status.token);
+    }
+
+    if (spend && spend.total_requests > 0) {
+      addLine(`This month via proxy: ${formatCurrency(spend.total_cost)} · ${spend.total_requests} requests`);
+      const top = (totals) => totals.slice(0, 3).map(t => `${t.name} ${formatCurrency(t.cost)}`).join(', ');
+      addLine(`Clients: ${top(spend.by_client)}`);
+      addLine(`Projects: ${top(spend.by_project)}`);
+    }
+  }
+
+  function proxyGuardOverrideActive() {
+    const until = currentSettings?.proxy_guard_override_until;
+    return !!until && new Date(until) > new Date();
+  }
+
+  function renderProxyGuard() {
+    if (!proxyGuardSettings) return;
+    proxyGuardSettings.classList.toggle('hidden', currentSettings?.proxy_enabled !== true);
+    proxyGuardToggle.checked = currentSettings?.proxy_guard_enabled !== false;
+    proxyGuardPaceInput.value = currentSettings?.proxy_guard_max_pace_percent ?? 100;
+    proxyAllowedModelsInput.value = (currentSettings?.proxy_allowed_models || []).join(', ');
+    proxyProjectCapsInput.value = Object.entries(currentSettings?.proxy_project_caps || {})
+      .map(([project, cap]) => `${project}=${cap}`)
+      .join(', ');
+
+    const overridden = proxyGuardOverrideActive();
+    overrideProxyGuardBtn.textContent = overridden ? 'End override' : 'Override for 1 h';
+    proxyGuardOverrideInfo.textContent = overridden
+      ? `Guard off until ${new Date(currentSettings.proxy_guard_override_until).toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}`
+      : '';
+  }
+
+  /** "billing=20, website=5" → { billing: 20, website: 5 } */
+  function parseProjectCaps(text) {
+    const caps = {};
+    for (const part of text.split(',').map(p => p.trim()).filter(Boolean)) {
+      const [project, cap] = part.split('=').map(p => p.trim());
+      const value = parseFloat(cap);
+      if (!project || !Number.isFinite(value)) throw new Error(`Invalid project cap "${part}" (use project=amount)`);
+      caps[project] = value;
+    }
+    return caps;
+  }
+
+  async function saveProxyGuard() {
+    try {
+      currentSettings = await invoke('set_proxy_guard', {
+        enabled: proxyGuardToggle.checked,
+        maxPacePercent: parseFloat(proxyGuardPaceInput.value) || 0,
+        allowedModels: proxyAllowedModelsInput.value.split(',').map(m => m.trim()).filter(Boolean),
+        projectCaps: parseProjectCaps(proxyProjectCapsInput.value),
+      });
+      showToast('Budget guard saved', 'success');
+    } catch (e) {
+      showError('Budget guard: ' + (e.message || e));
+    }
+    renderProxyGuard();
+  }
+
+  async function toggleProxyGuardOverride() {
+    try {
+      currentSettings = await invoke('override_proxy_guard', { minutes: proxyGuardOverrideActive() ? 0 : 60 });
+    } catch (e) {
+      showError(e);
+    }
+    renderProxyGuard();
+  }
+
+  async function loadProxyStatus() {
+    renderProxyGuard();
+    try {
+      const status = await invoke('get_proxy_status');
+      const spend = status.enabled ? await invoke('get_proxy_spend') : null;
+      renderProxyStatus(status, spend);
+    } catch (e) {
+      console.error('Failed to load proxy status:', e);
+    }
+  }
+
+  // Validate the menubar text template in Rust and show what it renders to
+  async function updateTemplatePreview() {
+    if (!menubarTemplateInput || !menubarTemplatePreview) return true;
+    const template = menubarTemplateInput.value;
+    menubarTemplatePreview.classList.remove('error');
+    if (!template.trim()) {
+      menubarTemplatePreview.textContent = 'Empty: value, unit and timeframe as set above';
+      return true;
+    }
+    try {
+      const text = await invoke('preview_menubar_template', { template, balance: currentBalance || null });
+      menubarTemplatePreview.textContent = `Preview: ${text}`;
+      return true;
+    } catch (e) {
+      menubarTemplatePreview.textContent = String(e);
+      menubarTemplatePreview.classList.add('error');
+      return false;
+    }
+  }
+
+  async function applyProxySettings() {
+    const port = parseInt(proxyPortInput?.value, 10) || 8788;
+    try {
+      currentSettings = await invoke('set_proxy_enabled', { enabled: proxyToggle.checked, port });
+    } catch (e) {
+      proxyToggle.checked = currentSettings?.proxy_enabled === true;
+      showError('Proxy: ' + e);
+    }
+    if (proxyPortInput) proxyPortInput.value = currentSettings?.proxy_port || 8788;
+    loadProxyStatus();
+  }
+
+  function renderKeyInfo(balance) {
+    if (!keyInfoLine) return;
+    const parts = [];
+    if (balance?.is_free_tier) parts.push('<span class="free-tier">Free tier</span>');
+    if (balance?.limit_reset) parts.push(`Limit resets ${balance.limit_reset}`);
+    if (balance?.rate_limit?.requests > 0) {
+      parts.push(`Rate limit ${balance.rate_limit.requests} req / ${balance.rate_limit.interval}`);
+    }
+    keyInfoLine.innerHTML = parts.join(' · ');
+    keyInfoLine.classList.toggle('hidden', parts.length === 0);
+
+    const label = currentSettings?.api_keys?.[currentSettings.active_api_key_index]?.label;
+    if (balance?.is_free_tier && label && !freeTierWarned.has(label)) {
+      freeTierWarned.add(label);
+      showToast(`"${label}" is still on the free tier: low rate limits and free models only`, 'info', 6000);
+    }
+  }
+
+  function renderAccountCredits(balance) {
+    if (!accountCreditsItem) return;
+    const account = balance?.account;
+    accountCreditsItem.classList.toggle('hidden', !account);
+    if (!account) return;
+    accountCreditsValue.textContent = `${formatCurrency(account.remaining)} of ${formatCurrency(account.total_credits)}`;
+    const accountBinding = balance.binding_constraint === 'account';
+    accountCreditsItem.classList.toggle('binding', accountBinding);
+    accountCreditsItem.title = accountBinding
+      ? 'Account credits run out before this key\'s limit — pace and remaining use the account'
+      : 'Prepaid credits shared by all keys on this account';
+  }
+
   function showToast(message, type = 'info', timeout = 3500) {
     if (!errorDisplay) return;
     errorDisplay.textContent = message;
@@ -514,6 +989,47 @@ window.addEventListener('DOMContentLoaded', () => {
     }
   }
 
+  async function handlePushOpenCodeKey() {
+    const activeKey = currentSettings?.api_keys?.[currentSettings.active_api_key_index];
+    if (!activeKey?.key) {
+      showError('No active API key to write.');
+      return;
+    }
+
+    try {
+      const written = await invoke('sync_opencode_key');
+      // Read back to confirm OpenCode now sees the key
+      const readBack = await invoke('read_opencode_openrouter_key');
+      if (readBack !== activeKey.key) {
+        showError('OpenCode auth file did not pick up the key.');
+        return;
+      }
+      addLog(`OpenCode key set to: ${activeKey.label}`);
+      showToast(written ? `OpenCode now uses "${activeKey.label}".` : `OpenCode already uses "${activeKey.label}".`, 'success');
+    } catch (error) {
+      showError(error?.toString?.() || 'Failed to write OpenCode auth file.');
+    }
+  }
+
+  // OpenCode's key changed outside BP-ESC: offer to adopt it
+  function handleOpenCodeKeyChanged(change) {
+    const known = change.known_label ? ` ("${change.known_label}")` : '';
+    showConfirm(
+      `OpenCode switched to key ${change.masked_key}${known}. Use it in BP-ESC too?`,
+      async () => {
+        try {
+          currentSettings = await invoke('adopt_opencode_key');
+          renderApiKeyList();
+          showToast('Switched to the OpenCode key.', 'success');
+          loadBalance();
+        } catch (error) {
+          showError(error);
+        }
+      },
+      () => invoke('dismiss_opencode_key_change').catch(() => {})
+    );
+  }
+
   // More options context menu helpers
   function showMoreMenu(x, y) {
     // Update label to current active key
@@ -521,6 +1037,9 @@ window.addEventListener('DOMContentLoaded', () => {
     if (copyKeyLabel) {
       copyKeyLabel.textContent = activeKey?.label || '…';
     }
+    if (pushKeyLabel) {
+      pushKeyLabel.textContent = activeKey?.label || '…';
+    }
     
     moreMenu.style.left = 'auto';
     moreMenu.style.right = '16px';
@@ -616,21 +1135,25 @@ document.addEventListener('contextmenu', (e) => {
 // ---------------------------------------------------
 
   // Confirm dialog helper
-  function showConfirm(message, onConfirm) {
+  function showConfirm(message, onConfirm, onCancel = null) {
     confirmMessage.textContent = message;
     confirmCallback = onConfirm;
+    confirmCancelCallback = onCancel;
     confirmDialog.classList.remove('hidden');
   }
 
   confirmCancel.onclick = () => {
     confirmDialog.classList.add('hidden');
+    if (confirmCancelCallback) confirmCancelCallback();
     confirmCallback = null;
+    confirmCancelCallback = null;
   };
 
   confirmOk.onclick = () => {
     confirmDialog.classList.add('hidden');
     if (confirmCallback) confirmCallback();
     confirmCallback = null;
+    confirmCancelCallback = null;
   };
 
   // Helper function to check if settings are visible
@@ -641,10 +1164,41 @@ document.addEventListener('contextmenu', (e) => {
   // Auto-refresh is now handled by Rust backend timer
   // This ensures updates work even when window is hidden
 
-  // Format currency
-  function formatCurrency(value) {
+  // Number formatting mirrors number_format.rs (locale separators, 1.2k/3.4M, display currency)
+  function localizeNumber(text) {
+    const sign = text.startsWith('-') ? '-' : '';
+    const [int, frac] = text.replace('-', '').split('.');
+    const [group, point] = numberFormat?.decimal_comma ? ['.', ','] : [',', '.'];
+    const grouped = int.replace(/\B(?=(\d{3})+(?!\d))/g, group);
+    return sign + grouped + (frac !== undefined ? point + frac : '');
+  }
+
+  function formatNumber(value, decimals) {
+    if (numberFormat?.compact && Math.abs(value) >= 1000) {
+      const [scaled, suffix] = Math.abs(value) >= 999500 ? [value / 1e6, 'M'] : [value / 1e3, 'k'];
+      const text = scaled.toFixed(Math.abs(scaled) >= 100 ? 0 : 1);
+      return localizeNumber(text.endsWith('.0') ? text.slice(0, -2) : text) + suffix;
+    }
+    return localizeNumber(value.toFixed(decimals));
+  }
+
+  // Format a USD amount in the display currency
+  function formatCurrency(value, decimals = 2) {
     if (value === null || value === undefined) return '-';
-    return `$${value.toFixed(2)}`;
+    const amount = formatNumber(value * (numberFormat?.rate ?? 1), decimals);
+    const sign = amount.startsWith('-') ? '-' : '';
+    const digits = amount.replace('-', '');
+    const symbol = numberFormat?.symbol ?? '$';
+    if (numberFormat?.decimal_comma) return `${sign}${digits} ${symbol}`;
+    return symbol.length === 1 ? `${sign}${symbol}${digits}` : `${sign}${symbol} ${digits}`;
+  }
+
+  async function loadNumberFormat() {
+    try {
+      numberFormat = await invoke('get_number_format');
+    } catch (e) {
+      console.error('Failed to load number format:', e);
+    }
   }
 
   function animateHexagon(targetPct, paceRatio = null, paceStatus = null) {
@@ -693,6 +1247,13 @@ document.addEventListener('contextmenu', (e) => {
     return Math.min(7, Math.max(0, dayIndex + getDayProgress(date)));
   }
 
+  // Days in the key limit's reset period; keys without one are treated as monthly
+  function getPeriodDays(balance, date = new Date()) {
+    if (balance?.limit_reset === 'daily') return 1;
+    if (balance?.limit_reset === 'weekly') return 7;
+    return getMonthContext(date).daysInMonth;
+  }
+
   function getMonthContext(date = new Date()) {
     const year = date.getFullYear();
     const month = date.getMonth();
@@ -720,11 +1281,16 @@ document.addEventListener('contextmenu', (e) => {
     nextKeyBtn.style.display = hasMultipleKeys ? '' : 'none';
     
     // Check if we have valid balance data
-    const hasData = balance && balance.limit != null && (balance.remaining_monthly != null || balance.usage_monthly != null);
+    // Budget = key limit, or usage + account credits when the shared account runs out first
+    const budgetLimit = balance?.effective_limit ?? balance?.limit ?? null;
+    const hasData = balance && budgetLimit != null && (balance.remaining_monthly != null || balance.usage_monthly != null);
     const monthlyUsage = balance?.usage_monthly ?? balance?.usage ?? null;
-    const monthlyRemaining = balance?.remaining_monthly ?? (balance?.limit != null && monthlyUsage != null ? balance.limit - monthlyUsage : null);
+    // Budgets are per reset period of the limit (the month unless it resets daily/weekly)
+    const periodUsage = balance?.usage_period ?? monthlyUsage;
+    const periodRemaining = (budgetLimit != null && periodUsage != null) ? budgetLimit - periodUsage : (balance?.remaining_monthly ?? null);
+    const periodDays = getPeriodDays(balance);
     const paceRatio = balance?.pace_ratio ?? null;
-    const limitValueRaw = balance?.limit ?? null;
+    const limitValueRaw = budgetLimit;
     let paceMonthTarget = balance?.pace_month_target ?? null;
     let paceWeekTarget = balance?.pace_week_target ?? null;
     let paceDayTarget = balance?.pace_day_target ?? null;
@@ -735,32 +1301,23 @@ document.addEventListener('contextmenu', (e) => {
     
     // Calculate daily budget for weekly/daily calculations
     if (limitValueRaw > 0) {
-      const { daysInMonth } = getMonthContext();
-      dailyBudget = daysInMonth > 0 ? limitValueRaw / daysInMonth : 0;
+      dailyBudget = limitValueRaw / periodDays;
     }
+    // A timeframe spanning the whole reset period shows the period; shorter ones get a share
+    const { daysInMonth } = getMonthContext();
+    const spansPeriod = (days) => days >= periodDays;
+    const budgetFor = (days) => dailyBudget == null ? null : (spansPeriod(days) ? limitValueRaw : dailyBudget * days);
+    const usageFor = (days, usage) => spansPeriod(days) ? periodUsage : usage;
     
     // Get timeframe-specific values based on settings
     const timeframe = currentSettings?.menubar_timeframe || 'monthly';
-    let selectedUsage, selectedRemaining, selectedBudget, selectedPaceDelta;
-    
-    if (timeframe === 'weekly') {
-      const weeklyBudget = dailyBudget ? dailyBudget * 7 : limitValueRaw;
-      selectedUsage = balance?.usage_weekly ?? 0;
-      selectedRemaining = weeklyBudget - selectedUsage;
-      selectedBudget = weeklyBudget;
-      selectedPaceDelta = paceWeekDeltaPercent;
-    } else if (timeframe === 'daily') {
-      selectedUsage = balance?.usage_daily ?? 0;
-      selectedRemaining = dailyBudget - selectedUsage;
-      selectedBudget = dailyBudget;
-      selectedPaceDelta = paceDayDeltaPercent;
-    } else {
-      // monthly (default)
-      selectedUsage = monthlyUsage;
-      selectedRemaining = monthlyRemaining;
-      selectedBudget = limitValueRaw;
-      selectedPaceDelta = paceMonthDeltaPercent;
-    }
+    const timeframeDays = timeframe === 'weekly' ? 7 : timeframe === 'daily' ? 1 : daysInMonth;
+    const selectedUsage = usageFor(timeframeDays, timeframe === 'weekly' ? balance?.usage_weekly : balance?.usage_daily) ?? 0;
+    const selectedBudget = budgetFor(timeframeDays) ?? limitValueRaw;
+    const selectedRemaining = spansPeriod(timeframeDays) ? periodRemaining : selectedBudget - selectedUsage;
+    const selectedPaceDelta = timeframe === 'weekly' ? paceWeekDeltaPercent
+      : timeframe === 'daily' ? paceDayDeltaPercent
+      : paceMonthDeltaPercent;
     
     // Calculate percentage for hexagon based on selected timeframe
     const usageRatio = (hasData && selectedBudget > 0 && selectedUsage != null)
@@ -771,9 +1328,9 @@ document.addEventListener('contextmenu', (e) => {
       : 0;
     const rawPercentage = currentSettings?.show_remaining ? remainingRatio : usageRatio;
 
-    if (limitValueRaw > 0) {
-      const { daysInMonth, elapsedDays, dayFraction } = getMonthContext();
-      dailyBudget = daysInMonth > 0 ? limitValueRaw / daysInMonth : 0;
+    // Targets the backend left out; daily/weekly keys always get theirs from it
+    if (limitValueRaw > 0 && periodDays === daysInMonth) {
+      const { elapsedDays, dayFraction } = getMonthContext();
       if (paceMonthTarget == null || paceWeekTarget == null || paceDayTarget == null) {
         const weekElapsed = getWeekElapsedDays();
         if (paceMonthTarget == null) paceMonthTarget = dailyBudget * elapsedDays;
@@ -783,8 +1340,8 @@ document.addEventListener('contextmenu', (e) => {
     }
 
     if (limitValueRaw > 0) {
-      if (paceMonthDeltaPercent == null && paceMonthTarget != null && monthlyUsage != null && paceMonthTarget > 0) {
-        paceMonthDeltaPercent = ((monthlyUsage - paceMonthTarget) / paceMonthTarget) * 100;
+      if (paceMonthDeltaPercent == null && paceMonthTarget != null && periodUsage != null && paceMonthTarget > 0) {
+        paceMonthDeltaPercent = ((periodUsage - paceMonthTarget) / paceMonthTarget) * 100;
       }
       if (paceWeekDeltaPercent == null && paceWeekTarget != null && balance?.usage_weekly != null && paceWeekTarget > 0) {
         paceWeekDeltaPercent = ((balance.usage_weekly - paceWeekTarget) / paceWeekTarget) * 100;
@@ -800,14 +1357,16 @@ document.addEventListener('contextmenu', (e) => {
     const exactPercentEl = document.getElementById('exactPercent');
     if (exactPercentEl) {
       const decimals = Math.max(0, Math.min(2, currentSettings?.decimal_places ?? 1));
-      exactPercentEl.textContent = hasData ? rawPercentage.toFixed(decimals) : '-';
+      exactPercentEl.textContent = hasData ? formatNumber(rawPercentage, decimals) : '-';
     }
 
     // Update caption with timeframe context
     if (percentCaption) {
       const showRemaining = currentSettings?.show_remaining;
-      const timeframeText = timeframe === 'weekly' ? ' THIS WEEK' : 
-                            timeframe === 'daily' ? ' TODAY' : 
+      // Timeframes longer than the reset period show the period
+      const shownDays = Math.min(timeframeDays, periodDays);
+      const timeframeText = shownDays === 1 ? ' TODAY' :
+                            shownDays === 7 ? ' THIS WEEK' :
                             ' THIS MONTH';
       percentCaption.textContent = (showRemaining ? 'REMAINING' : 'USED') + timeframeText;
     }
@@ -831,9 +1390,15 @@ document.addEventListener('contextmenu', (e) => {
       }
     };
 
-    updateValue(limitValue, formatCurrency(hasData ? balance.limit : null));
-    updateValue(usageValue, formatCurrency(hasData ? monthlyUsage : null));
-    updateValue(remainingValue, formatCurrency(hasData ? monthlyRemaining : null));
+    // Keys that reset daily/weekly show their limit, usage and remaining for that period
+    const resetPeriod = balance?.limit_reset === 'daily' ? 'day' : balance?.limit_reset === 'weekly' ? 'week' : null;
+    const limitText = formatCurrency(hasData ? balance.limit : null);
+
+    updateValue(limitValue, (hasData && resetPeriod) ? `${limitText} / ${resetPeriod}` : limitText);
+    renderAccountCredits(balance);
+    renderKeyInfo(balance);
+    updateValue(usageValue, formatCurrency(hasData ? periodUsage : null));
+    updateValue(remainingValue, formatCurrency(hasData ? periodRemaining : null));
     updateValue(usageMonthValue, formatCurrency(hasData ? monthlyUsage : null));
     updateValue(usageWeekValue, formatCurrency(hasData ? balance.usage_weekly : null));
     updateValue(usageDayValue, formatCurrency(hasData ? balance.usage_daily : null));
@@ -874,12 +1439,12 @@ document.addEventListener('contextmenu', (e) => {
         notch.style.display = '';
         const targetLeft = Math.round(notchRatio * maxBarWidth);
         notch.style.left = `${targetLeft}px`;
-        notchLabel.textContent = target == null ? '-' : `$${target.toFixed(2)}`;
+        notchLabel.textContent = formatCurrency(target);
       }
 
       // Update timeframe label with clip-path for color transition
       if (timeframeLabelGray && timeframeLabelWhite && budgetValue != null) {
-        const labelText = `$${budgetValue.toFixed(2)}`;
+        const labelText = formatCurrency(budgetValue);
         timeframeLabelGray.textContent = labelText;
         timeframeLabelWhite.textContent = labelText;
         
@@ -900,9 +1465,9 @@ document.addEventListener('contextmenu', (e) => {
       }
     };
 
-    const monthBudget = limitValueRaw > 0 ? limitValueRaw : null;
-    const weekBudget = dailyBudget != null ? dailyBudget * 7 : null;
-    const dayBudget = dailyBudget != null ? dailyBudget : null;
+    const monthBudget = budgetFor(daysInMonth);
+    const weekBudget = budgetFor(7);
+    const dayBudget = budgetFor(1);
 
     updatePaceBar(
       usageMonthBar,
@@ -912,7 +1477,7 @@ document.addEventListener('contextmenu', (e) => {
       usageMonthPaceValue,
       paceMonthDeltaPercent,
       paceMonthTarget,
-      monthlyUsage,
+      usageFor(daysInMonth, monthlyUsage),
       monthBudget,
       usageMonthTimeframeLabelGray,
       usageMonthTimeframeLabelWhite,
@@ -926,7 +1491,7 @@ document.addEventListener('contextmenu', (e) => {
       usageWeekPaceValue,
       paceWeekDeltaPercent,
       paceWeekTarget,
-      balance?.usage_weekly ?? null,
+      usageFor(7, balance?.usage_weekly ?? null),
       weekBudget,
       usageWeekTimeframeLabelGray,
       usageWeekTimeframeLabelWhite,
@@ -940,7 +1505,7 @@ document.addEventListener('contextmenu', (e) => {
       usageDayPaceValue,
       paceDayDeltaPercent,
       paceDayTarget,
-      balance?.usage_daily ?? null,
+      usageFor(1, balance?.usage_daily ?? null),
       dayBudget,
       usageDayTimeframeLabelGray,
       usageDayTimeframeLabelWhite,
@@ -1077,6 +1642,7 @@ document.addEventListener('contextmenu', (e) => {
       const balance = await invoke('fetch_balance', { apiKey: activeKey });
       addLog('Balance fetch successful');
       await displayBalance(balance);
+      loadModelBreakdown(); // fire and forget, independent of the key balance
     } catch (error) {
       addLog(`Balance fetch failed: ${error}`, 'error');
       await resetBalanceDisplay();
@@ -1086,6 +1652,45 @@ document.addEventListener('contextmenu', (e) => {
     }
   }
 
+  async function loadModelBreakdown() {
+    if (!modelBreakdown) return;
+    if (!currentSettings?.provisioning_key) {
+      modelBreakdown.classList.add('hidden');
+      return;
+    }
+
+    const timeframe = currentSettings.menubar_timeframe || 'monthly';
+    try {
+      const breakdown = await invoke('get_model_breakdown', { timeframe, top: 3 });
+      // Activity covers completed UTC days only, so the ranges end yesterday
+      const thisMonth = new Date().toISOString().slice(0, 7);
+      modelBreakdownTitle.textContent = timeframe === 'daily' ? 'Top models yesterday'
+        : timeframe === 'weekly' ? 'Top models, last 7 days'
+        : breakdown.from.startsWith(thisMonth) ? 'Top models this month' : 'Top models last month';
+      modelBreakdownRows.innerHTML = '';
+      breakdown.models.forEach(model => {
+        const row = document.createElement('div');
+        row.className = 'model-row';
+        const name = document.createElement('span');
+        name.className = 'model-row-name';
+        name.textContent = model.model;
+        name.title = model.model;
+        const usage = document.createElement('span');
+        usage.className = 'model-row-usage';
+        usage.textContent = formatCurrency(model.usage);
+        const requests = document.createElement('span');
+        requests.className = 'model-row-requests';
+        requests.textContent = `${model.requests} req`;
+        row.append(name, usage, requests);
+        modelBreakdownRows.appendChild(row);
+      });
+      modelBreakdown.classList.toggle('hidden', breakdown.models.length === 0);
+    } catch (e) {
+      modelBreakdown.classList.add('hidden');
+      addLog(`Model breakdown unavailable: ${e}`, 'warn');
+    }
+  }
+
   // Settings UI Sync
   function syncSettingsToUI() {
     if (!currentSettings) return;
@@ -1100,6 +1705,21 @@ document.addEventListener('contextmenu', (e) => {
     startWindowToggle.checked = currentSettings.show_window_on_start;
     launchAtLoginToggle.checked = currentSettings.launch_at_login;
     alwaysOnTopToggle.checked = currentSettings.always_on_top;
+    if (opencodeSyncToggle) {
+      opencodeSyncToggle.checked = currentSettings.opencode_sync_enabled === true;
+    }
+    if (envExportToggle) {
+      envExportToggle.checked = currentSettings.env_export_enabled === true;
+      loadEnvExportStatus();
+    }
+    if (proxyToggle) {
+      proxyToggle.checked = currentSettings.proxy_enabled === true;
+      if (proxyPortInput) proxyPortInput.value = currentSettings.proxy_port || 8788;
+      loadProxyStatus();
+    }
+    if (provisioningKeyInput) {
+      provisioningKeyInput.value = currentSettings.provisioning_key || '';
+    }
     unfocusedOverlayToggle.checked = currentSettings.unfocused_overlay;
     decimalValue.textContent = currentSettings.decimal_places || 0;
     if (menubarMonochromeToggle) {
@@ -1141,6 +1761,24 @@ document.addEventListener('contextmenu', (e) => {
     timeframeWeekly.classList.toggle('active', timeframe === 'weekly');
     timeframeDaily.classList.toggle('active', timeframe === 'daily');
 
+    const iconStyle = currentSettings.icon_style || 'hexagon';
+    iconStyleButtons.forEach(btn => btn.classList.toggle('active', btn.dataset.iconStyle === iconStyle));
+    if (paceMarkerToggle) {
+      paceMarkerToggle.checked = currentSettings.show_pace_marker === true;
+      paceMarkerToggle.disabled = iconStyle !== 'hexagon';
+    }
+    if (menubarTemplateInput && document.activeElement !== menubarTemplateInput) {
+      menubarTemplateInput.value = currentSettings.menubar_template || '';
+      updateTemplatePreview();
+    }
+    const numberLocale = currentSettings.number_locale || 'auto';
+    numberLocaleButtons.forEach(btn => btn.classList.toggle('active', btn.dataset.numberLocale === numberLocale));
+    const language = currentSettings.language || 'auto';
+    languageButtons.forEach(btn => btn.classList.toggle('active', btn.dataset.language === language));
+    if (compactNumbersToggle) compactNumbersToggle.checked = currentSettings.compact_numbers === true;
+    if (displayCurrencyInput) displayCurrencyInput.value = currentSettings.display_currency || 'USD';
+    if (exchangeRateInput) exchangeRateInput.value = currentSettings.exchange_rate ?? 1;
+
     // Mood settings
     syncMoodSettingsToUI();
   }
@@ -1164,6 +1802,8 @@ document.addEventListener('contextmenu', (e) => {
       show_window_on_start: startWindowToggle.checked,
       launch_at_login: launchAtLoginToggle.checked,
       always_on_top: alwaysOnTopToggle.checked,
+      opencode_sync_enabled: opencodeSyncToggle ? opencodeSyncToggle.checked : (currentSettings?.opencode_sync_enabled ?? false),
+      env_export_enabled: envExportToggle ? envExportToggle.checked : (currentSettings?.env_export_enabled ?? false),
       unfocused_overlay: unfocusedOverlayToggle.checked,
       decimal_places: parseInt(decimalValue.textContent),
       pace_warn_threshold: paceWarnValue ? parseFloat(paceWarnValue.textContent) : (currentSettings?.pace_warn_threshold ?? 20),
@@ -1176,6 +1816,13 @@ document.addEventListener('contextmenu', (e) => {
       menubar_timeframe: timeframeMonthly.classList.contains('active') ? 'monthly' :
                          timeframeWeekly.classList.contains('active') ? 'weekly' :
                          timeframeDaily.classList.contains('active') ? 'daily' : 'monthly',
+      icon_style: document.querySelector('#iconStyleGroup .btn-toggle.active')?.dataset.iconStyle || 'hexagon',
+      show_pace_marker: paceMarkerToggle ? paceMarkerToggle.checked : (currentSettings?.show_pace_marker ?? false),
+      number_locale: document.querySelector('#numberLocaleGroup .btn-toggle.active')?.dataset.numberLocale || 'auto',
+      language: document.querySelector('#languageGroup .btn-toggle.active')?.dataset.language || 'auto',
+      compact_numbers: compactNumbersToggle ? compactNumbersToggle.checked : (currentSettings?.compact_numbers ?? false),
+      display_currency: displayCurrencyInput ? (displayCurrencyInput.value.trim().toUpperCase() || 'USD') : (currentSettings?.display_currency ?? 'USD'),
+      exchange_rate: exchangeRateInput ? (parseFloat(exchangeRateInput.value) || 0) : (currentSettings?.exchange_rate ?? 1),
     };
 
     const resetHexAnimation = currentSettings?.show_remaining !== newSettings.show_remaining ||
@@ -1198,6 +1845,7 @@ document.addEventListener('contextmenu', (e) => {
       }
 
       currentSettings = newSettings;
+      await loadNumberFormat();
       applyDebugMode(newSettings.debugging_enabled);
       if (resetHexAnimation) {
         currentAnimatedPct = 0;
@@ -1272,6 +1920,16 @@ document.addEventListener('contextmenu', (e) => {
     await saveSettingsAction(true);
     if (currentBalance) displayBalance(currentBalance, false);
   };
+
+  // Menubar icon style buttons
+  iconStyleButtons.forEach(btn => {
+    btn.onclick = async () => {
+      iconStyleButtons.forEach(other => other.classList.toggle('active', other === btn));
+      if (paceMarkerToggle) paceMarkerToggle.disabled = btn.dataset.iconStyle !== 'hexagon';
+      await saveSettingsAction(true);
+      if (currentBalance) displayBalance(currentBalance, false);
+    };
+  });
   
   // Click on "remaining/used" caption toggles the setting
   percentCaption.onclick = async () => {
@@ -1298,6 +1956,88 @@ document.addEventListener('contextmenu', (e) => {
   startWindowToggle.onchange = () => saveSettingsAction(true);
   launchAtLoginToggle.onchange = () => saveSettingsAction(true);
   alwaysOnTopToggle.onchange = () => saveSettingsAction(true);
+  if (menubarTemplateInput) {
+    menubarTemplateInput.oninput = () => updateTemplatePreview();
+    menubarTemplateInput.onchange = async () => {
+      if (!(await updateTemplatePreview())) return;
+      currentSettings = { ...currentSettings, menubar_template: menubarTemplateInput.value };
+      await saveSettingsAction(true);
+      if (currentBalance) displayBalance(currentBalance, false);
+    };
+  }
+  if (paceMarkerToggle) {
+    paceMarkerToggle.onchange = async () => {
+      await saveSettingsAction(true);
+      if (currentBalance) displayBalance(currentBalance, false);
+    };
+  }
+  numberLocaleButtons.forEach(btn => {
+    btn.onclick = async () => {
+      numberLocaleButtons.forEach(other => other.classList.toggle('active', other === btn));
+      await saveSettingsAction(true);
+    };
+  });
+  languageButtons.forEach(btn => {
+    btn.onclick = async () => {
+      languageButtons.forEach(other => other.classList.toggle('active', other === btn));
+      await saveSettingsAction(true);
+    };
+  });
+  if (compactNumbersToggle) {
+    compactNumbersToggle.onchange = () => saveSettingsAction(true);
+  }
+  // Not silent: an unknown currency or missing rate is rejected by save_settings
+  if (displayCurrencyInput) {
+    displayCurrencyInput.onchange = () => saveSettingsAction();
+  }
+  if (exchangeRateInput) {
+    exchangeRateInput.onchange = () => saveSettingsAction();
+  }
+  if (opencodeSyncToggle) {
+    opencodeSyncToggle.onchange = () => saveSettingsAction(true);
+  }
+  if (saveProvisioningKeyBtn) {
+    saveProvisioningKeyBtn.onclick = async () => {
+      const key = provisioningKeyInput.value.trim();
+      try {
+        currentSettings = await invoke('set_provisioning_key', { key });
+        showToast(key ? 'Provisioning key verified and saved' : 'Provisioning key removed', 'success');
+        loadModelBreakdown();
+      } catch (e) {
+        showError(e);
+      }
+    };
+  }
+  if (envExportToggle) {
+    envExportToggle.onchange = async () => {
+      if (!envExportToggle.checked) {
+        await saveSettingsAction(true);
+        loadEnvExportStatus();
+        return;
+      }
+      try {
+        const status = await invoke('export_active_key_env');
+        currentSettings.env_export_enabled = true;
+        renderEnvExportStatus(status);
+        showToast(`Exported key to ${status.env_path}`, 'success');
+      } catch (e) {
+        envExportToggle.checked = false;
+        showError('Env export failed: ' + e);
+      }
+    };
+  }
+  if (proxyToggle) {
+    proxyToggle.onchange = applyProxySettings;
+  }
+  if (saveProxyGuardBtn) {
+    saveProxyGuardBtn.onclick = saveProxyGuard;
+    overrideProxyGuardBtn.onclick = toggleProxyGuardOverride;
+  }
+  if (proxyPortInput) {
+    proxyPortInput.onchange = () => {
+      if (proxyToggle?.checked) applyProxySettings();
+    };
+  }
   unfocusedOverlayToggle.onchange = () => {
     saveSettingsAction(true);
     if (!unfocusedOverlayToggle.checked) {
@@ -1381,6 +2121,7 @@ document.addEventListener('contextmenu', (e) => {
         hideMoreMenu();
         const action = item.getAttribute('data-action');
         if (action === 'extract') handleExtractOpenCodeKey();
+        else if (action === 'push') handlePushOpenCodeKey();
         else if (action === 'copy') handleCopyToClipboard();
       };
     });
@@ -1455,8 +2196,10 @@ document.addEventListener('contextmenu', (e) => {
 
     const balance = currentBalance;
     const monthlyUsage = balance?.usage_monthly ?? balance?.usage ?? null;
-    const monthlyRemaining = balance?.remaining_monthly ?? (balance?.limit != null && monthlyUsage != null ? balance.limit - monthlyUsage : null);
-    const limitValueRaw = balance?.limit ?? null;
+    const periodUsage = balance?.usage_period ?? monthlyUsage;
+    const limitValueRaw = balance?.effective_limit ?? balance?.limit ?? null;
+    const monthlyRemaining = (limitValueRaw != null && periodUsage != null) ? limitValueRaw - periodUsage : (balance?.remaining_monthly ?? null);
+    const periodDays = getPeriodDays(balance);
     
     let dailyBudget = null;
     let paceMonthTarget = balance?.pace_month_target ?? null;
@@ -1464,18 +2207,19 @@ document.addEventListener('contextmenu', (e) => {
     let paceDayTarget = balance?.pace_day_target ?? null;
     
     if (limitValueRaw > 0) {
-      const { daysInMonth, elapsedDays, dayFraction } = getMonthContext();
-      dailyBudget = daysInMonth > 0 ? limitValueRaw / daysInMonth : 0;
+      const { elapsedDays, dayFraction } = getMonthContext();
+      dailyBudget = limitValueRaw / periodDays;
       if (paceMonthTarget == null) paceMonthTarget = dailyBudget * elapsedDays;
       if (paceWeekTarget == null) paceWeekTarget = dailyBudget * getWeekElapsedDays();
       if (paceDayTarget == null) paceDayTarget = dailyBudget * dayFraction;
     }
 
-    const monthBudget = limitValueRaw;
-    const weekBudget = dailyBudget != null ? dailyBudget * 7 : null;
-    const dayBudget = dailyBudget;
+    const budgetFor = (days) => dailyBudget == null ? null : (days >= periodDays ? limitValueRaw : dailyBudget * days);
+    const monthBudget = budgetFor(getMonthContext().daysInMonth);
+    const weekBudget = budgetFor(7);
+    const dayBudget = budgetFor(1);
 
-    const usageRatio = (limitValueRaw > 0 && monthlyUsage != null) ? (monthlyUsage / limitValueRaw) * 100 : 0;
+    const usageRatio = (limitValueRaw > 0 && periodUsage != null) ? (periodUsage / limitValueRaw) * 100 : 0;
     const remainingRatio = (limitValueRaw > 0 && monthlyRemaining != null) ? (monthlyRemaining / limitValueRaw) * 100 : 0;
 
     addLog('=== BALANCE DEBUG INFO ===');
@@ -1487,6 +2231,8 @@ document.addEventListener('contextmenu', (e) => {
     addLog(`Usage Daily: ${balance?.usage_daily ?? 'null'}`);
     addLog(`Remaining (legacy): ${balance?.remaining ?? 'null'}`);
     addLog(`Remaining Monthly: ${balance?.remaining_monthly ?? 'null'}`);
+    addLog(`Usage Period: ${balance?.usage_period ?? 'null'} (reset: ${balance?.limit_reset ?? 'none'}, ${periodDays} days)`);
+    addLog(`Account Credits: ${balance?.account ? `${balance.account.remaining} of ${balance.account.total_credits}` : 'n/a'} (binding: ${balance?.binding_constraint ?? 'n/a'})`);
     addLog(`Pace Ratio: ${balance?.pace_ratio ?? 'null'}`);
     
     addLog('--- CALCULATED VALUES ---');
@@ -1521,7 +2267,7 @@ document.addEventListener('contextmenu', (e) => {
     addLog(`Week Elapsed Days: ${getWeekElapsedDays().toFixed(3)}`);
     
     if (dailyBudget) {
-      addLog(`Daily Budget Calc: ${limitValueRaw} / ${daysInMonth} = ${dailyBudget.toFixed(4)}`);
+      addLog(`Daily Budget Calc: ${limitValueRaw} / ${periodDays} = ${dailyBudget.toFixed(4)}`);
       addLog(`Month Target Calc: ${dailyBudget.toFixed(4)} * ${elapsedDays.toFixed(3)} = ${(dailyBudget * elapsedDays).toFixed(4)}`);
       addLog(`Week Target Calc: ${dailyBudget.toFixed(4)} * ${getWeekElapsedDays().toFixed(3)} = ${(dailyBudget * getWeekElapsedDays()).toFixed(4)}`);
       addLog(`Day Target Calc: ${dailyBudget.toFixed(4)} * ${dayFraction.toFixed(3)} = ${(dailyBudget * dayFraction).toFixed(4)}`);
@@ -1634,6 +2380,31 @@ document.addEventListener('contextmenu', (e) => {
     }
   }
 
+  // Settings changed outside the window (tray, pinned icons, proxy, CLI): take them over,
+  // so the next save from here doesn't write a stale copy back
+  async function adoptSettings(settings) {
+    if (!settings) return;
+    currentSettings = settings;
+    await loadNumberFormat();
+    syncSettingsToUI();
+  }
+
+  async function setupSettingsListeners() {
+    try {
+      await window.__TAURI__.event.listen('settings-updated', async (event) => {
+        await adoptSettings(event.payload);
+        loadBalance();
+      });
+      // Emitted when the window is shown from the tray; the CLI may have changed the file
+      await window.__TAURI__.event.listen('refresh-balance', async () => {
+        await adoptSettings(await invoke('read_settings'));
+        loadBalance();
+      });
+    } catch (error) {
+      console.error('Failed to setup settings listeners:', error);
+    }
+  }
+
   // Setup event listener for auto-refresh from Rust backend
   async function setupRustAutoRefreshListener() {
     try {
@@ -1683,9 +2454,21 @@ document.addEventListener('contextmenu', (e) => {
     try {
       const focusLabel = document.getElementById('focusValue');
       
-      window.addEventListener('focus', () => {
+      window.addEventListener('focus', async () => {
         document.body.classList.remove('unfocused');
         if (focusLabel) focusLabel.textContent = 'FOCUSED';
+        // Pick up CLI changes (e.g. the project cd hook) before anything is saved from here
+        try {
+          const stored = await invoke('read_settings');
+          if (JSON.stringify(stored) !== JSON.stringify(currentSettings)) {
+            const keyChanged = stored.api_keys[stored.active_api_key_index]?.key
+              !== currentSettings?.api_keys[currentSettings.active_api_key_index]?.key;
+            await adoptSettings(stored);
+            if (keyChanged) loadBalance();
+          }
+        } catch (e) {
+          console.error('Failed to re-read settings:', e);
+        }
       });
 
       window.addEventListener('blur', () => {
@@ -1754,6 +2537,7 @@ document.addEventListener('contextmenu', (e) => {
       const version = await invoke('get_app_version');
       appVersion.textContent = `v${version}`;
       currentSettings = await invoke('read_settings');
+      await loadNumberFormat();
         syncSettingsToUI(); // Always sync UI after loading settings
 
       // Setup focus listeners
@@ -1765,9 +2549,18 @@ document.addEventListener('contextmenu', (e) => {
       // Setup Rust auto-refresh listener (works even when window hidden)
       await setupRustAutoRefreshListener();
 
+      // Keep currentSettings in step with changes made outside the window
+      await setupSettingsListeners();
+
       // Navigate to a specific tab when Rust emits navigate-tab
       await window.__TAURI__.event.listen('navigate-tab', (event) => {
         const target = event.payload;
+        if (target === 'settings-openrouter') {
+          syncSettingsToUI();
+          showState('settings');
+          setSettingsSubTab('openrouter');
+          return;
+        }
         const tab = document.querySelector(`.nav-tab[data-tab="${target}"]`);
         if (tab) {
           tabs.forEach(t => t.classList.remove('active'));
@@ -1782,6 +2575,38 @@ document.addEventListener('contextmenu', (e) => {
         }
       });
       
+      // Key rotation reminders (emitted at most once per day by the refresh timer)
+      await window.__TAURI__.event.listen('key-rotation-due', (event) => {
+        keyRotationReminders = event.payload || [];
+        renderApiKeyList();
+        const first = keyRotationReminders[0];
+        if (first) {
+          const when = first.days_left < 0 ? 'is overdue' : `is due ${first.due_date}`;
+          showToast(`Rotation for key "${first.label}" ${when}.`, 'info', 6000);
+        }
+      });
+      loadKeyRotationReminders();
+
+      // A request went through the metering proxy: show its cost before the next refresh
+      await window.__TAURI__.event.listen('proxy-usage', (event) => {
+        const record = event.payload?.record;
+        if (record) addLog(`Proxy: ${record.client}${record.project ? ' / ' + record.project : ''} ${record.model || ''} $${record.cost.toFixed(4)}`);
+        if (event.payload?.balance) displayBalance(event.payload.balance);
+        if (proxyDetails && !proxyDetails.classList.contains('hidden')) loadProxyStatus();
+      });
+
+      // The budget guard rejected a request
+      await window.__TAURI__.event.listen('proxy-blocked', (event) => {
+        const { request, reason } = event.payload || {};
+        addLog(`Proxy blocked ${request?.client || 'request'}: ${reason}`);
+        showToast(`Blocked ${request?.client || 'request'}: ${reason}`, 'error', 6000);
+      });
+
+      // OpenCode's auth.json changed outside BP-ESC (only with sync enabled)
+      await window.__TAURI__.event.listen('opencode-key-changed', (event) => {
+        if (event.payload) handleOpenCodeKeyChanged(event.payload);
+      });
+
       // Setup update checker (macOS only)
       setupUpdateChecker();
       